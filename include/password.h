@@ -0,0 +1,59 @@
+#ifndef PASSWORD_GENERATOR_H
+#define PASSWORD_GENERATOR_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of a call into the library
+typedef enum PgStatus {
+  PG_STATUS_OK = 0,
+  // A required pointer argument was NULL
+  PG_STATUS_NULL_POINTER = 1,
+  // The options can't produce a password (e.g. a length of 0, or extended symbols without symbols)
+  PG_STATUS_INVALID_OPTIONS = 2,
+  // The operating system's random number generator could not be read
+  PG_STATUS_RNG_FAILURE = 3,
+} PgStatus;
+
+// What to generate. Start from `pg_default_options()` and change the fields you need.
+typedef struct PgOptions {
+  uint16_t length;
+  bool symbols;
+  bool extended_symbols;
+  bool allow_space;
+  bool exclude_ambiguous;
+  // Generate a passphrase of this many words instead (0 for a password)
+  uint16_t words;
+  // Generate a pronounceable password of `length` characters instead
+  bool pronounceable;
+} PgOptions;
+
+// The defaults used by the command-line tool: 36 characters with letters,
+// digits and symbols
+struct PgOptions pg_default_options(void);
+
+// Generate a password and store a NUL-terminated UTF-8 copy in `*out`.
+//
+// On failure `*out` is set to NULL.
+//
+// # Safety
+//
+// `options` must point to a valid `PgOptions` and `out` to writable storage
+// for a pointer. The string written to `*out` must be released with
+// `pg_free()`.
+enum PgStatus pg_generate(const struct PgOptions *options, char **out);
+
+// Overwrite and free a password returned by `pg_generate()`. NULL is ignored.
+//
+// # Safety
+//
+// `password` must be NULL or a pointer returned by `pg_generate()` that
+// hasn't been freed yet.
+void pg_free(char *password);
+
+#endif  /* PASSWORD_GENERATOR_H */
@@ -0,0 +1,154 @@
+//! Acrostic passwords (`password acrostic`): the first letter of each word of
+//! a sentence the user can remember, with the casing, look-alike
+//! substitutions and separators chosen by the secure RNG.
+//!
+//! `My dog Rex loves long walks` might give `m7D!R#1$L.w`. Each letter is
+//! written in one of its forms (lower, upper or a leetspeak substitute) and
+//! one digit or symbol is placed between every pair. Letters and separators
+//! alternate, so every choice is visible in the output.
+//!
+//! The sentence is assumed known to an attacker, since memorable sentences
+//! are quotes, lyrics and sayings that can be guessed. Only the random
+//! choices count towards the entropy, which is why it is far below that of a
+//! random password of the same length.
+
+use rand::Rng;
+use zeroize::Zeroizing;
+
+use crate::{leet, PasswordError, Secret, CHARS_SYMBOLS};
+
+/// Fewest words a sentence needs
+pub const MIN_WORDS: usize = 4;
+
+/// Most words a sentence can have
+pub const MAX_WORDS: usize = 64;
+
+/// The letters of a sentence an acrostic is built from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Acrostic {
+    letters: Zeroizing<Vec<char>>,
+}
+
+impl Acrostic {
+    /// Take the first letter or digit of every word of `sentence`. Words with
+    /// neither (`-`, `&`) are skipped.
+    pub fn new(sentence: &str) -> Result<Self, PasswordError> {
+        let mut letters = Zeroizing::new(Vec::new());
+        letters.extend(sentence.split_whitespace().filter_map(|word| word.chars().find(|c| c.is_alphanumeric())));
+
+        if letters.len() < MIN_WORDS {
+            return Err(PasswordError::AcrosticSentence(format!(
+                "it has {} words, and at least {} are needed", letters.len(), MIN_WORDS
+            )));
+        }
+        if letters.len() > MAX_WORDS {
+            return Err(PasswordError::AcrosticSentence(format!(
+                "it has {} words, and at most {} are allowed", letters.len(), MAX_WORDS
+            )));
+        }
+
+        Ok(Acrostic { letters })
+    }
+
+    /// Number of letters taken from the sentence
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Secret {
+        let separators = separators();
+        let mut password = Secret::new(String::with_capacity(self.letters.len() * 2));
+
+        for (i, letter) in self.letters.iter().enumerate() {
+            if i > 0 {
+                password.push(separators[rng.gen_range(0..separators.len())]);
+            }
+            let forms = forms(*letter);
+            password.push(forms[rng.gen_range(0..forms.len())]);
+        }
+
+        password
+    }
+
+    /// Bits of entropy from the random choices alone
+    pub fn entropy(&self) -> f64 {
+        let letters: f64 = self.letters.iter().map(|letter| (forms(*letter).len() as f64).log2()).sum();
+        let gaps = self.letters.len().saturating_sub(1) as f64;
+        letters + gaps * (separators().len() as f64).log2()
+    }
+}
+
+/// Every way `letter` can be written: lower and upper case, then its
+/// leetspeak substitutes. A digit is kept as it is.
+fn forms(letter: char) -> Vec<char> {
+    let mut forms = Vec::with_capacity(4);
+    let cases: [Vec<char>; 2] = [letter.to_lowercase().collect(), letter.to_uppercase().collect()];
+    for form in cases {
+        // Letters that change length with their case (ß → SS) keep their own form
+        let form = if form.len() == 1 { form[0] } else { letter };
+        if !forms.contains(&form) {
+            forms.push(form);
+        }
+    }
+    forms.extend(leet::substitutes(letter));
+    forms
+}
+
+/// Characters placed between the letters: digits and symbols
+fn separators() -> Vec<char> {
+    ('0'..='9').chain(CHARS_SYMBOLS.iter().copied()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_letters() {
+        let acrostic = Acrostic::new("My dog Rex - loves \"long\" walks").unwrap();
+        assert_eq!(*acrostic.letters, ['M', 'd', 'R', 'l', 'l', 'w']);
+
+        let acrostic = Acrostic::new("  Only 2 words? no, five  ").unwrap();
+        assert_eq!(acrostic.len(), 5);
+
+        assert!(matches!(Acrostic::new("too short here"), Err(PasswordError::AcrosticSentence(_))));
+        assert!(Acrostic::new(&"word ".repeat(MAX_WORDS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_generate() {
+        let acrostic = Acrostic::new("My dog Rex loves long walks").unwrap();
+        let separators = separators();
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..20 {
+            let password: Vec<char> = acrostic.generate(&mut rng).chars().collect();
+            assert_eq!(password.len(), 11);
+            for (i, c) in password.iter().enumerate() {
+                if i % 2 == 1 {
+                    assert!(separators.contains(c));
+                } else {
+                    assert!(forms(acrostic.letters[i / 2]).contains(c), "{}", c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(forms('m'), ['m', 'M']);
+        assert_eq!(forms('A'), ['a', 'A', '@', '4']);
+        assert_eq!(forms('7'), ['7']);
+        assert_eq!(forms('ß'), ['ß']);
+
+        // m, d and w have two forms, s and a four; 4 gaps of 36 separators
+        let acrostic = Acrostic::new("many dogs sleep with abandon").unwrap();
+        let expected = 3.0 + 2.0 * 2.0 + 4.0 * 36f64.log2();
+        assert!((acrostic.entropy() - expected).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,157 @@
+//! Audit an existing password: what it is made of, how much entropy a
+//! brute-force attacker faces, and which guessable patterns it contains.
+
+use crate::keyboard::{has_keyboard_walk, Layout, DEFAULT_WALK_RUN};
+use crate::policy::has_sequential_run;
+use crate::strength::{self, Strength};
+use crate::grapheme_len;
+
+/// Passwords shorter than this are reported as short
+pub const MIN_RECOMMENDED_LENGTH: usize = 12;
+
+/// Shortest run of one repeated character that is reported
+const REPEAT_RUN: usize = 3;
+
+/// Shortest ascending or descending run that is reported
+const SEQUENTIAL_RUN: u16 = 3;
+
+/// Name of a class, whether a character belongs to it, and how many
+/// characters it adds to the pool
+type Class = (&'static str, fn(char) -> bool, usize);
+
+/// The character classes a brute-force attacker would have to cover
+const CLASSES: [Class; 5] = [
+    ("lowercase", |c| c.is_ascii_lowercase(), 26),
+    ("uppercase", |c| c.is_ascii_uppercase(), 26),
+    ("digits", |c| c.is_ascii_digit(), 10),
+    ("symbols", |c| c.is_ascii_punctuation(), 32),
+    ("space", |c| c == ' ', 1),
+];
+
+/// What [`analyze`] found
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    /// Length in user-perceived characters
+    pub length: usize,
+    /// Names of the classes present, plus `other` for anything outside ASCII
+    pub classes: Vec<&'static str>,
+    /// Size of the pool those classes imply
+    pub pool_size: usize,
+    /// `length × log2(pool_size)`: the most the password can be worth, if
+    /// every character had been drawn at random from the pool
+    pub charset_entropy: f64,
+    /// zxcvbn's estimate, which accounts for words and patterns
+    pub strength: Strength,
+    /// Guessable patterns, one sentence each
+    pub findings: Vec<String>,
+}
+
+impl Analysis {
+    /// Bits of entropy to plan with: the lower of the charset bound and
+    /// zxcvbn's guess count
+    pub fn estimated_entropy(&self) -> f64 {
+        self.charset_entropy.min(self.strength.guesses_log10 * 10f64.log2())
+    }
+}
+
+/// Whether `password` has `run` or more copies of one character in a row
+fn has_repeat_run(password: &str, run: usize) -> bool {
+    let mut length = 0;
+    let mut previous = None;
+    for c in password.chars() {
+        length = if previous == Some(c) { length + 1 } else { 1 };
+        if length >= run {
+            return true;
+        }
+        previous = Some(c);
+    }
+    false
+}
+
+/// Analyze `password`, looking for keyboard walks on `layout`
+pub fn analyze(password: &str, layout: Layout) -> Analysis {
+    let mut classes = Vec::new();
+    let mut pool_size = 0;
+    for (name, contains, size) in CLASSES {
+        if password.chars().any(contains) {
+            classes.push(name);
+            pool_size += size;
+        }
+    }
+
+    // Outside ASCII there is no natural pool, so count only the distinct
+    // characters actually used: a lower bound on what an attacker must try
+    let mut other: Vec<char> = password.chars().filter(|c| !c.is_ascii()).collect();
+    other.sort_unstable();
+    other.dedup();
+    if !other.is_empty() {
+        classes.push("other");
+        pool_size += other.len();
+    }
+
+    let length = grapheme_len(password);
+    let charset_entropy = if pool_size > 1 { length as f64 * (pool_size as f64).log2() } else { 0.0 };
+    let strength = strength::estimate(password);
+
+    let mut findings = Vec::new();
+    if length < MIN_RECOMMENDED_LENGTH {
+        findings.push(format!("Shorter than {} characters", MIN_RECOMMENDED_LENGTH));
+    }
+    if classes.len() == 1 {
+        findings.push("Uses a single character class".to_string());
+    }
+    if has_repeat_run(password, REPEAT_RUN) {
+        findings.push(format!("Repeats a character {} or more times in a row", REPEAT_RUN));
+    }
+    if has_sequential_run(password, SEQUENTIAL_RUN) {
+        findings.push("Contains a sequence like abc or 321".to_string());
+    }
+    if has_keyboard_walk(password, layout, DEFAULT_WALK_RUN) {
+        findings.push(format!("Contains a walk across neighbouring {} keys", layout));
+    }
+    if let Some(warning) = &strength.warning {
+        findings.push(warning.clone());
+    }
+
+    Analysis { length, classes, pool_size, charset_entropy, strength, findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strong_password() {
+        let analysis = analyze("hT7k-Pq2M-9xLr-a3Vd", Layout::Qwerty);
+        assert_eq!(analysis.length, 19);
+        assert_eq!(analysis.classes, ["lowercase", "uppercase", "digits", "symbols"]);
+        assert_eq!(analysis.pool_size, 94);
+        assert!((analysis.charset_entropy - 19.0 * 94f64.log2()).abs() < 1e-9);
+        assert!(analysis.findings.is_empty(), "{:?}", analysis.findings);
+        assert_eq!(analysis.strength.score, 4);
+    }
+
+    #[test]
+    fn test_weak_password() {
+        let analysis = analyze("qwerty1234", Layout::Qwerty);
+        assert_eq!(analysis.classes, ["lowercase", "digits"]);
+        assert_eq!(analysis.pool_size, 36);
+        assert!(analysis.findings.iter().any(|finding| finding.starts_with("Shorter than")));
+        assert!(analysis.findings.iter().any(|finding| finding.contains("sequence")));
+        assert!(analysis.findings.iter().any(|finding| finding.contains("qwerty keys")));
+        assert!(analysis.strength.score <= 1);
+        assert!(analysis.estimated_entropy() < analysis.charset_entropy);
+
+        let analysis = analyze("aaaaaaaaaaaaaaaa", Layout::Qwerty);
+        assert!(analysis.findings.contains(&"Uses a single character class".to_string()));
+        assert!(analysis.findings.iter().any(|finding| finding.starts_with("Repeats a character")));
+    }
+
+    #[test]
+    fn test_non_ascii() {
+        let analysis = analyze("παράδειγμα", Layout::Qwerty);
+        assert_eq!(analysis.classes, ["other"]);
+        assert_eq!(analysis.length, 10);
+        assert_eq!(analysis.pool_size, 9);
+    }
+}
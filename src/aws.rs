@@ -0,0 +1,76 @@
+//! Write a generated secret into AWS Secrets Manager with the AWS SDK,
+//! finding credentials and the region like the `aws` CLI does: environment
+//! variables, the shared config and credentials files (profiles, SSO), and
+//! the container or instance role.
+
+use std::error::Error;
+
+use aws_sdk_secretsmanager::Client;
+
+/// An SDK error and its causes, outermost first, e.g. `dispatch failure:
+/// other: no credentials found in chain`
+fn describe(err: impl Error) -> String {
+    let mut description = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        description.push_str(": ");
+        description.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    description
+}
+
+/// Add `secret` as the new version of the secret `id` (a name or ARN),
+/// creating the secret when there is none yet, and return the version id
+pub fn put(id: &str, secret: &str) -> Result<Option<String>, String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| format!("could not start the AWS client ({})", err))?;
+
+    runtime.block_on(async {
+        let config = aws_config::load_from_env().await;
+        let client = Client::new(&config);
+
+        match client.put_secret_value().secret_id(id).secret_string(secret).send().await {
+            Ok(output) => Ok(output.version_id),
+            Err(err) if err.as_service_error().is_some_and(|err| err.is_resource_not_found_exception()) => client
+                .create_secret()
+                .name(id)
+                .secret_string(secret)
+                .send()
+                .await
+                .map(|output| output.version_id)
+                .map_err(describe),
+            Err(err) => Err(describe(err)),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct Failure(&'static str, Option<Box<Failure>>);
+
+    impl fmt::Display for Failure {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl Error for Failure {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.1.as_deref().map(|cause| cause as &(dyn Error + 'static))
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        let err = Failure("dispatch failure", Some(Box::new(Failure("other", Some(Box::new(Failure("no credentials", None)))))));
+        assert_eq!(describe(err), "dispatch failure: other: no credentials");
+        assert_eq!(describe(Failure("service error", None)), "service error");
+    }
+}
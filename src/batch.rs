@@ -0,0 +1,526 @@
+//! Heterogeneous batches: one generation spec per line of stdin.
+//!
+//! A spec is a whitespace-separated list of `key=value` settings, for example
+//! `length=20 classes=alnum` or `passphrase=6`. Keys mirror the long
+//! command-line flags. Each spec starts from the settings given on the command
+//! line (and config file), with the generation mode reset to a plain random
+//! password, so `password --batch -a` excludes ambiguous characters for every
+//! line that doesn't say otherwise.
+//!
+//! Values containing spaces can be double-quoted: `title="Work email"`, with
+//! `\"` and `\\` escaping a quote or backslash inside the quotes.
+//!
+//! `--labels` is the simpler shape: one label per line, and one secret with
+//! the shared settings for each.
+
+use std::io::BufRead;
+
+use password::inject::InjectPosition;
+use password::passphrase::{self, Language, WordCase};
+use password::policy::CharClass;
+use password::PasswordError;
+
+use crate::export::Account;
+use crate::{
+    validate_candidates, validate_group_size, validate_length, validate_pin_length, validate_run_length,
+    validate_word_length, Args,
+};
+
+/// Parse every spec read from `reader`. Blank lines and lines starting with
+/// `#` are skipped.
+pub fn read_specs<B: BufRead>(base: &Args, reader: B) -> Result<Vec<Args>, PasswordError> {
+    let mut specs = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| PasswordError::BatchRead(err.to_string()))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let spec = parse_spec(base, line)
+            .map_err(|reason| PasswordError::BatchSpec(index + 1, reason))?;
+        specs.push(spec);
+    }
+
+    Ok(specs)
+}
+
+/// Read one label per line from `reader`. Blank lines and lines starting
+/// with `#` are skipped, like in specs.
+pub fn read_labels<B: BufRead>(reader: B) -> Result<Vec<String>, PasswordError> {
+    let mut labels = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| PasswordError::Labels(err.to_string()))?;
+        let label = line.trim();
+        if label.is_empty() || label.starts_with('#') {
+            continue;
+        }
+        // A tab would split the `label<TAB>secret` columns
+        if label.contains('\t') {
+            return Err(PasswordError::Labels(format!("line {}: labels can't contain tabs", index + 1)));
+        }
+        labels.push(label.to_string());
+    }
+
+    if labels.is_empty() {
+        return Err(PasswordError::Labels("no labels to generate secrets for".to_string()));
+    }
+    Ok(labels)
+}
+
+/// Apply a single spec line on top of `base`
+pub fn parse_spec(base: &Args, line: &str) -> Result<Args, String> {
+    let words = split_spec(line)?;
+    let settings = words.iter().map(|setting| match setting.split_once('=') {
+        Some((key, value)) => (key, Some(value)),
+        None => (setting.as_str(), None),
+    });
+
+    apply_settings(base, settings)
+}
+
+/// Split a spec line on whitespace outside double quotes, removing the quotes
+fn split_spec(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\')) => word.push(escaped),
+                            Some(other) => {
+                                word.push('\\');
+                                word.push(other);
+                            }
+                            None => return Err("unterminated quote".to_string()),
+                        },
+                        Some(other) => word.push(other),
+                        None => return Err("unterminated quote".to_string()),
+                    }
+                }
+            }
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+
+    Ok(words)
+}
+
+/// Apply `key`/`value` settings on top of `base`, starting from a plain
+/// random password. A value of `None` turns a flag on.
+pub fn apply_settings<'a, I>(base: &Args, settings: I) -> Result<Args, String>
+where
+    I: IntoIterator<Item = (&'a str, Option<&'a str>)>,
+{
+    let mut args = base.clone();
+    args.passphrase = None;
+    args.pronounceable = false;
+    args.markov = false;
+    args.leet = false;
+    args.language = Language::English;
+    args.separator = " ".to_string();
+    args.word_case = WordCase::Lower;
+    args.inject_digits = 0;
+    args.inject_symbols = 0;
+    args.inject_at = InjectPosition::Boundary;
+    args.charset = None;
+    args.template = None;
+    args.hashcat_mask = None;
+    args.pin = None;
+    args.account = Account::default();
+
+    let mut modes = Vec::new();
+    for (key, value) in settings {
+        if apply_setting(&mut args, key, value)? {
+            modes.push(key);
+        }
+    }
+
+    if modes.len() > 1 {
+        return Err(format!("'{}' and '{}' cannot be combined", modes[0], modes[1]));
+    }
+    if args.markov && (!args.pronounceable || args.leet) {
+        return Err("'markov' only applies to 'pronounceable' without 'leet'".to_string());
+    }
+    if (args.inject_digits > 0 || args.inject_symbols > 0) && (args.passphrase.is_none() || args.leet) {
+        return Err("'inject-digits' and 'inject-symbols' only apply to 'passphrase' without 'leet'".to_string());
+    }
+    if args.optimize_typing.is_some() && (args.passphrase.is_some() || args.pin.is_some()) {
+        return Err("'optimize-typing' doesn't apply to 'passphrase' or 'pin'".to_string());
+    }
+
+    Ok(args)
+}
+
+/// Apply one setting, returning whether it selected a generation mode
+fn apply_setting(args: &mut Args, key: &str, value: Option<&str>) -> Result<bool, String> {
+    let required = || value.ok_or_else(|| format!("'{}' needs a value", key));
+    let mut is_mode = false;
+
+    match key {
+        "length" => {
+            args.length = validate_length(required()?)?;
+            args.bits = None;
+        }
+        "bits" => args.bits = Some(parse_bits(key, required()?)?),
+        "classes" => {
+            args.classes = Vec::new();
+            match required()? {
+                "alnum" => {
+                    args.exclude_symbols = true;
+                    args.extended_symbols = false;
+                    args.url_safe = false;
+                }
+                "symbols" => {
+                    args.exclude_symbols = false;
+                    args.extended_symbols = false;
+                    args.url_safe = false;
+                }
+                "extended" => {
+                    args.exclude_symbols = false;
+                    args.extended_symbols = true;
+                    args.url_safe = false;
+                }
+                "url-safe" => {
+                    args.exclude_symbols = false;
+                    args.extended_symbols = false;
+                    args.url_safe = true;
+                }
+                // A list of classes, as with --classes
+                list => {
+                    args.classes = list.split(',').map(|class| class.parse()).collect::<Result<_, String>>()
+                        .map_err(|_| format!(
+                            "unknown classes '{}' (expected alnum, symbols, extended, url-safe or a list of {})",
+                            list, CharClass::NAMES.join(", ")
+                        ))?;
+                    args.exclude_symbols = false;
+                    args.extended_symbols = false;
+                    args.url_safe = false;
+                }
+            }
+        }
+        "allow-space" => args.allow_space = parse_flag(key, value)?,
+        "exclude-ambiguous" => args.exclude_ambiguous = parse_flag(key, value)?,
+        "unicode" => {
+            args.unicode = required()?
+                .split(',')
+                .map(|block| block.parse())
+                .collect::<Result<_, _>>()?;
+        }
+        "emoji" => args.emoji = parse_flag(key, value)?,
+        "emoji-only" => args.emoji_only = parse_flag(key, value)?,
+        "weights" => args.weights = Some(required()?.parse()?),
+        "exclude" => args.exclude = Some(required()?.to_string()),
+        "group" => args.group = Some(validate_group_size(required()?)?),
+        "group-sep" => args.group_sep = parse_char(key, required()?)?,
+        "min-digits" => args.min_digits = parse_count(key, required()?)?,
+        "min-upper" => args.min_upper = parse_count(key, required()?)?,
+        "min-lower" => args.min_lower = parse_count(key, required()?)?,
+        "min-symbols" => args.min_symbols = parse_count(key, required()?)?,
+        "no-adjacent-repeat" => args.no_adjacent_repeat = parse_flag(key, value)?,
+        "start-with" => args.start_with = Some(required()?.parse()?),
+        "no-symbol-edges" => args.no_symbol_edges = parse_flag(key, value)?,
+        "max-digits" => args.max_digits = Some(parse_count(key, required()?)?),
+        "max-upper" => args.max_upper = Some(parse_count(key, required()?)?),
+        "max-lower" => args.max_lower = Some(parse_count(key, required()?)?),
+        "max-symbols" => args.max_symbols = Some(parse_count(key, required()?)?),
+        "unique" => args.unique = parse_flag(key, value)?,
+        "no-sequential" => {
+            args.no_sequential = Some(validate_run_length(value.unwrap_or("3"))?);
+        }
+        "no-keyboard-walk" => {
+            args.no_keyboard_walk = Some(validate_run_length(value.unwrap_or("4"))?);
+        }
+        "no-dictionary-words" => {
+            args.no_dictionary_words = Some(validate_word_length(value.unwrap_or("4"))?);
+        }
+        "keyboard-layout" => args.keyboard_layout = required()?.parse()?,
+        "optimize-typing" => args.optimize_typing = Some(validate_candidates(value.unwrap_or("32"))?),
+        "passphrase" => {
+            args.passphrase = Some(passphrase::validate_word_count(required()?)?);
+            is_mode = true;
+        }
+        "pronounceable" => {
+            args.pronounceable = parse_flag(key, value)?;
+            is_mode = args.pronounceable;
+        }
+        "markov" => args.markov = parse_flag(key, value)?,
+        "leet" => args.leet = parse_flag(key, value)?,
+        "language" => args.language = required()?.parse()?,
+        "separator" => args.separator = required()?.to_string(),
+        "word-case" => args.word_case = required()?.parse()?,
+        "inject-digits" => args.inject_digits = parse_inject_count(key, required()?)?,
+        "inject-symbols" => args.inject_symbols = parse_inject_count(key, required()?)?,
+        "inject-at" => args.inject_at = required()?.parse()?,
+        "charset" => {
+            args.charset = Some(required()?.to_string());
+            is_mode = true;
+        }
+        "template" => {
+            args.template = Some(required()?.to_string());
+            is_mode = true;
+        }
+        "hashcat-mask" => {
+            args.hashcat_mask = Some(required()?.to_string());
+            is_mode = true;
+        }
+        "pin" => {
+            args.pin = Some(validate_pin_length(required()?)?);
+            args.bits = None;
+            is_mode = true;
+        }
+        "title" => args.account.title = Some(required()?.to_string()),
+        "username" => args.account.username = Some(required()?.to_string()),
+        "url" => args.account.url = Some(required()?.to_string()),
+        "notes" => args.account.notes = Some(required()?.to_string()),
+        "folder" => args.account.folder = Some(required()?.to_string()),
+        _ => return Err(format!("unknown setting '{}'", key)),
+    }
+
+    Ok(is_mode)
+}
+
+fn parse_flag(key: &str, value: Option<&str>) -> Result<bool, String> {
+    match value {
+        None | Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        Some(other) => Err(format!("'{}' must be true or false. Got: {}", key, other)),
+    }
+}
+
+fn parse_char(key: &str, value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("'{}' must be a single character. Got: {}", key, value)),
+    }
+}
+
+fn parse_bits(key: &str, value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(bits) if bits > 0 => Ok(bits),
+        _ => Err(format!("'{}' must be a positive number. Got: {}", key, value)),
+    }
+}
+
+fn parse_count(key: &str, value: &str) -> Result<u16, String> {
+    value.parse().map_err(|_| format!("'{}' must be a non-negative number. Got: {}", key, value))
+}
+
+/// Parse how many digits or symbols to inject (at most 64)
+fn parse_inject_count(key: &str, value: &str) -> Result<u16, String> {
+    match parse_count(key, value)? {
+        count @ 0..=64 => Ok(count),
+        count => Err(format!("'{}' must be at most 64. Got: {}", key, count)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use password::get_secure_rng;
+    use password::unicode::UnicodeBlock;
+
+    use crate::Generator;
+
+    fn base(argv: &[&str]) -> Args {
+        Args::try_parse_from(argv).unwrap()
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let args = parse_spec(&base(&["password"]), "length=20 classes=alnum").unwrap();
+        assert_eq!(args.length, 20);
+        assert!(args.exclude_symbols);
+
+        let args = parse_spec(&base(&["password"]), "passphrase=6").unwrap();
+        assert_eq!(args.passphrase, Some(6));
+
+        let args = parse_spec(&base(&["password", "-n"]), "classes=url-safe").unwrap();
+        assert!(args.url_safe && !args.exclude_symbols);
+        assert!(!parse_spec(&args, "classes=symbols").unwrap().url_safe);
+
+        let args = parse_spec(&base(&["password", "-n"]), "classes=digit,symbol").unwrap();
+        assert_eq!(args.classes, [CharClass::Digit, CharClass::Symbol]);
+        assert!(!args.exclude_symbols);
+        assert!(parse_spec(&args, "classes=alnum").unwrap().classes.is_empty());
+        assert!(parse_spec(&args, "classes=digit,emoji").is_err());
+
+        let args = parse_spec(&base(&["password"]), "weights=letters=4,symbols=0").unwrap();
+        assert_eq!(args.weights.unwrap().to_string(), "letters=4,digits=1,symbols=0");
+        assert!(parse_spec(&args, "weights=letters").is_err());
+
+        let args = parse_spec(&base(&["password"]), "pin=6 exclude-ambiguous=false exclude=0").unwrap();
+        assert_eq!(args.pin, Some(6));
+        assert!(!args.exclude_ambiguous);
+        assert_eq!(args.exclude.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_read_labels() {
+        let labels = read_labels("alice\n\n# staff\n  bob smith  \ncarol\n".as_bytes()).unwrap();
+        assert_eq!(labels, ["alice", "bob smith", "carol"]);
+
+        assert!(matches!(read_labels("alice\nbob\tsmith\n".as_bytes()), Err(PasswordError::Labels(reason)) if reason.contains("line 2")));
+        assert!(matches!(read_labels("\n# nobody\n".as_bytes()), Err(PasswordError::Labels(_))));
+    }
+
+    #[test]
+    fn test_spec_inherits_shared_settings() {
+        let args = parse_spec(&base(&["password", "-a", "-l", "12"]), "min-digits=2").unwrap();
+        assert!(args.exclude_ambiguous);
+        assert_eq!(args.length, 12);
+        assert_eq!(args.min_digits, 2);
+    }
+
+    #[test]
+    fn test_markov_spec() {
+        let args = parse_spec(&base(&["password"]), "pronounceable markov length=24").unwrap();
+        assert!(args.pronounceable && args.markov);
+        assert!(!parse_spec(&args, "pronounceable").unwrap().markov);
+        assert!(parse_spec(&args, "markov").is_err());
+        assert!(parse_spec(&args, "pronounceable markov leet").is_err());
+    }
+
+    #[test]
+    fn test_leet_spec() {
+        let args = parse_spec(&base(&["password"]), "passphrase=4 leet").unwrap();
+        assert!(args.leet);
+        assert!(!parse_spec(&args, "length=20").unwrap().leet);
+    }
+
+    #[test]
+    fn test_passphrase_style_spec() {
+        let args = parse_spec(&base(&["password"]), "passphrase=4 separator=. word-case=upper").unwrap();
+        assert_eq!(args.separator, ".");
+        assert_eq!(args.word_case, WordCase::Upper);
+
+        let args = parse_spec(&args, "passphrase=4").unwrap();
+        assert_eq!(args.separator, " ");
+        assert_eq!(args.word_case, WordCase::Lower);
+        assert!(parse_spec(&args, "passphrase=4 word-case=sponge").is_err());
+    }
+
+    #[test]
+    fn test_inject_spec() {
+        let args = parse_spec(&base(&["password"]), "passphrase=4 inject-digits=2 inject-symbols=1 inject-at=anywhere").unwrap();
+        assert_eq!((args.inject_digits, args.inject_symbols), (2, 1));
+        assert_eq!(args.inject_at, InjectPosition::Anywhere);
+
+        let args = parse_spec(&args, "passphrase=4").unwrap();
+        assert_eq!((args.inject_digits, args.inject_symbols), (0, 0));
+        assert!(parse_spec(&args, "length=20 inject-digits=2").is_err());
+        assert!(parse_spec(&args, "passphrase=4 leet inject-symbols=1").is_err());
+        assert!(parse_spec(&args, "passphrase=4 inject-digits=65").is_err());
+    }
+
+    #[test]
+    fn test_optimize_typing_spec() {
+        let args = parse_spec(&base(&["password"]), "length=16 optimize-typing").unwrap();
+        assert_eq!(args.optimize_typing, Some(32));
+        assert_eq!(parse_spec(&args, "optimize-typing=64").unwrap().optimize_typing, Some(64));
+        assert!(parse_spec(&args, "optimize-typing=1").is_err());
+        assert!(parse_spec(&args, "passphrase=4").is_err());
+        assert!(parse_spec(&base(&["password"]), "pin=6 optimize-typing").is_err());
+    }
+
+    #[test]
+    fn test_language_spec() {
+        let args = parse_spec(&base(&["password"]), "passphrase=4 language=fr").unwrap();
+        assert_eq!(args.language, Language::French);
+        assert_eq!(parse_spec(&args, "passphrase=4").unwrap().language, Language::English);
+        assert!(parse_spec(&args, "passphrase=4 language=nl").is_err());
+    }
+
+    #[test]
+    fn test_bits_spec() {
+        let args = parse_spec(&base(&["password"]), "bits=128 classes=alnum").unwrap();
+        assert_eq!(args.bits, Some(128));
+        let Generator::Password { length, .. } = Generator::from_args(&args).unwrap() else {
+            panic!("expected a password generator");
+        };
+        assert_eq!(length, 22);
+
+        // An explicit length or PIN in the spec replaces --bits from the command line
+        let base = base(&["password", "--bits", "100"]);
+        assert_eq!(parse_spec(&base, "length=20").unwrap().bits, None);
+        assert_eq!(parse_spec(&base, "pin=6").unwrap().bits, None);
+        assert!(parse_spec(&base, "bits=0").is_err());
+        assert!(parse_spec(&base, "bits=lots").is_err());
+    }
+
+    #[test]
+    fn test_extra_pool_specs() {
+        let args = parse_spec(&base(&["password"]), "unicode=greek,latin1").unwrap();
+        assert_eq!(args.unicode, [UnicodeBlock::Greek, UnicodeBlock::Latin1]);
+        assert!(parse_spec(&args, "unicode=greek,runic").is_err());
+
+        let args = parse_spec(&base(&["password", "--emoji"]), "emoji-only").unwrap();
+        assert!(args.emoji && args.emoji_only);
+
+        let args = parse_spec(&base(&["password", "--unicode", "cyrillic"]), "pin=6").unwrap();
+        let pin = Generator::from_args(&args).unwrap().generate(&mut get_secure_rng().unwrap()).unwrap();
+        assert!(pin.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_group_spec() {
+        let args = parse_spec(&base(&["password"]), "group=5 group-sep=.").unwrap();
+        assert_eq!(args.group, Some(5));
+        assert_eq!(args.group_sep, '.');
+        assert!(parse_spec(&args, "group=0").is_err());
+        assert!(parse_spec(&args, "group-sep=--").is_err());
+    }
+
+    #[test]
+    fn test_account_spec() {
+        let args = base(&["password"]);
+        let spec = parse_spec(&args, r#"length=20 title="Work \"email\"" username=ann@example.com folder=Work"#).unwrap();
+        assert_eq!(spec.length, 20);
+        assert_eq!(spec.account.title.as_deref(), Some("Work \"email\""));
+        assert_eq!(spec.account.username.as_deref(), Some("ann@example.com"));
+        assert_eq!(spec.account.folder.as_deref(), Some("Work"));
+        assert_eq!(spec.account.url, None);
+
+        let spec = parse_spec(&args, r#"notes="C:\\Users  two spaces" url=https://example.com/?a=b"#).unwrap();
+        assert_eq!(spec.account.notes.as_deref(), Some("C:\\Users  two spaces"));
+        assert_eq!(spec.account.url.as_deref(), Some("https://example.com/?a=b"));
+
+        assert!(parse_spec(&args, r#"title="Work email"#).is_err());
+        assert!(parse_spec(&args, "title").is_err());
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        let args = base(&["password"]);
+        assert!(parse_spec(&args, "length=0").is_err());
+        assert!(parse_spec(&args, "length").is_err());
+        assert!(parse_spec(&args, "classes=emoji").is_err());
+        assert!(parse_spec(&args, "colour=blue").is_err());
+        assert!(parse_spec(&args, "pronounceable passphrase=4").is_err());
+    }
+
+    #[test]
+    fn test_read_specs() {
+        let input = "# accounts\nlength=20 classes=alnum\n\npin=4\n";
+        let specs = read_specs(&base(&["password"]), input.as_bytes()).unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[1].pin, Some(4));
+
+        assert!(matches!(
+            read_specs(&base(&["password"]), "length=20\nbogus\n".as_bytes()),
+            Err(PasswordError::BatchSpec(2, _))
+        ));
+    }
+}
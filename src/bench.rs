@@ -0,0 +1,163 @@
+//! `password bench`: how many passwords per second each common mode
+//! produces, and how many bytes per second each RNG backend delivers.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use password::passphrase::{self, Wordlist};
+use password::rng::RngBackend;
+use rand::RngCore;
+use password::{build_charset, generate_from_charset, PasswordError, CHARS_ALPHA_NUM};
+
+/// Bytes drawn per call when measuring RNG throughput
+const RNG_BLOCK_BYTES: usize = 64 * 1024;
+
+/// One line of the report
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub name: String,
+    /// Operations (or bytes) per second
+    pub rate: f64,
+    pub unit: &'static str,
+}
+
+/// Call `op` in rounds until `duration` has passed and return calls per second
+fn measure<F: FnMut()>(duration: Duration, mut op: F) -> f64 {
+    let start = Instant::now();
+    let mut calls: u64 = 0;
+    let mut round = 1;
+    loop {
+        for _ in 0..round {
+            op();
+        }
+        calls += round;
+        let elapsed = start.elapsed();
+        if elapsed >= duration {
+            return calls as f64 / elapsed.as_secs_f64();
+        }
+        // Grow rounds so the clock is read rarely on fast operations
+        round = (round * 2).min(4096);
+    }
+}
+
+/// Measure password generation with `backend`, spending about `duration` on each case
+pub fn run(backend: RngBackend, duration: Duration) -> Result<Vec<Measurement>, PasswordError> {
+    let mut rng = backend.new_rng()?;
+    let mut results = Vec::new();
+
+    let charsets = [
+        ("alphanumeric", CHARS_ALPHA_NUM.to_vec()),
+        ("default", build_charset(true, false, false).to_vec()),
+        ("extended", build_charset(true, true, false).to_vec()),
+    ];
+    for (name, charset) in &charsets {
+        for length in [16, 36, 128] {
+            let rate = measure(duration, || {
+                black_box(generate_from_charset(&mut rng, length, charset).expect("charset is not empty"));
+            });
+            results.push(Measurement {
+                name: format!("{} ({} chars), length {}", name, charset.len(), length),
+                rate,
+                unit: "passwords",
+            });
+        }
+    }
+
+    let wordlist = Wordlist::eff_large();
+    let rate = measure(duration, || {
+        black_box(passphrase::generate_passphrase(&mut rng, 6, &wordlist).expect("wordlist is not empty"));
+    });
+    results.push(Measurement { name: "passphrase, 6 words".to_string(), rate, unit: "passwords" });
+
+    for backend in [RngBackend::Os, RngBackend::ChaCha20, RngBackend::ChaCha20Reseeding, RngBackend::HmacDrbg] {
+        let mut rng = backend.new_rng()?;
+        let mut block = vec![0u8; RNG_BLOCK_BYTES];
+        let rate = measure(duration, || {
+            rng.fill_bytes(&mut block);
+            black_box(&block);
+        });
+        results.push(Measurement {
+            name: format!("rng {}", backend),
+            rate: rate * RNG_BLOCK_BYTES as f64,
+            unit: "bytes",
+        });
+    }
+
+    Ok(results)
+}
+
+/// Digits of a whole number grouped in thousands, like `1,234,567`
+fn group_thousands(value: f64) -> String {
+    let digits = format!("{:.0}", value);
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// One aligned line per measurement; byte rates are shown in MiB/s
+pub fn format_report(results: &[Measurement]) -> String {
+    let width = results.iter().map(|result| result.name.len()).max().unwrap_or(0);
+    results
+        .iter()
+        .map(|result| {
+            let rate = match result.unit {
+                "bytes" => format!("{:.1} MiB/s", result.rate / (1024.0 * 1024.0)),
+                unit => format!("{} {}/s", group_thousands(result.rate), unit),
+            };
+            format!("{:<width$}  {:>20}", result.name, rate, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse the time spent on each case (more than 0 and at most 60 seconds)
+pub fn validate_seconds(s: &str) -> Result<f64, String> {
+    let seconds: f64 = s.parse().map_err(|_| format!(
+        "The time per case must be a number of seconds. Got: {}", s
+    ))?;
+
+    if !(seconds > 0.0 && seconds <= 60.0) {
+        return Err(format!("The time per case must be between 0 and 60 seconds. Got: {}", s));
+    }
+
+    Ok(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let results = run(RngBackend::ChaCha20, Duration::from_millis(1)).unwrap();
+        assert_eq!(results.len(), 9 + 1 + 4);
+        assert!(results.iter().all(|result| result.rate > 0.0));
+        assert_eq!(results.iter().filter(|result| result.unit == "bytes").count(), 4);
+
+        let report = format_report(&results);
+        assert_eq!(report.lines().count(), results.len());
+        assert!(report.contains("default (88 chars), length 36"));
+        assert!(report.contains("rng hmac-drbg"));
+    }
+
+    #[test]
+    fn test_group_thousands() {
+        assert_eq!(group_thousands(7.0), "7");
+        assert_eq!(group_thousands(1234.4), "1,234");
+        assert_eq!(group_thousands(1234567.0), "1,234,567");
+        assert_eq!(group_thousands(100000.0), "100,000");
+    }
+
+    #[test]
+    fn test_validate_seconds() {
+        assert_eq!(validate_seconds("0.5").unwrap(), 0.5);
+        assert!(validate_seconds("0").is_err());
+        assert!(validate_seconds("61").is_err());
+        assert!(validate_seconds("soon").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Offline list of banned or breached passwords.
+//!
+//! A blocklist file holds one entry per line, either a plaintext password or
+//! the hex SHA-1 hash of one (optionally followed by `:count`, as in the
+//! downloadable Pwned Passwords files). Any line made of exactly 40 hex digits
+//! is treated as a hash.
+
+use sha1::{Digest, Sha1};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::PasswordError;
+
+/// Banned passwords and password hashes loaded from a file
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    passwords: HashSet<String>,
+    hashes: HashSet<[u8; 20]>,
+}
+
+fn parse_sha1(hex: &str) -> Option<[u8; 20]> {
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut hash = [0u8; 20];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+impl Blocklist {
+    pub fn from_file(path: &Path) -> Result<Self, PasswordError> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            PasswordError::BlocklistRead(format!("{}: {}", path.display(), err))
+        })?;
+
+        Ok(Self::parse(&contents))
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let mut blocklist = Blocklist::default();
+
+        for line in contents.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                continue;
+            }
+
+            let hash_part = line.split_once(':').map_or(line, |(hash, _)| hash);
+            match parse_sha1(hash_part) {
+                Some(hash) => blocklist.hashes.insert(hash),
+                None => blocklist.passwords.insert(line.to_string()),
+            };
+        }
+
+        blocklist
+    }
+
+    pub fn len(&self) -> usize {
+        self.passwords.len() + self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `password` (or its SHA-1 hash) is on the list
+    pub fn contains(&self, password: &str) -> bool {
+        if self.passwords.contains(password) {
+            return true;
+        }
+
+        !self.hashes.is_empty() && self.hashes.contains(&<[u8; 20]>::from(Sha1::digest(password.as_bytes())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plaintext_entries() {
+        let blocklist = Blocklist::parse("123456\npassword\r\n\nqwerty\n");
+        assert_eq!(blocklist.len(), 3);
+        assert!(blocklist.contains("password"));
+        assert!(blocklist.contains("123456"));
+        assert!(!blocklist.contains("Password"));
+        assert!(!blocklist.contains(""));
+    }
+
+    #[test]
+    fn test_hash_entries() {
+        // SHA-1 of "password", with and without a breach count
+        let blocklist = Blocklist::parse(
+            "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:10434004\n\
+             7c4a8d09ca3762af61e59520943dc26494f8941b\n"
+        );
+        assert_eq!(blocklist.len(), 2);
+        assert!(blocklist.contains("password"));
+        assert!(blocklist.contains("123456"));
+        assert!(!blocklist.contains("5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"));
+    }
+}
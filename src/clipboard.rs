@@ -0,0 +1,178 @@
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use password::PasswordError;
+
+/// External clipboard utility used on the current platform
+#[derive(Debug, PartialEq)]
+enum Backend {
+    MacOs,
+    Windows,
+    Wayland,
+    Xclip,
+    Xsel,
+}
+
+impl Backend {
+    fn detect() -> Option<Self> {
+        let on_path = |program: &str| {
+            env::var_os("PATH")
+                .map(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+                .unwrap_or(false)
+        };
+
+        select_backend(
+            env::consts::OS,
+            env::var_os("WAYLAND_DISPLAY").is_some(),
+            env::var_os("DISPLAY").is_some(),
+            on_path,
+        )
+    }
+
+    fn copy_command(&self) -> Command {
+        match self {
+            Backend::MacOs => Command::new("pbcopy"),
+            Backend::Windows => Command::new("clip"),
+            Backend::Wayland => Command::new("wl-copy"),
+            Backend::Xclip => {
+                let mut command = Command::new("xclip");
+                command.args(["-selection", "clipboard"]);
+                command
+            }
+            Backend::Xsel => {
+                let mut command = Command::new("xsel");
+                command.args(["--clipboard", "--input"]);
+                command
+            }
+        }
+    }
+
+    fn paste_command(&self) -> Command {
+        match self {
+            Backend::MacOs => Command::new("pbpaste"),
+            Backend::Windows => {
+                let mut command = Command::new("powershell");
+                command.args(["-NoProfile", "-Command", "Get-Clipboard -Raw"]);
+                command
+            }
+            Backend::Wayland => {
+                let mut command = Command::new("wl-paste");
+                command.arg("--no-newline");
+                command
+            }
+            Backend::Xclip => {
+                let mut command = Command::new("xclip");
+                command.args(["-selection", "clipboard", "-o"]);
+                command
+            }
+            Backend::Xsel => {
+                let mut command = Command::new("xsel");
+                command.args(["--clipboard", "--output"]);
+                command
+            }
+        }
+    }
+}
+
+fn select_backend(
+    os: &str,
+    wayland: bool,
+    x11: bool,
+    on_path: impl Fn(&str) -> bool,
+) -> Option<Backend> {
+    match os {
+        "macos" => Some(Backend::MacOs),
+        "windows" => Some(Backend::Windows),
+        _ if wayland && on_path("wl-copy") => Some(Backend::Wayland),
+        _ if x11 && on_path("xclip") => Some(Backend::Xclip),
+        _ if x11 && on_path("xsel") => Some(Backend::Xsel),
+        _ => None,
+    }
+}
+
+fn write(backend: &Backend, contents: &str) -> Result<(), PasswordError> {
+    let mut child = backend
+        .copy_command()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| PasswordError::Clipboard(err.to_string()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(contents.as_bytes())
+            .map_err(|err| PasswordError::Clipboard(err.to_string()))?;
+    }
+
+    let status = child
+        .wait()
+        .map_err(|err| PasswordError::Clipboard(err.to_string()))?;
+
+    if !status.success() {
+        return Err(PasswordError::Clipboard(format!("clipboard utility exited with {}", status)));
+    }
+
+    Ok(())
+}
+
+fn read(backend: &Backend) -> Option<String> {
+    let output = backend.paste_command().stderr(Stdio::null()).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Place `contents` on the system clipboard
+pub fn copy(contents: &str) -> Result<(), PasswordError> {
+    let backend = Backend::detect().ok_or_else(|| {
+        PasswordError::Clipboard(
+            "no clipboard utility found (pbcopy, clip, wl-copy, xclip or xsel)".to_string(),
+        )
+    })?;
+
+    write(&backend, contents)
+}
+
+/// Wait for `timeout`, then clear the clipboard if it still holds `contents`.
+///
+/// If the clipboard can't be read back it is cleared regardless.
+pub fn clear_after(contents: &str, timeout: Duration) -> Result<(), PasswordError> {
+    let backend = match Backend::detect() {
+        Some(backend) => backend,
+        None => return Ok(()),
+    };
+
+    thread::sleep(timeout);
+
+    match read(&backend) {
+        Some(current) if current.trim_end_matches(['\r', '\n']) != contents => Ok(()),
+        _ => write(&backend, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_backend() {
+        let everything = |_: &str| true;
+        let nothing = |_: &str| false;
+
+        assert_eq!(select_backend("macos", false, false, nothing), Some(Backend::MacOs));
+        assert_eq!(select_backend("windows", false, false, nothing), Some(Backend::Windows));
+        assert_eq!(select_backend("linux", true, true, everything), Some(Backend::Wayland));
+        assert_eq!(select_backend("linux", false, true, everything), Some(Backend::Xclip));
+        assert_eq!(
+            select_backend("linux", false, true, |program: &str| program == "xsel"),
+            Some(Backend::Xsel)
+        );
+        assert_eq!(select_backend("linux", true, true, nothing), None);
+        assert_eq!(select_backend("linux", false, false, everything), None);
+    }
+}
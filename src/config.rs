@@ -0,0 +1,453 @@
+use clap::parser::ValueSource;
+use clap::{ArgMatches, ValueEnum};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use password::keyboard::Layout;
+use password::passphrase::Language;
+use password::policy::StartWith;
+use password::PasswordError;
+
+use crate::encrypt;
+use crate::output::OutputFormat;
+use crate::store::Store;
+use crate::{validate_candidates, validate_length, validate_run_length, validate_word_length, Args};
+
+/// Preferred defaults loaded from `~/.config/password-generator/config.toml`.
+///
+/// Every key is optional; flags given on the command line take precedence.
+/// `[profile.NAME]` tables bundle settings for one use case, picked with
+/// `--profile NAME` and applied on top of the rest of the file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub length: Option<u32>,
+    pub length_limit: Option<u32>,
+    pub no_symbols: Option<bool>,
+    pub extended_symbols: Option<bool>,
+    pub allow_space: Option<bool>,
+    pub exclude_ambiguous: Option<bool>,
+    pub exclude: Option<String>,
+    pub charset: Option<String>,
+    pub min_digits: Option<u16>,
+    pub min_upper: Option<u16>,
+    pub min_lower: Option<u16>,
+    pub min_symbols: Option<u16>,
+    pub max_digits: Option<u16>,
+    pub max_upper: Option<u16>,
+    pub max_lower: Option<u16>,
+    pub max_symbols: Option<u16>,
+    pub unique: Option<bool>,
+    pub no_adjacent_repeat: Option<bool>,
+    pub start_with: Option<String>,
+    pub no_symbol_edges: Option<bool>,
+    pub no_sequential: Option<u16>,
+    pub no_keyboard_walk: Option<u16>,
+    pub no_dictionary_words: Option<u16>,
+    pub keyboard_layout: Option<String>,
+    pub optimize_typing: Option<u16>,
+    pub wordlist: Option<PathBuf>,
+    pub language: Option<String>,
+    pub blocklist: Option<PathBuf>,
+    pub clear_after: Option<u64>,
+    pub format: Option<String>,
+    pub name: Option<String>,
+    pub copy: Option<bool>,
+    pub out: Option<PathBuf>,
+    pub store: Option<String>,
+    pub dotenv: Option<PathBuf>,
+    /// Recipient every --log record is encrypted to, so the history is
+    /// never kept in the clear by accident
+    pub log_gpg: Option<String>,
+    pub log_age: Option<String>,
+    #[serde(default, rename = "profile")]
+    pub profiles: BTreeMap<String, Config>,
+}
+
+/// Location of the user's config file, honouring `XDG_CONFIG_HOME`
+pub fn default_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// Location of the user's presets file, next to the config file
+pub fn default_presets_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("presets.toml"))
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+
+    Some(config_home.join("password-generator"))
+}
+
+impl Config {
+    /// Load the config file at `path`. A missing file is only an error when
+    /// it was requested explicitly.
+    pub fn load(path: &Path, explicit: bool) -> Result<Self, PasswordError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound && !explicit => {
+                return Ok(Config::default())
+            }
+            Err(err) => {
+                return Err(PasswordError::Config(format!("{}: {}", path.display(), err)))
+            }
+        };
+
+        Self::parse(&contents)
+            .map_err(|reason| PasswordError::Config(format!("{}: {}", path.display(), reason)))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(contents).map_err(|err| err.message().to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the values that serde can't
+    pub fn validate(&self) -> Result<(), String> {
+        for length in [self.length, self.length_limit].into_iter().flatten() {
+            validate_length(&length.to_string())?;
+        }
+        if self.charset.as_deref().is_some_and(str::is_empty) {
+            return Err("charset must not be empty".to_string());
+        }
+        for run in [self.no_sequential, self.no_keyboard_walk].into_iter().flatten() {
+            validate_run_length(&run.to_string())?;
+        }
+        if let Some(min_length) = self.no_dictionary_words {
+            validate_word_length(&min_length.to_string())?;
+        }
+        if let Some(layout) = &self.keyboard_layout {
+            layout.parse::<Layout>()?;
+        }
+        if let Some(candidates) = self.optimize_typing {
+            validate_candidates(&candidates.to_string())?;
+        }
+        if let Some(start) = &self.start_with {
+            start.parse::<StartWith>()?;
+        }
+        if let Some(language) = &self.language {
+            language.parse::<Language>()?;
+        }
+        if let Some(format) = &self.format {
+            OutputFormat::from_str(format, true)?;
+        }
+        if let Some(store) = &self.store {
+            store.parse::<Store>()?;
+        }
+        if self.destinations() > 1 {
+            return Err("only one of copy, out, store and dotenv can be set".to_string());
+        }
+        if let Some(recipient) = &self.log_gpg {
+            encrypt::validate_recipient(recipient)?;
+        }
+        if let Some(recipient) = &self.log_age {
+            encrypt::parse_age_recipient(recipient)?;
+        }
+        if self.log_gpg.is_some() && self.log_age.is_some() {
+            return Err("only one of log-gpg and log-age can be set".to_string());
+        }
+
+        for (name, profile) in &self.profiles {
+            if !profile.profiles.is_empty() {
+                return Err(format!("profile '{}': profiles can't be nested", name));
+            }
+            profile.validate().map_err(|reason| format!("profile '{}': {}", name, reason))?;
+        }
+
+        Ok(())
+    }
+
+    /// How many of the destinations instead of stdout are set
+    fn destinations(&self) -> usize {
+        [self.copy == Some(true), self.out.is_some(), self.store.is_some(), self.dotenv.is_some()]
+            .iter()
+            .filter(|&&set| set)
+            .count()
+    }
+
+    /// The settings of the profile called `name`
+    pub fn profile(&self, name: &str) -> Result<&Config, PasswordError> {
+        self.profiles.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if names.is_empty() {
+                PasswordError::UnknownProfile(format!("'{}' (the config file defines none)", name))
+            } else {
+                PasswordError::UnknownProfile(format!("'{}' (available: {})", name, names.join(", ")))
+            }
+        })
+    }
+
+    /// Fill in every argument that wasn't given on the command line
+    pub fn apply(&self, args: &mut Args, matches: &ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        if let Some(length) = self.length.filter(|_| !from_cli("length")) {
+            args.length = length;
+        }
+        if let Some(limit) = self.length_limit.filter(|_| !from_cli("length_limit")) {
+            args.length_limit = limit;
+        }
+        if let Some(no_symbols) = self.no_symbols.filter(|_| !from_cli("exclude_symbols")) {
+            args.exclude_symbols = no_symbols;
+        }
+        // An explicit -n on the command line overrides extended symbols from the file
+        if let Some(extended) = self.extended_symbols
+            .filter(|_| !from_cli("extended_symbols") && !from_cli("exclude_symbols"))
+        {
+            args.extended_symbols = extended;
+        }
+        if let Some(allow_space) = self.allow_space.filter(|_| !from_cli("allow_space")) {
+            args.allow_space = allow_space;
+        }
+        if let Some(exclude_ambiguous) = self.exclude_ambiguous.filter(|_| !from_cli("exclude_ambiguous")) {
+            args.exclude_ambiguous = exclude_ambiguous;
+        }
+        if let Some(exclude) = self.exclude.as_ref().filter(|_| !from_cli("exclude")) {
+            args.exclude = Some(exclude.clone());
+        }
+        // Any choice of mode or character classes on the command line replaces the charset
+        let mode_from_cli = [
+            "charset", "passphrase", "pronounceable", "template", "hashcat_mask", "pin",
+            "classes", "exclude_symbols", "extended_symbols", "allow_space", "url_safe",
+        ].iter().any(|id| from_cli(id));
+        if let Some(charset) = self.charset.as_ref().filter(|_| !mode_from_cli) {
+            args.charset = Some(charset.clone());
+        }
+        if let Some(min) = self.min_digits.filter(|_| !from_cli("min_digits")) {
+            args.min_digits = min;
+        }
+        if let Some(min) = self.min_upper.filter(|_| !from_cli("min_upper")) {
+            args.min_upper = min;
+        }
+        if let Some(min) = self.min_lower.filter(|_| !from_cli("min_lower")) {
+            args.min_lower = min;
+        }
+        if let Some(min) = self.min_symbols.filter(|_| !from_cli("min_symbols")) {
+            args.min_symbols = min;
+        }
+        if let Some(max) = self.max_digits.filter(|_| !from_cli("max_digits")) {
+            args.max_digits = Some(max);
+        }
+        if let Some(max) = self.max_upper.filter(|_| !from_cli("max_upper")) {
+            args.max_upper = Some(max);
+        }
+        if let Some(max) = self.max_lower.filter(|_| !from_cli("max_lower")) {
+            args.max_lower = Some(max);
+        }
+        if let Some(max) = self.max_symbols.filter(|_| !from_cli("max_symbols")) {
+            args.max_symbols = Some(max);
+        }
+        if let Some(unique) = self.unique.filter(|_| !from_cli("unique")) {
+            args.unique = unique;
+        }
+        if let Some(no_repeat) = self.no_adjacent_repeat.filter(|_| !from_cli("no_adjacent_repeat")) {
+            args.no_adjacent_repeat = no_repeat;
+        }
+        if let Some(start) = self.start_with.as_ref().filter(|_| !from_cli("start_with")) {
+            args.start_with = Some(start.parse().expect("first character class validated when the config was loaded"));
+        }
+        if let Some(edges) = self.no_symbol_edges.filter(|_| !from_cli("no_symbol_edges")) {
+            args.no_symbol_edges = edges;
+        }
+        if let Some(run) = self.no_sequential.filter(|_| !from_cli("no_sequential")) {
+            args.no_sequential = Some(run);
+        }
+        if let Some(run) = self.no_keyboard_walk.filter(|_| !from_cli("no_keyboard_walk")) {
+            args.no_keyboard_walk = Some(run);
+        }
+        if let Some(min_length) = self.no_dictionary_words.filter(|_| !from_cli("no_dictionary_words")) {
+            args.no_dictionary_words = Some(min_length);
+        }
+        if let Some(layout) = self.keyboard_layout.as_ref().filter(|_| !from_cli("keyboard_layout")) {
+            args.keyboard_layout = layout.parse().expect("layout validated when the config was loaded");
+        }
+        if let Some(candidates) = self.optimize_typing.filter(|_| !from_cli("optimize_typing")) {
+            args.optimize_typing = Some(candidates);
+        }
+        // A wordlist or language on the command line replaces both from the file
+        let words_from_cli = from_cli("wordlist") || from_cli("language");
+        if let Some(wordlist) = self.wordlist.as_ref().filter(|_| !words_from_cli) {
+            args.wordlist = Some(wordlist.clone());
+        }
+        if let Some(language) = self.language.as_ref().filter(|_| !words_from_cli) {
+            args.language = language.parse().expect("language validated when the config was loaded");
+        }
+        if let Some(blocklist) = self.blocklist.as_ref().filter(|_| !from_cli("blocklist")) {
+            args.blocklist = Some(blocklist.clone());
+        }
+        if let Some(clear_after) = self.clear_after.filter(|_| !from_cli("clear_after")) {
+            args.clear_after = clear_after;
+        }
+        // Passwords that are copied or shown as QR codes are never formatted
+        let output_from_cli = ["format", "copy", "qr"].iter().any(|id| from_cli(id));
+        if let Some(format) = self.format.as_ref().filter(|_| !output_from_cli) {
+            args.format = OutputFormat::from_str(format, true).expect("format validated when the config was loaded");
+        }
+        if let Some(name) = self.name.as_ref().filter(|_| !from_cli("name")) {
+            args.name = Some(name.clone());
+        }
+        // A recipient on the command line replaces the file's
+        let log_recipient_from_cli = from_cli("log_gpg") || from_cli("log_age");
+        if (self.log_gpg.is_some() || self.log_age.is_some()) && !log_recipient_from_cli {
+            args.log_gpg = self.log_gpg.clone();
+            args.log_age = self.log_age.as_ref()
+                .map(|recipient| encrypt::parse_age_recipient(recipient).expect("recipient validated when the config was loaded"));
+        }
+        // A destination replaces the one set before it, and any choice of
+        // destination on the command line replaces the file's
+        let destination_from_cli = [
+            "copy", "out", "store", "dotenv", "stream", "qr", "encrypt_gpg", "encrypt_age",
+        ].iter().any(|id| from_cli(id));
+        if self.destinations() > 0 && !destination_from_cli {
+            args.copy = self.copy == Some(true);
+            args.out = self.out.clone();
+            args.store = self.store.as_ref()
+                .map(|store| store.parse().expect("store validated when the config was loaded"));
+            args.dotenv = self.dotenv.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn apply_to(config: &str, argv: &[&str]) -> Args {
+        let config = Config::parse(config).unwrap();
+        let matches = Args::command().get_matches_from(argv);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.apply(&mut args, &matches);
+        args
+    }
+
+    #[test]
+    fn test_config_defaults_apply() {
+        let args = apply_to("length = 20\nextended-symbols = true\n", &["password"]);
+        assert_eq!(args.length, 20);
+        assert!(args.extended_symbols);
+        assert!(!args.exclude_symbols);
+
+        let args = apply_to("start-with = \"letter\"\nno-symbol-edges = true\nmax-symbols = 2\n", &["password"]);
+        assert_eq!(args.start_with, Some(StartWith::Letter));
+        assert!(args.no_symbol_edges);
+        assert_eq!(args.max_symbols, Some(2));
+    }
+
+    #[test]
+    fn test_cli_overrides_config() {
+        let args = apply_to("length = 20\nextended-symbols = true\n", &["password", "-l", "64", "-n"]);
+        assert_eq!(args.length, 64);
+        assert!(args.exclude_symbols);
+        assert!(!args.extended_symbols);
+    }
+
+    #[test]
+    fn test_config_language() {
+        let config = "wordlist = \"/home/me/words.txt\"\nlanguage = \"de\"\n";
+        let args = apply_to(config, &["password", "-w", "4"]);
+        assert_eq!(args.language, Language::German);
+        assert!(args.wordlist.is_some());
+
+        let args = apply_to(config, &["password", "-w", "4", "--language", "fr"]);
+        assert_eq!(args.language, Language::French);
+        assert_eq!(args.wordlist, None);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let config = Config::parse(r#"
+            length = 20
+            copy = true
+
+            [profile.work-db]
+            length = 40
+            format = "json"
+            out = "db-password.json"
+
+            [profile.personal]
+            exclude-ambiguous = true
+        "#).unwrap();
+
+        let matches = Args::command().get_matches_from(["password", "--profile", "work-db"]);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.apply(&mut args, &matches);
+        config.profile("work-db").unwrap().apply(&mut args, &matches);
+        assert_eq!(args.length, 40);
+        assert_eq!(args.format, OutputFormat::Json);
+        // The profile's destination replaces the file's
+        assert!(!args.copy);
+        assert_eq!(args.out, Some(PathBuf::from("db-password.json")));
+
+        // Settings the profile leaves alone keep the file's
+        let mut args = apply_to("length = 20\ncopy = true\n", &["password"]);
+        config.profile("personal").unwrap().apply(&mut args, &Args::command().get_matches_from(["password"]));
+        assert_eq!(args.length, 20);
+        assert!(args.copy);
+        assert!(args.exclude_ambiguous);
+
+        // A destination on the command line replaces the profile's
+        let matches = Args::command().get_matches_from(["password", "--store", "pass:db"]);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        config.profile("work-db").unwrap().apply(&mut args, &matches);
+        assert_eq!(args.out, None);
+        assert!(args.store.is_some());
+
+        match config.profile("home") {
+            Err(PasswordError::UnknownProfile(reason)) => assert!(reason.contains("personal, work-db")),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_config_log_recipient() {
+        let args = apply_to("log-age = \"age1abc\"\n", &["password", "--log"]);
+        assert_eq!(args.log_age, Some(encrypt::AgeRecipient::Key("age1abc".to_string())));
+        assert_eq!(args.log_gpg, None);
+
+        let args = apply_to("log-age = \"age1abc\"\n", &["password", "--log", "--log-gpg", "ops@example.com"]);
+        assert_eq!(args.log_gpg.as_deref(), Some("ops@example.com"));
+        assert_eq!(args.log_age, None);
+
+        assert!(Config::parse("log-gpg = \"ops@example.com\"\nlog-age = \"age1abc\"\n").unwrap_err().contains("only one"));
+        assert!(Config::parse("log-gpg = \"--homedir\"\n").is_err());
+    }
+
+    #[test]
+    fn test_invalid_profiles() {
+        assert!(Config::parse("[profile.db]\nlength = 0\n").unwrap_err().contains("'db'"));
+        assert!(Config::parse("[profile.db]\ncolour = \"blue\"\n").is_err());
+        assert!(Config::parse("[profile.db.profile.inner]\nlength = 20\n").unwrap_err().contains("nested"));
+        assert!(Config::parse("[profile.db]\ncopy = true\nout = \"x\"\n").unwrap_err().contains("only one"));
+        assert!(Config::parse("[profile.db]\nstore = \"keychain:x\"\n").is_err());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Config::parse("length = 0").is_err());
+        assert!(Config::parse("length = 1048577").is_err());
+        assert!(Config::parse("length-limit = 0").is_err());
+        assert!(Config::parse("no-sequential = 1").is_err());
+        assert!(Config::parse("keyboard-layout = \"dvorak\"").is_err());
+        assert!(Config::parse("lenght = 20").is_err());
+        assert!(Config::parse("no-symbols = \"yes\"").is_err());
+        assert!(Config::parse("format = \"xml\"").is_err());
+        assert!(Config::parse("language = \"nl\"").is_err());
+        assert!(Config::parse("start-with = \"symbol\"").is_err());
+        assert!(Config::parse("no-dictionary-words = 2").is_err());
+        assert!(Config::parse("optimize-typing = 1").is_err());
+        assert!(Config::parse("").is_ok());
+    }
+}
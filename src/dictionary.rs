@@ -0,0 +1,76 @@
+//! Rejects random passwords that happen to contain an English word
+//! (`--no-dictionary-words`). Compliance scanners flag strings like
+//! `x7#Tiger9q` even though the word arose by chance, so candidates holding a
+//! word from the embedded EFF large wordlist are drawn again.
+
+use std::collections::HashSet;
+
+use once_cell::sync::Lazy;
+
+use crate::Secret;
+
+/// Every word of the EFF large wordlist made only of letters
+static WORDS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
+    include_str!("wordlists/eff_large.txt")
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .filter(|word| word.chars().all(|c| c.is_ascii_alphabetic()))
+        .collect()
+});
+
+/// Length of the longest word in the list
+static LONGEST: Lazy<usize> = Lazy::new(|| WORDS.iter().map(|word| word.len()).max().unwrap_or(0));
+
+/// Whether `text` contains a word of at least `min_length` letters, ignoring
+/// case. Words are only matched within a run of letters, so `ti7ger` holds no
+/// `tiger`.
+pub fn contains_word(text: &str, min_length: u16) -> bool {
+    let min_length = (min_length as usize).max(1);
+    let mut letters = Secret::new(String::with_capacity(text.len()));
+
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphabetic() {
+            letters.push(c.to_ascii_lowercase());
+            continue;
+        }
+
+        let run = letters.as_str();
+        for start in 0..run.len() {
+            let longest = (run.len() - start).min(*LONGEST);
+            if (min_length..=longest).any(|length| WORDS.contains(&run[start..start + length])) {
+                return true;
+            }
+        }
+        letters.clear();
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_list() {
+        assert!(WORDS.len() > 7700);
+        assert!(WORDS.contains("abacus"));
+        assert!(!WORDS.contains("t-shirt"));
+        assert_eq!(*LONGEST, 9);
+    }
+
+    #[test]
+    fn test_contains_word() {
+        assert!(contains_word("x7#Tiger9q", 4));
+        assert!(contains_word("qqABACUSqq", 4));
+        assert!(contains_word("zzzcrispyzzz", 6));
+        assert!(!contains_word("ti7ger", 4));
+        assert!(!contains_word("Q7#xZ9$kWp", 4));
+        assert!(!contains_word("", 4));
+
+        // The minimum length decides which words count
+        assert!(contains_word("9#aim#0", 3));
+        assert!(!contains_word("9#aim#0", 4));
+        assert!(!contains_word("x7#Tiger9q", 6));
+    }
+}
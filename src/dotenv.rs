@@ -0,0 +1,181 @@
+//! `--dotenv`: set a variable in a `.env` file to the generated secret,
+//! replacing its old value or appending it, without printing the secret.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use password::{PasswordError, Secret};
+
+use crate::outfile;
+
+/// Characters that some loaders treat specially inside double quotes:
+/// dotenv-expand, docker compose and Ruby dotenv expand `$VAR`, and Node's
+/// dotenv doesn't unescape `\\` or `\"`
+const DOUBLE_QUOTE_SPECIAL: [char; 3] = ['$', '\\', '"'];
+
+/// Quote `value` for a dotenv file so that every common loader reads it back
+/// unchanged: in single quotes, or in double quotes when it contains `'`.
+/// A value with both `'` and one of `$`, `\` or `"` has no such quoting.
+pub fn quote(value: &str) -> Option<Secret> {
+    let quote = if !value.contains('\'') {
+        '\''
+    } else if !value.contains(DOUBLE_QUOTE_SPECIAL) {
+        '"'
+    } else {
+        return None;
+    };
+
+    let mut quoted = Secret::new(String::with_capacity(value.len() + 2));
+    quoted.push(quote);
+    quoted.push_str(value);
+    quoted.push(quote);
+    Some(quoted)
+}
+
+/// Whether `line` assigns `name`, as `NAME=...` or `export NAME=...`
+fn assigns(line: &str, name: &str) -> bool {
+    let line = line.trim_start();
+    let line = line.strip_prefix("export ").map(str::trim_start).unwrap_or(line);
+    line.strip_prefix(name).is_some_and(|rest| rest.trim_start().starts_with('='))
+}
+
+/// `contents` with `name` set to `value`: the first assignment is replaced in
+/// place (keeping an `export` prefix), later ones are dropped, and a new
+/// variable is appended at the end. `None` when `value` can't be quoted.
+fn update(contents: &str, name: &str, value: &str) -> Option<Secret> {
+    let quoted = quote(value)?;
+    let assignment = |line: &str| {
+        let mut assignment = Secret::new(String::with_capacity(name.len() + quoted.len() + 8));
+        if line.trim_start().starts_with("export ") {
+            assignment.push_str("export ");
+        }
+        assignment.push_str(name);
+        assignment.push('=');
+        assignment.push_str(&quoted);
+        assignment
+    };
+
+    let mut updated = Secret::new(String::with_capacity(contents.len() + name.len() + value.len() + 8));
+    let mut found = false;
+    for line in contents.lines() {
+        if !assigns(line, name) {
+            updated.push_str(line);
+            updated.push('\n');
+        } else if !found {
+            found = true;
+            updated.push_str(&assignment(line));
+            updated.push('\n');
+        }
+    }
+    if !found {
+        updated.push_str(&assignment(""));
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+/// A scratch file next to `path`, so it can be renamed over it
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+/// Set `name` to `value` in the dotenv file at `path`, creating it if needed.
+/// The new contents are written to a private scratch file and renamed over
+/// the old one, so readers never see a half-written file.
+pub fn set(path: &Path, name: &str, value: &str) -> Result<(), PasswordError> {
+    let error = |reason: String| PasswordError::Dotenv(format!("{}: {}", path.display(), reason));
+
+    let (contents, permissions) = match fs::read_to_string(path) {
+        Ok(contents) => (Secret::new(contents), fs::metadata(path).ok().map(|metadata| metadata.permissions())),
+        Err(err) if err.kind() == ErrorKind::NotFound => (Secret::new(String::new()), None),
+        Err(err) => return Err(error(err.to_string())),
+    };
+    let updated = update(&contents, name, value).ok_or_else(|| error(
+        "the secret contains ' together with $, \\ or \", which no quoting keeps literal in every dotenv loader; \
+        generate one without ' (leave out --extended, or ' from --charset)".to_string()
+    ))?;
+
+    let scratch = temporary_path(path);
+    let _ = fs::remove_file(&scratch);
+    let result = outfile::create_private(&scratch)
+        .and_then(|mut file| {
+            file.write_all(updated.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| match permissions {
+            // Keep the mode of an existing file, in case others are meant to read it
+            Some(permissions) => fs::set_permissions(&scratch, permissions),
+            None => Ok(()),
+        })
+        .and_then(|_| fs::rename(&scratch, path));
+
+    result.map_err(|err| {
+        let _ = fs::remove_file(&scratch);
+        error(err.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Read a quoted value back the way the strictest loaders do: nothing is
+    /// unescaped, and `$` in double quotes would be expanded
+    fn unquote(quoted: &str) -> Option<&str> {
+        if let Some(value) = quoted.strip_prefix('\'').and_then(|rest| rest.strip_suffix('\'')) {
+            return (!value.contains('\'')).then_some(value);
+        }
+        let value = quoted.strip_prefix('"')?.strip_suffix('"')?;
+        (!value.contains(DOUBLE_QUOTE_SPECIAL)).then_some(value)
+    }
+
+    #[test]
+    fn test_quote() {
+        for value in ["a$b\"c\\d#e", "it's", "it's #1 `x`", "plain"] {
+            let quoted = quote(value).unwrap();
+            assert_eq!(unquote(&quoted), Some(value), "{}", value);
+        }
+        assert_eq!(*quote("it's").unwrap(), "\"it's\"");
+
+        for value in ["it's $HOME", "it's \\", "it's \"x\"", "'$\\"] {
+            assert!(quote(value).is_none(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let contents = "# database\nDB_HOST=localhost\nexport DB_PASSWORD = old\nDB_PASSWORD_HINT=x\nDB_PASSWORD=older\n";
+        assert_eq!(
+            *update(contents, "DB_PASSWORD", "new").unwrap(),
+            "# database\nDB_HOST=localhost\nexport DB_PASSWORD='new'\nDB_PASSWORD_HINT=x\n"
+        );
+        assert_eq!(*update("A=1", "B", "2").unwrap(), "A=1\nB='2'\n");
+        assert_eq!(*update("", "B", "2").unwrap(), "B='2'\n");
+    }
+
+    #[test]
+    fn test_set() {
+        let path = env::temp_dir().join(format!("password-dotenv-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        set(&path, "API_KEY", "one").unwrap();
+        set(&path, "DB_PASSWORD", "two").unwrap();
+        set(&path, "API_KEY", "three").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "API_KEY='three'\nDB_PASSWORD='two'\n");
+        assert!(!temporary_path(&path).exists());
+
+        // A value no quoting keeps literal leaves the file as it was
+        assert!(matches!(set(&path, "API_KEY", "it's $4\\"), Err(PasswordError::Dotenv(reason)) if reason.contains("'")));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "API_KEY='three'\nDB_PASSWORD='two'\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
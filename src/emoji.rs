@@ -0,0 +1,60 @@
+//! A curated emoji pool for `--emoji` and `--emoji-only`.
+//!
+//! Every emoji in the pool is a single code point that renders as an emoji
+//! on its own (Emoji_Presentation), so no variation selector is needed. The
+//! pool leaves out skin tone modifiers, regional indicators and anything that
+//! joins with its neighbours, so each drawn character is exactly one
+//! grapheme and a password of length N shows N symbols however it is mixed.
+
+use once_cell::sync::Lazy;
+
+/// Code point ranges of the pool: plants, food, animals, smileys and transport
+const RANGES: [(u32, u32); 5] = [
+    (0x1F330, 0x1F335),
+    (0x1F337, 0x1F37C),
+    (0x1F400, 0x1F43E),
+    (0x1F600, 0x1F64F),
+    (0x1F680, 0x1F6C5),
+];
+
+/// The emoji `--emoji` adds to the pool
+pub static EMOJI: Lazy<Vec<char>> = Lazy::new(|| {
+    RANGES
+        .iter()
+        .flat_map(|&(start, end)| start..=end)
+        .map(|code| char::from_u32(code).expect("emoji ranges are valid code points"))
+        .collect()
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::CharClass;
+    use crate::{generate_from_charset, get_secure_rng, grapheme_len, password_entropy};
+
+    #[test]
+    fn test_pool() {
+        assert_eq!(EMOJI.len(), 289);
+        assert!(EMOJI.contains(&'🐙') && EMOJI.contains(&'🚀') && EMOJI.contains(&'🍕'));
+
+        // No modifiers, regional indicators, selectors or joiners
+        for &c in EMOJI.iter() {
+            assert!(!('\u{1F3FB}'..='\u{1F3FF}').contains(&c), "{:?}", c);
+            assert!(!('\u{1F1E6}'..='\u{1F1FF}').contains(&c), "{:?}", c);
+            assert!(CharClass::Symbol.contains(c));
+        }
+        let mut sorted = EMOJI.clone();
+        sorted.dedup();
+        assert_eq!(sorted.len(), EMOJI.len());
+    }
+
+    #[test]
+    fn test_every_emoji_is_one_grapheme() {
+        let mut charset = EMOJI.clone();
+        charset.extend("aZ9!".chars());
+        let password = generate_from_charset(&mut get_secure_rng().unwrap(), 64, &charset).unwrap();
+        assert_eq!(password.chars().count(), 64);
+        assert_eq!(grapheme_len(&password), 64);
+        assert!((password_entropy(64, charset.len()) - 64.0 * 293f64.log2()).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,210 @@
+//! `--encrypt-gpg` and `--encrypt-age`: emit the output as ciphertext for a
+//! recipient instead of plaintext, so it can be mailed or committed to a
+//! secrets repository. The same tools decrypt the secrets `--log` keeps in
+//! the history file.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use password::{PasswordError, Secret};
+
+/// Who the output is encrypted for
+#[derive(Clone, Debug, PartialEq)]
+pub enum Recipient {
+    /// A key in the GnuPG keyring (user ID, email or fingerprint)
+    Gpg(String),
+    /// An age recipient
+    Age(AgeRecipient),
+}
+
+/// An age public key, or a file of them as `age -R` reads
+#[derive(Clone, Debug, PartialEq)]
+pub enum AgeRecipient {
+    Key(String),
+    File(PathBuf),
+}
+
+/// Parse an age recipient: a public key (`age1...`, or an `ssh-ed25519` or
+/// `ssh-rsa` key), otherwise the path of a recipients file
+pub fn parse_age_recipient(s: &str) -> Result<AgeRecipient, String> {
+    let s = validate_recipient(s)?;
+    if s.starts_with("age1") || s.starts_with("ssh-ed25519 ") || s.starts_with("ssh-rsa ") {
+        Ok(AgeRecipient::Key(s))
+    } else {
+        Ok(AgeRecipient::File(PathBuf::from(s)))
+    }
+}
+
+/// Accept a recipient that can't be mistaken for an option
+pub fn validate_recipient(s: &str) -> Result<String, String> {
+    if s.trim().is_empty() || s.starts_with('-') {
+        return Err(format!("The recipient must be a key, user ID or file and can't start with '-'. Got: {:?}", s));
+    }
+    Ok(s.to_string())
+}
+
+/// The command that encrypts its stdin to `recipient`, writing ASCII-armored
+/// ciphertext to stdout
+fn command(recipient: &Recipient) -> Command {
+    match recipient {
+        Recipient::Gpg(key) => {
+            let mut command = Command::new("gpg");
+            command.args(["--batch", "--quiet", "--armor", "--encrypt", "--recipient", key, "--output", "-"]);
+            command
+        }
+        Recipient::Age(recipient) => {
+            let mut command = Command::new("age");
+            command.args(["--encrypt", "--armor"]);
+            match recipient {
+                AgeRecipient::Key(key) => command.arg("--recipient").arg(key),
+                AgeRecipient::File(path) => command.arg("--recipients-file").arg(path),
+            };
+            command
+        }
+    }
+}
+
+/// Encrypt `plaintext` to `recipient`. The plaintext only travels over the
+/// encryption tool's stdin.
+pub fn encrypt(recipient: &Recipient, plaintext: &str) -> Result<String, PasswordError> {
+    run(command(recipient), &[plaintext, "\n"], PasswordError::Encrypt).map(|ciphertext| ciphertext.to_string())
+}
+
+/// Armor headers of GPG and age ciphertext
+const GPG_ARMOR: &str = "-----BEGIN PGP MESSAGE-----";
+const AGE_ARMOR: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// The command that decrypts the ASCII-armored `ciphertext` on its stdin:
+/// gpg with the user's keyring, or age with the `identity` file
+fn decrypt_command(ciphertext: &str, identity: Option<&Path>) -> Result<Command, String> {
+    if ciphertext.starts_with(GPG_ARMOR) {
+        let mut command = Command::new("gpg");
+        command.args(["--batch", "--quiet", "--decrypt", "--output", "-"]);
+        Ok(command)
+    } else if ciphertext.starts_with(AGE_ARMOR) {
+        let identity = identity.ok_or("age: --identity is needed to decrypt age records")?;
+        let mut command = Command::new("age");
+        command.arg("--decrypt").arg("--identity").arg(identity);
+        Ok(command)
+    } else {
+        Err("gpg or age: the record is neither GPG nor age ciphertext".to_string())
+    }
+}
+
+/// Decrypt ciphertext written by [`encrypt`]. The plaintext only travels over
+/// the decryption tool's stdout.
+pub fn decrypt(ciphertext: &str, identity: Option<&Path>) -> Result<Secret, PasswordError> {
+    let command = decrypt_command(ciphertext, identity).map_err(PasswordError::Decrypt)?;
+    run(command, &[ciphertext], PasswordError::Decrypt)
+}
+
+/// Run `command` with `input` on its stdin and return its stdout without
+/// trailing whitespace. The input is written from another thread while the
+/// output is drained, so a tool that fills its stdout pipe before it has
+/// read all of stdin can't deadlock with us.
+fn run(mut command: Command, input: &[&str], kind: fn(String) -> PasswordError) -> Result<Secret, PasswordError> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let error = |reason: String| kind(format!("{}: {}", program, reason));
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| error(format!("could not run {} ({})", program, err)))?;
+    let stdin = child.stdin.take();
+
+    let (written, output) = thread::scope(|scope| {
+        let writer = scope.spawn(move || {
+            if let Some(mut stdin) = stdin {
+                for part in input {
+                    stdin.write_all(part.as_bytes())?;
+                }
+            }
+            Ok(())
+        });
+        let output = child.wait_with_output();
+        (writer.join().unwrap_or_else(|_| Err(std::io::Error::other("stdin writer panicked"))), output)
+    });
+
+    // A tool that fails usually closes stdin early, so its exit status says more than the broken pipe
+    let output = output.map_err(|err| error(err.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(error(format!("exited with {}: {}", output.status, stderr.trim())));
+    }
+    written.map_err(|err| error(err.to_string()))?;
+
+    let mut stdout = Secret::new(String::from_utf8(output.stdout).map_err(|_| error("output is not text".to_string()))?);
+    let len = stdout.trim_end().len();
+    stdout.truncate(len);
+    Ok(stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_recipient() {
+        assert!(validate_recipient("ops@example.com").is_ok());
+        assert!(validate_recipient("0x1234ABCD").is_ok());
+        assert!(validate_recipient("").is_err());
+        assert!(validate_recipient("--homedir").is_err());
+    }
+
+    #[test]
+    fn test_gpg_command() {
+        let command = command(&Recipient::Gpg("ops@example.com".to_string()));
+        assert_eq!(command.get_program(), "gpg");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["--batch", "--quiet", "--armor", "--encrypt", "--recipient", "ops@example.com", "--output", "-"]
+        );
+    }
+
+    #[test]
+    fn test_age_command() {
+        let key = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+        let recipient = parse_age_recipient(key).unwrap();
+        assert_eq!(recipient, AgeRecipient::Key(key.to_string()));
+        let age = command(&Recipient::Age(recipient));
+        assert_eq!(age.get_program(), "age");
+        assert_eq!(age.get_args().collect::<Vec<_>>(), ["--encrypt", "--armor", "--recipient", key]);
+
+        let recipient = parse_age_recipient("team.pub").unwrap();
+        assert_eq!(recipient, AgeRecipient::File(PathBuf::from("team.pub")));
+        let age = command(&Recipient::Age(recipient));
+        assert_eq!(age.get_args().collect::<Vec<_>>(), ["--encrypt", "--armor", "--recipients-file", "team.pub"]);
+
+        assert!(parse_age_recipient("ssh-ed25519 AAAAC3Nza user@host").is_ok_and(|r| matches!(r, AgeRecipient::Key(_))));
+        assert!(parse_age_recipient("-R").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_large_input() {
+        // Far more than a pipe buffer each way: writing it all before reading would deadlock
+        let plaintext = "0123456789abcdef".repeat(96 * 1024);
+        let output = run(Command::new("cat"), &[&plaintext, "\n"], PasswordError::Encrypt).unwrap();
+        assert_eq!(output.len(), plaintext.len());
+        assert_eq!(output.as_str(), plaintext);
+    }
+
+    #[test]
+    fn test_decrypt_command() {
+        let gpg = decrypt_command("-----BEGIN PGP MESSAGE-----\n\nhQEMA...", None).unwrap();
+        assert_eq!(gpg.get_program(), "gpg");
+        assert_eq!(gpg.get_args().collect::<Vec<_>>(), ["--batch", "--quiet", "--decrypt", "--output", "-"]);
+
+        let armored = "-----BEGIN AGE ENCRYPTED FILE-----\nYWdl...";
+        assert!(decrypt_command(armored, None).is_err());
+        let age = decrypt_command(armored, Some(Path::new("key.txt"))).unwrap();
+        assert_eq!(age.get_program(), "age");
+        assert_eq!(age.get_args().collect::<Vec<_>>(), ["--decrypt", "--identity", "key.txt"]);
+
+        assert!(decrypt_command("plaintext", None).is_err());
+    }
+}
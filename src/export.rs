@@ -0,0 +1,360 @@
+//! Import files for password managers, so a batch of generated credentials
+//! can be loaded in one step instead of copied over by hand.
+//!
+//! The account details of each entry come from batch spec keys (`title=`,
+//! `username=`, `url=`, `notes=`, `folder=`). Formats that wrap their
+//! records in a document (KeePass XML, Bitwarden JSON) are written as a
+//! header, one record per line and a footer, so they can be streamed.
+
+use password::Secret;
+use serde::Serialize;
+
+use crate::output::Generated;
+
+/// What a password is for, as far as a password manager cares
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Account {
+    pub title: Option<String>,
+    pub username: Option<String>,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+    /// Group or folder to file the entry under
+    pub folder: Option<String>,
+}
+
+impl Account {
+    /// Bytes in all of the fields, to size a record up front so it never
+    /// reallocates and leaves a copy of the password behind
+    fn text_len(&self) -> usize {
+        [&self.title, &self.username, &self.url, &self.notes, &self.folder]
+            .iter()
+            .map(|field| field.as_deref().map_or(0, str::len))
+            .sum()
+    }
+}
+
+/// Append `value` to `row` as a quoted CSV field. Every field is quoted, as
+/// KeePassXC does in its own exports.
+fn push_quoted(value: &str, row: &mut Secret) {
+    row.push('"');
+    for c in value.chars() {
+        if c == '"' {
+            row.push('"');
+        }
+        row.push(c);
+    }
+    row.push('"');
+}
+
+/// Room for a CSV row of `fields` quoted fields holding `text` bytes, each
+/// of which may be a doubled quote
+fn csv_capacity(text: usize, fields: usize) -> usize {
+    text * 2 + fields * 3
+}
+
+/// Header row of `--format keepass-csv`, in the column order KeePassXC exports
+pub const KEEPASS_CSV_HEADER: &str = r#""Group","Title","Username","Password","URL","Notes""#;
+
+/// One `--format keepass-csv` row
+pub fn keepass_csv_record(entry: &Generated) -> Secret {
+    let account = &entry.account;
+    let mut row = Secret::new(String::with_capacity(csv_capacity(account.text_len() + entry.password.len(), 6)));
+    let fields = [
+        account.folder.as_deref(),
+        account.title.as_deref(),
+        account.username.as_deref(),
+        Some(entry.password.as_str()),
+        account.url.as_deref(),
+        account.notes.as_deref(),
+    ];
+    for (index, field) in fields.into_iter().enumerate() {
+        if index > 0 {
+            row.push(',');
+        }
+        push_quoted(field.unwrap_or(""), &mut row);
+    }
+    row
+}
+
+/// Header row of `--format 1password-csv`, in the column order 1Password's
+/// CSV importer suggests
+pub const ONEPASSWORD_CSV_HEADER: &str = r#""Title","Website","Username","Password","Notes""#;
+
+/// One `--format 1password-csv` row
+pub fn onepassword_csv_record(entry: &Generated) -> Secret {
+    let account = &entry.account;
+    let mut row = Secret::new(String::with_capacity(csv_capacity(account.text_len() + entry.password.len(), 5)));
+    let fields = [
+        account.title.as_deref(),
+        account.url.as_deref(),
+        account.username.as_deref(),
+        Some(entry.password.as_str()),
+        account.notes.as_deref(),
+    ];
+    for (index, field) in fields.into_iter().enumerate() {
+        if index > 0 {
+            row.push(',');
+        }
+        push_quoted(field.unwrap_or(""), &mut row);
+    }
+    row
+}
+
+/// Escape text for an XML element or attribute
+fn xml_escape(value: &str, escaped: &mut Secret) {
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+}
+
+/// Start of a `--format keepass-xml` document: a KeePass 2.x XML file with
+/// every entry in one group
+pub const KEEPASS_XML_HEADER: &str = "\
+<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>
+<KeePassFile>
+\t<Root>
+\t\t<Group>
+\t\t\t<Name>Generated</Name>";
+
+/// End of a `--format keepass-xml` document
+pub const KEEPASS_XML_FOOTER: &str = "\
+\t\t</Group>
+\t</Root>
+</KeePassFile>";
+
+/// One `<Entry>` of a `--format keepass-xml` document. KeePass has no
+/// folder field on entries, so a folder is recorded as a tag.
+pub fn keepass_xml_record(entry: &Generated) -> Secret {
+    let account = &entry.account;
+    let mut xml = Secret::new(String::with_capacity(entry.password.len() * 2 + 512));
+    xml.push_str("\t\t\t<Entry>");
+
+    if let Some(folder) = &account.folder {
+        xml.push_str("\n\t\t\t\t<Tags>");
+        xml_escape(folder, &mut xml);
+        xml.push_str("</Tags>");
+    }
+
+    let fields = [
+        ("Title", account.title.as_deref()),
+        ("UserName", account.username.as_deref()),
+        ("Password", Some(entry.password.as_str())),
+        ("URL", account.url.as_deref()),
+        ("Notes", account.notes.as_deref()),
+    ];
+    for (key, value) in fields {
+        xml.push_str("\n\t\t\t\t<String><Key>");
+        xml.push_str(key);
+        xml.push_str(if key == "Password" { "</Key><Value ProtectInMemory=\"True\">" } else { "</Key><Value>" });
+        xml_escape(value.unwrap_or(""), &mut xml);
+        xml.push_str("</Value></String>");
+    }
+
+    xml.push_str("\n\t\t\t</Entry>");
+    xml
+}
+
+/// The distinct folders of `accounts`, in order of first use
+pub fn folders<'a, I>(accounts: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a Account>,
+{
+    let mut folders = Vec::new();
+    for folder in accounts.into_iter().filter_map(|account| account.folder.as_deref()) {
+        if !folders.contains(&folder) {
+            folders.push(folder);
+        }
+    }
+    folders
+}
+
+/// A folder of a Bitwarden export. The importer only uses the id to match
+/// items to folders, so the name doubles as the id.
+#[derive(Serialize)]
+struct BitwardenFolder<'a> {
+    id: &'a str,
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct BitwardenUri<'a> {
+    #[serde(rename = "match")]
+    match_: Option<u8>,
+    uri: &'a str,
+}
+
+#[derive(Serialize)]
+struct BitwardenLogin<'a> {
+    username: Option<&'a str>,
+    password: &'a str,
+    uris: Vec<BitwardenUri<'a>>,
+    totp: Option<&'a str>,
+}
+
+/// A login item of a Bitwarden export
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BitwardenItem<'a> {
+    #[serde(rename = "type")]
+    kind: u8,
+    name: &'a str,
+    notes: Option<&'a str>,
+    favorite: bool,
+    folder_id: Option<&'a str>,
+    login: BitwardenLogin<'a>,
+}
+
+/// Item type of a login in Bitwarden exports
+const BITWARDEN_LOGIN: u8 = 1;
+
+/// Start of a `--format bitwarden-json` document, declaring `folders`
+pub fn bitwarden_header(folders: &[&str]) -> String {
+    let folders: Vec<BitwardenFolder> = folders.iter().map(|&name| BitwardenFolder { id: name, name }).collect();
+    format!(
+        r#"{{"encrypted":false,"folders":{},"items":["#,
+        serde_json::to_string(&folders).expect("folders are always serializable")
+    )
+}
+
+/// End of a `--format bitwarden-json` document
+pub const BITWARDEN_FOOTER: &str = "]}";
+
+/// The `--format bitwarden-json` item for the entry at (1-based) `index`.
+/// Items after the first start with the comma that separates them, so they
+/// can be written as they are generated. Untitled entries are named by index.
+pub fn bitwarden_record(index: usize, entry: &Generated) -> Secret {
+    let account = &entry.account;
+    let fallback_name;
+    let name = match &account.title {
+        Some(title) => title.as_str(),
+        None => {
+            fallback_name = format!("Generated password {}", index);
+            &fallback_name
+        }
+    };
+
+    let item = BitwardenItem {
+        kind: BITWARDEN_LOGIN,
+        name,
+        notes: account.notes.as_deref(),
+        favorite: false,
+        folder_id: account.folder.as_deref(),
+        login: BitwardenLogin {
+            username: account.username.as_deref(),
+            password: &entry.password,
+            uris: account.url.as_deref().map(|uri| BitwardenUri { match_: None, uri }).into_iter().collect(),
+            totp: None,
+        },
+    };
+
+    let mut record = Secret::new(if index > 1 { ",".to_string() } else { String::new() });
+    record.push_str(&serde_json::to_string(&item).expect("Bitwarden item is always serializable"));
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(password: &str, account: Account) -> Generated {
+        Generated {
+            password: Secret::new(password.to_string()),
+            charset_size: Some(88),
+            entropy_bits: 100.0,
+            hash: None,
+            account,
+            label: None,
+            mnemonic: None,
+        }
+    }
+
+    fn work_email() -> Account {
+        Account {
+            title: Some("Work \"email\"".to_string()),
+            username: Some("ann@example.com".to_string()),
+            url: Some("https://mail.example.com".to_string()),
+            notes: None,
+            folder: Some("Work".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_keepass_csv() {
+        assert_eq!(
+            keepass_csv_record(&entry("a,b\"c", work_email())).as_str(),
+            r#""Work","Work ""email""","ann@example.com","a,b""c","https://mail.example.com","""#
+        );
+        assert_eq!(keepass_csv_record(&entry("x", Account::default())).as_str(), r#""","","","x","","""#);
+
+        // Sized for the worst case up front, so the row never reallocates
+        let account = Account { notes: Some("\"".repeat(300)), ..work_email() };
+        let row = keepass_csv_record(&entry(&"\"".repeat(40), account.clone()));
+        assert_eq!(row.capacity(), csv_capacity(account.text_len() + 40, 6));
+    }
+
+    #[test]
+    fn test_onepassword_csv() {
+        assert_eq!(
+            onepassword_csv_record(&entry("a,b\"c", work_email())).as_str(),
+            r#""Work ""email""","https://mail.example.com","ann@example.com","a,b""c","""#
+        );
+        assert_eq!(ONEPASSWORD_CSV_HEADER.split(',').count(), 5);
+
+        let account = Account { notes: Some("\"".repeat(300)), ..work_email() };
+        let row = onepassword_csv_record(&entry(&"\"".repeat(40), account.clone()));
+        assert_eq!(row.capacity(), csv_capacity(account.text_len() + 40, 5));
+    }
+
+    #[test]
+    fn test_keepass_xml() {
+        let xml = keepass_xml_record(&entry("<&>", work_email()));
+        assert!(xml.starts_with("\t\t\t<Entry>\n\t\t\t\t<Tags>Work</Tags>"));
+        assert!(xml.contains("<Key>Title</Key><Value>Work &quot;email&quot;</Value>"));
+        assert!(xml.contains("<Key>Password</Key><Value ProtectInMemory=\"True\">&lt;&amp;&gt;</Value>"));
+        assert!(xml.contains("<Key>Notes</Key><Value></Value>"));
+        assert!(xml.ends_with("</Entry>"));
+        assert!(KEEPASS_XML_HEADER.starts_with("<?xml") && KEEPASS_XML_FOOTER.ends_with("</KeePassFile>"));
+    }
+
+    #[test]
+    fn test_bitwarden_json() {
+        let entries = [
+            entry("one", work_email()),
+            entry("two", Account::default()),
+            entry("three", Account { folder: Some("Home".to_string()), ..work_email() }),
+            entry("four", work_email()),
+        ];
+        let folders = folders(entries.iter().map(|entry| &entry.account));
+        assert_eq!(folders, ["Work", "Home"]);
+
+        let mut document = bitwarden_header(&folders);
+        for (i, entry) in entries.iter().enumerate() {
+            document.push('\n');
+            document.push_str(&bitwarden_record(i + 1, entry));
+        }
+        document.push_str(BITWARDEN_FOOTER);
+
+        let json: serde_json::Value = serde_json::from_str(&document).unwrap();
+        assert_eq!(json["encrypted"], false);
+        assert_eq!(json["folders"][1], serde_json::json!({"id": "Home", "name": "Home"}));
+        let items = json["items"].as_array().unwrap();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0]["type"], 1);
+        assert_eq!(items[0]["name"], "Work \"email\"");
+        assert_eq!(items[0]["folderId"], "Work");
+        assert_eq!(items[0]["login"]["username"], "ann@example.com");
+        assert_eq!(items[0]["login"]["password"], "one");
+        assert_eq!(items[0]["login"]["uris"][0]["uri"], "https://mail.example.com");
+        assert_eq!(items[1]["name"], "Generated password 2");
+        assert!(items[1]["folderId"].is_null());
+        assert_eq!(items[1]["login"]["uris"], serde_json::json!([]));
+        assert_eq!(items[2]["folderId"], "Home");
+    }
+}
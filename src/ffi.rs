@@ -0,0 +1,185 @@
+//! C bindings, built with the `ffi` feature. The header is generated into
+//! `include/password.h`.
+//!
+//! Strings returned by `pg_generate()` are owned by this library: release
+//! them with `pg_free()`, which overwrites the password before freeing it.
+//! Never pass them to the C allocator's `free`.
+
+use std::ffi::{c_char, CString};
+use std::ptr;
+use zeroize::Zeroizing;
+
+use crate::options::{self, Options};
+use crate::PasswordError;
+
+/// Result of a call into the library
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PgStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullPointer = 1,
+    /// The options can't produce a password (e.g. a length of 0, or extended symbols without symbols)
+    InvalidOptions = 2,
+    /// The operating system's random number generator could not be read
+    RngFailure = 3,
+}
+
+/// What to generate. Start from `pg_default_options()` and change the fields you need.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PgOptions {
+    pub length: u16,
+    pub symbols: bool,
+    pub extended_symbols: bool,
+    pub allow_space: bool,
+    pub exclude_ambiguous: bool,
+    /// Generate a passphrase of this many words instead (0 for a password)
+    pub words: u16,
+    /// Generate a pronounceable password of `length` characters instead
+    pub pronounceable: bool,
+}
+
+impl From<&PgOptions> for Options {
+    fn from(options: &PgOptions) -> Self {
+        Options {
+            length: options.length.into(),
+            symbols: options.symbols,
+            extended_symbols: options.extended_symbols,
+            allow_space: options.allow_space,
+            exclude_ambiguous: options.exclude_ambiguous,
+            words: (options.words > 0).then_some(options.words),
+            pronounceable: options.pronounceable,
+        }
+    }
+}
+
+/// The defaults used by the command-line tool: 36 characters with letters,
+/// digits and symbols
+#[no_mangle]
+pub extern "C" fn pg_default_options() -> PgOptions {
+    let defaults = Options::default();
+    PgOptions {
+        length: defaults.length as u16,
+        symbols: defaults.symbols,
+        extended_symbols: defaults.extended_symbols,
+        allow_space: defaults.allow_space,
+        exclude_ambiguous: defaults.exclude_ambiguous,
+        words: defaults.words.unwrap_or(0),
+        pronounceable: defaults.pronounceable,
+    }
+}
+
+/// Generate a password and store a NUL-terminated UTF-8 copy in `*out`.
+///
+/// On failure `*out` is set to NULL.
+///
+/// # Safety
+///
+/// `options` must point to a valid `PgOptions` and `out` to writable storage
+/// for a pointer. The string written to `*out` must be released with
+/// `pg_free()`.
+#[no_mangle]
+pub unsafe extern "C" fn pg_generate(options: *const PgOptions, out: *mut *mut c_char) -> PgStatus {
+    if out.is_null() {
+        return PgStatus::NullPointer;
+    }
+    *out = ptr::null_mut();
+
+    let Some(options) = options.as_ref() else {
+        return PgStatus::NullPointer;
+    };
+
+    let secret = match options::generate(&Options::from(options)) {
+        Ok(secret) => secret,
+        Err(PasswordError::RngInitializationError) => return PgStatus::RngFailure,
+        Err(_) => return PgStatus::InvalidOptions,
+    };
+
+    // Room for the terminator up front, so CString never reallocates and
+    // leaves a copy behind
+    let mut bytes = Vec::with_capacity(secret.len() + 1);
+    bytes.extend_from_slice(secret.as_bytes());
+    match CString::new(bytes) {
+        Ok(password) => {
+            *out = password.into_raw();
+            PgStatus::Ok
+        }
+        Err(err) => {
+            drop(Zeroizing::new(err.into_vec()));
+            PgStatus::InvalidOptions
+        }
+    }
+}
+
+/// Overwrite and free a password returned by `pg_generate()`. NULL is ignored.
+///
+/// # Safety
+///
+/// `password` must be NULL or a pointer returned by `pg_generate()` that
+/// hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn pg_free(password: *mut c_char) {
+    if password.is_null() {
+        return;
+    }
+    drop(Zeroizing::new(CString::from_raw(password).into_bytes_with_nul()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_generate_and_free() {
+        let options = PgOptions { length: 20, ..pg_default_options() };
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(pg_generate(&options, &mut out), PgStatus::Ok);
+            assert_eq!(CStr::from_ptr(out).to_bytes().len(), 20);
+            pg_free(out);
+            pg_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let options = PgOptions { words: 4, ..pg_default_options() };
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(pg_generate(&options, &mut out), PgStatus::Ok);
+            assert_eq!(CStr::from_ptr(out).to_str().unwrap().split(' ').count(), 4);
+            pg_free(out);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = PgOptions { symbols: false, extended_symbols: true, ..pg_default_options() };
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(pg_generate(&options, &mut out), PgStatus::InvalidOptions);
+            assert!(out.is_null());
+            assert_eq!(pg_generate(ptr::null(), &mut out), PgStatus::NullPointer);
+            assert_eq!(pg_generate(&options, ptr::null_mut()), PgStatus::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_zero_length() {
+        let options = PgOptions { length: 0, ..pg_default_options() };
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(pg_generate(&options, &mut out), PgStatus::InvalidOptions);
+            assert!(out.is_null());
+            let options = PgOptions { pronounceable: true, ..options };
+            assert_eq!(pg_generate(&options, &mut out), PgStatus::InvalidOptions);
+            assert!(out.is_null());
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Password hashes in the formats user databases store, so a generated
+//! password can be provisioned without a separate hashing utility.
+
+use argon2::password_hash::{PasswordHasher, SaltString};
+use argon2::Argon2;
+use md5::{Digest, Md5};
+use rand::Rng;
+use rand_core::{CryptoRng, RngCore};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::PasswordError;
+
+/// bcrypt cost used when none is given (2^12 rounds)
+pub const DEFAULT_BCRYPT_COST: u32 = 12;
+
+/// Longest password bcrypt hashes in full (its 72-byte key includes a NUL terminator)
+pub const BCRYPT_MAX_BYTES: usize = 71;
+
+/// SHA-512 crypt rounds used when none are given (the glibc default, left
+/// out of the hash string)
+pub const DEFAULT_SHA512_CRYPT_ROUNDS: u32 = 5_000;
+
+/// Characters crypt(3) salts are drawn from
+const CRYPT_SALT_CHARS: &[u8; 64] =
+    b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Hash formats that can be emitted alongside a password
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HashScheme {
+    /// PHC string (`$argon2id$v=19$...`) with the argon2 crate's defaults
+    /// (19 MiB, 2 iterations, 1 lane), as recommended by OWASP
+    Argon2id,
+    /// `$2b$` hash with 2^cost rounds
+    Bcrypt { cost: u32 },
+    /// crypt(3) `$6$` hash, as used in `/etc/shadow`
+    Sha512Crypt { rounds: u32 },
+    /// Apache's MD5-based `$apr1$` hash, for htpasswd files read by servers
+    /// without bcrypt support. Weak: prefer bcrypt where it's accepted.
+    Apr1,
+}
+
+impl FromStr for HashScheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, param) = match s.split_once(':') {
+            Some((name, param)) => (name, Some(param)),
+            None => (s, None),
+        };
+
+        match (name.to_ascii_lowercase().as_str(), param) {
+            ("argon2id", None) => Ok(HashScheme::Argon2id),
+            ("bcrypt", None) => Ok(HashScheme::Bcrypt { cost: DEFAULT_BCRYPT_COST }),
+            ("bcrypt", Some(cost)) => {
+                let cost = cost.parse().ok().filter(|cost| (4..=31).contains(cost)).ok_or_else(|| {
+                    format!("bcrypt cost must be between 4 and 31. Got: {}", cost)
+                })?;
+                Ok(HashScheme::Bcrypt { cost })
+            }
+            ("sha512-crypt", None) => Ok(HashScheme::Sha512Crypt { rounds: DEFAULT_SHA512_CRYPT_ROUNDS }),
+            ("sha512-crypt", Some(rounds)) => {
+                let rounds = rounds.parse().ok()
+                    .filter(|rounds| (1_000..=999_999_999).contains(rounds))
+                    .ok_or_else(|| format!(
+                        "sha512-crypt rounds must be between 1000 and 999999999. Got: {}", rounds
+                    ))?;
+                Ok(HashScheme::Sha512Crypt { rounds })
+            }
+            ("apr1", None) => Ok(HashScheme::Apr1),
+            _ => Err(format!(
+                "Unknown hash algorithm '{}' (expected argon2id, bcrypt[:cost], sha512-crypt[:rounds] or apr1)", s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for HashScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashScheme::Argon2id => f.write_str("argon2id"),
+            HashScheme::Bcrypt { cost } => write!(f, "bcrypt:{}", cost),
+            HashScheme::Sha512Crypt { rounds } => write!(f, "sha512-crypt:{}", rounds),
+            HashScheme::Apr1 => f.write_str("apr1"),
+        }
+    }
+}
+
+/// Hash `password` with a fresh random salt drawn from `rng`
+pub fn hash_password<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    password: &str,
+    scheme: HashScheme,
+) -> Result<String, PasswordError> {
+    match scheme {
+        HashScheme::Argon2id => {
+            let salt = SaltString::generate(rng);
+            Argon2::default()
+                .hash_password(password.as_bytes(), &salt)
+                .map(|hash| hash.to_string())
+                .map_err(|err| PasswordError::Hash(err.to_string()))
+        }
+        HashScheme::Bcrypt { cost } => {
+            if password.len() > BCRYPT_MAX_BYTES {
+                return Err(PasswordError::Hash(format!(
+                    "bcrypt only uses the first {} bytes, and this password is {} bytes long",
+                    BCRYPT_MAX_BYTES,
+                    password.len()
+                )));
+            }
+            let mut salt = [0u8; 16];
+            rng.fill_bytes(&mut salt);
+            bcrypt::non_truncating_hash_with_salt(password, cost, salt)
+                .map(|hash| hash.format_for_version(bcrypt::Version::TwoB))
+                .map_err(|err| PasswordError::Hash(err.to_string()))
+        }
+        HashScheme::Sha512Crypt { rounds } => sha512_crypt(password, &crypt_salt(rng, 16), rounds),
+        HashScheme::Apr1 => Ok(apr1(password, &crypt_salt(rng, 8))),
+    }
+}
+
+/// A salt of `length` characters from the crypt(3) alphabet
+fn crypt_salt<R: RngCore>(rng: &mut R, length: usize) -> String {
+    (0..length)
+        .map(|_| CRYPT_SALT_CHARS[rng.gen_range(0..CRYPT_SALT_CHARS.len())] as char)
+        .collect()
+}
+
+/// `$6$[rounds=N$]salt$hash`, with the rounds only spelled out when they
+/// differ from the default (as glibc does)
+fn sha512_crypt(password: &str, salt: &str, rounds: u32) -> Result<String, PasswordError> {
+    let params = sha_crypt::Sha512Params::new(rounds as usize)
+        .map_err(|err| PasswordError::Hash(format!("{:?}", err)))?;
+    let hash = sha_crypt::sha512_crypt_b64(password.as_bytes(), salt.as_bytes(), &params)
+        .map_err(|err| PasswordError::Hash(format!("{:?}", err)))?;
+
+    Ok(match rounds {
+        DEFAULT_SHA512_CRYPT_ROUNDS => format!("$6${}${}", salt, hash),
+        _ => format!("$6$rounds={}${}${}", rounds, salt, hash),
+    })
+}
+
+/// Apache's variant of Poul-Henning Kamp's MD5 crypt: `$apr1$salt$hash`
+fn apr1(password: &str, salt: &str) -> String {
+    const MAGIC: &str = "$apr1$";
+    let password = password.as_bytes();
+    let salt = &salt.as_bytes()[..salt.len().min(8)];
+
+    let alternate = Md5::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(password)
+        .finalize();
+
+    let mut context = Md5::new()
+        .chain_update(password)
+        .chain_update(MAGIC)
+        .chain_update(salt);
+    for chunk in password.chunks(16) {
+        context.update(&alternate[..chunk.len()]);
+    }
+    // Quirk of the original implementation: a NUL byte for each set bit of the
+    // length, the first password byte for each clear one
+    let mut length = password.len();
+    while length > 0 {
+        if length & 1 == 1 {
+            context.update([0]);
+        } else {
+            context.update(&password[..1]);
+        }
+        length >>= 1;
+    }
+    let mut digest = context.finalize();
+
+    // 1000 rounds to slow down brute force
+    for round in 0..1000 {
+        let mut context = Md5::new();
+        if round % 2 == 1 {
+            context.update(password);
+        } else {
+            context.update(digest);
+        }
+        if round % 3 != 0 {
+            context.update(salt);
+        }
+        if round % 7 != 0 {
+            context.update(password);
+        }
+        if round % 2 == 1 {
+            context.update(digest);
+        } else {
+            context.update(password);
+        }
+        digest = context.finalize();
+    }
+
+    let mut hash = format!("{}{}$", MAGIC, String::from_utf8_lossy(salt));
+    let mut push = |value: u32, chars: usize| {
+        for i in 0..chars {
+            hash.push(CRYPT_SALT_CHARS[((value >> (6 * i)) & 0x3f) as usize] as char);
+        }
+    };
+    for (a, b, c) in [(0, 6, 12), (1, 7, 13), (2, 8, 14), (3, 9, 15), (4, 10, 5)] {
+        push((digest[a] as u32) << 16 | (digest[b] as u32) << 8 | digest[c] as u32, 4);
+    }
+    push(digest[11] as u32, 2);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+
+    #[test]
+    fn test_argon2id_hash() {
+        let mut rng = get_secure_rng().unwrap();
+        let hash = hash_password(&mut rng, "correct horse", HashScheme::Argon2id).unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=19456,t=2,p=1$"));
+
+        let parsed = PasswordHash::new(&hash).unwrap();
+        assert!(Argon2::default().verify_password(b"correct horse", &parsed).is_ok());
+        assert!(Argon2::default().verify_password(b"battery staple", &parsed).is_err());
+
+        // Every hash gets its own salt
+        assert_ne!(hash, hash_password(&mut rng, "correct horse", HashScheme::Argon2id).unwrap());
+    }
+
+    #[test]
+    fn test_bcrypt_hash() {
+        let mut rng = get_secure_rng().unwrap();
+        let hash = hash_password(&mut rng, "correct horse", HashScheme::Bcrypt { cost: 4 }).unwrap();
+        assert!(hash.starts_with("$2b$04$"));
+        assert_eq!(hash.len(), 60);
+        assert!(bcrypt::verify("correct horse", &hash).unwrap());
+        assert!(!bcrypt::verify("battery staple", &hash).unwrap());
+
+        // Longer passwords would be truncated, so are rejected
+        let long = "a".repeat(BCRYPT_MAX_BYTES + 1);
+        assert!(hash_password(&mut rng, &long, HashScheme::Bcrypt { cost: 4 }).is_err());
+        assert!(hash_password(&mut rng, &long[1..], HashScheme::Bcrypt { cost: 4 }).is_ok());
+    }
+
+    #[test]
+    fn test_sha512_crypt() {
+        // Test vectors from Drepper's SHA-crypt specification
+        assert_eq!(
+            sha512_crypt("Hello world!", "saltstring", 5_000).unwrap(),
+            "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJu\
+             esI68u4OTLiBFdcbYEdFCoEOfaS35inz1"
+        );
+        assert_eq!(
+            sha512_crypt("Hello world!", "saltstringsaltst", 10_000).unwrap(),
+            "$6$rounds=10000$saltstringsaltst$OW1/O6BYHV6BcXZu8QVeXbDWra3Oeqh0sb\
+             HbbMCVNSnCM/UrjmM0Dp8vOuZeHBy/YTBmSK6H9qs/y3RnOaw5v."
+        );
+
+        let mut rng = get_secure_rng().unwrap();
+        let hash = hash_password(&mut rng, "correct horse", HashScheme::Sha512Crypt { rounds: 5_000 }).unwrap();
+        let salt = hash.split('$').nth(2).unwrap();
+        assert_eq!(salt.len(), 16);
+        assert!(salt.bytes().all(|b| CRYPT_SALT_CHARS.contains(&b)));
+        assert_eq!(hash, sha512_crypt("correct horse", salt, 5_000).unwrap());
+    }
+
+    #[test]
+    fn test_apr1() {
+        // Checked against `openssl passwd -apr1`
+        assert_eq!(apr1("Hello world!", "saltsalt"), "$apr1$saltsalt$6BwcdpRros16.J9J/tHRr/");
+        assert_eq!(apr1("password", "r31...."), "$apr1$r31....$kMmt8Ia8qcWk4vKKEhpgx1");
+        assert_eq!(
+            apr1("a very long password that spans more than sixteen bytes", "Abc"),
+            "$apr1$Abc$FOLfoC/eX2s/VZWhZlBAl0"
+        );
+
+        let mut rng = get_secure_rng().unwrap();
+        let hash = hash_password(&mut rng, "correct horse", HashScheme::Apr1).unwrap();
+        let salt = hash.split('$').nth(2).unwrap();
+        assert_eq!(salt.len(), 8);
+        assert_eq!(hash, apr1("correct horse", salt));
+    }
+
+    #[test]
+    fn test_hash_scheme_names() {
+        assert_eq!("Argon2id".parse::<HashScheme>(), Ok(HashScheme::Argon2id));
+        assert_eq!("bcrypt".parse::<HashScheme>(), Ok(HashScheme::Bcrypt { cost: DEFAULT_BCRYPT_COST }));
+        assert_eq!("bcrypt:10".parse::<HashScheme>(), Ok(HashScheme::Bcrypt { cost: 10 }));
+        assert!("bcrypt:3".parse::<HashScheme>().is_err());
+        assert!("bcrypt:32".parse::<HashScheme>().is_err());
+        assert!("bcrypt:x".parse::<HashScheme>().is_err());
+        assert!("argon2id:3".parse::<HashScheme>().is_err());
+        assert_eq!(
+            "sha512-crypt".parse::<HashScheme>(),
+            Ok(HashScheme::Sha512Crypt { rounds: DEFAULT_SHA512_CRYPT_ROUNDS })
+        );
+        assert_eq!("sha512-crypt:656000".parse::<HashScheme>(), Ok(HashScheme::Sha512Crypt { rounds: 656_000 }));
+        assert!("sha512-crypt:999".parse::<HashScheme>().is_err());
+        assert_eq!("apr1".parse::<HashScheme>(), Ok(HashScheme::Apr1));
+        assert!("md5".parse::<HashScheme>().is_err());
+        assert_eq!(HashScheme::Argon2id.to_string(), "argon2id");
+        assert_eq!(HashScheme::Bcrypt { cost: 12 }.to_string(), "bcrypt:12");
+    }
+}
@@ -0,0 +1,69 @@
+use sha1::{Digest, Sha1};
+use std::time::Duration;
+
+use password::PasswordError;
+
+const RANGE_API: &str = "https://api.pwnedpasswords.com/range/";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Uppercase hex SHA-1 of `password`, split into the 5 character prefix sent
+/// to the API and the 35 character suffix that never leaves this machine
+fn hash_parts(password: &str) -> (String, String) {
+    let digest = Sha1::digest(password.as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{:02X}", byte)).collect();
+    let (prefix, suffix) = hex.split_at(5);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Find `suffix` in a range response (`SUFFIX:COUNT` lines) and return how many
+/// times it appears in breaches. Padding entries have a count of 0.
+fn breach_count(body: &str, suffix: &str) -> u64 {
+    body.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Ask Have I Been Pwned whether `password` appears in a known breach.
+///
+/// Only the first 5 characters of the password's SHA-1 hash are sent
+/// (k-anonymity); the comparison happens locally. Responses are padded so
+/// their size doesn't reveal the prefix either.
+pub fn is_pwned(password: &str) -> Result<bool, PasswordError> {
+    let (prefix, suffix) = hash_parts(password);
+
+    let body = ureq::get(&format!("{}{}", RANGE_API, prefix))
+        .timeout(TIMEOUT)
+        .set("Add-Padding", "true")
+        .set("User-Agent", concat!("password-generator/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|err| PasswordError::BreachCheck(err.to_string()))?
+        .into_string()
+        .map_err(|err| PasswordError::BreachCheck(err.to_string()))?;
+
+    Ok(breach_count(&body, &suffix) > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_parts() {
+        let (prefix, suffix) = hash_parts("password");
+        assert_eq!(prefix, "5BAA6");
+        assert_eq!(suffix, "1E4C9B93F3F0682250B6CF8331B7EE68FD8");
+    }
+
+    #[test]
+    fn test_breach_count() {
+        let body = "003D68EB55068C33ACE09247EE4C639306B:3\r\n\
+                    1E4C9B93F3F0682250B6CF8331B7EE68FD8:10434004\r\n\
+                    1E4C9B93F3F0682250B6CF8331B7EE68FD9:0\r\n";
+        assert_eq!(breach_count(body, "1E4C9B93F3F0682250B6CF8331B7EE68FD8"), 10434004);
+        assert_eq!(breach_count(body, "1e4c9b93f3f0682250b6cf8331b7ee68fd8"), 10434004);
+        assert_eq!(breach_count(body, "1E4C9B93F3F0682250B6CF8331B7EE68FD9"), 0);
+        assert_eq!(breach_count(body, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"), 0);
+    }
+}
@@ -0,0 +1,289 @@
+//! `--log` and the `history` command: an append-only record of what was
+//! generated and when, so a fresh secret isn't lost between generating it
+//! and pasting it somewhere. The file is readable only by its owner.
+//!
+//! With a GPG or age recipient, each record (time, label, settings and the
+//! secret) is encrypted as a whole and stored as `{"sealed": "<armor>"}`, so
+//! nothing about it can be read without the key. Without one, the record is
+//! kept in the clear and the secret is left out.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use password::PasswordError;
+
+use crate::encrypt::{self, Recipient};
+use crate::outfile;
+
+/// One generated secret, stored as a line of JSON
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// When the secret was generated, as an RFC 3339 timestamp in UTC
+    pub time: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// How the secret was generated, e.g. `password length=36 charset=94`
+    pub settings: String,
+    pub entropy: f64,
+    /// The secret, only ever written inside a sealed record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl Drop for Record {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// A line of the history file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Line {
+    /// A whole record encrypted to a recipient, as ASCII armor
+    Sealed { sealed: String },
+    Clear(Record),
+}
+
+impl Record {
+    /// Whether the label or settings contain `query`, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.label.as_deref().is_some_and(|label| label.to_lowercase().contains(&query))
+            || self.settings.to_lowercase().contains(&query)
+    }
+
+    /// The record as a tab-separated line: time, label (`-` without one) and
+    /// settings
+    pub fn line(&self) -> String {
+        format!("{}\t{}\t{}", self.time, self.label.as_deref().unwrap_or("-"), self.settings)
+    }
+}
+
+/// Location of the history file, honouring `XDG_DATA_HOME`
+pub fn default_path() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+
+    Some(data_home.join("password-generator").join("history.jsonl"))
+}
+
+/// `record` as JSON in a buffer that is wiped when dropped, sized so it
+/// never grows and leaves a copy of the secret behind
+fn record_json(record: &Record) -> Result<Zeroizing<Vec<u8>>, serde_json::Error> {
+    let text = record.label.as_deref().unwrap_or_default().len()
+        + record.settings.len()
+        + record.secret.as_deref().unwrap_or_default().len();
+    let mut json = Zeroizing::new(Vec::with_capacity(text * 6 + 256));
+    serde_json::to_writer(&mut *json, record)?;
+    Ok(json)
+}
+
+/// The history file line for `record`: sealed to `recipient` when there is
+/// one, otherwise in the clear without its secret
+fn line(record: &Record, recipient: Option<&Recipient>) -> Result<String, PasswordError> {
+    let error = |err: serde_json::Error| PasswordError::HistoryWrite(err.to_string());
+    let line = match recipient {
+        Some(recipient) => {
+            let json = record_json(record).map_err(error)?;
+            let json = std::str::from_utf8(&json).expect("serde_json writes UTF-8");
+            Line::Sealed { sealed: encrypt::encrypt(recipient, json)? }
+        }
+        None => Line::Clear(Record {
+            time: record.time.clone(),
+            label: record.label.clone(),
+            settings: record.settings.clone(),
+            entropy: record.entropy,
+            secret: None,
+        }),
+    };
+    serde_json::to_string(&line).map_err(error)
+}
+
+/// The record a history file line holds, decrypting a sealed one with gpg or
+/// the age `identity` file
+fn open(line: Line, identity: Option<&Path>) -> Result<Record, PasswordError> {
+    match line {
+        Line::Clear(record) => Ok(record),
+        Line::Sealed { sealed } => {
+            let json = encrypt::decrypt(&sealed, identity)?;
+            serde_json::from_str(&json).map_err(|err| PasswordError::Decrypt(format!("sealed record: {}", err)))
+        }
+    }
+}
+
+/// Append `records` to the history file at `path`, sealed to `recipient`
+/// when there is one. A new file (and its directory) is created readable
+/// only by the owner.
+pub fn append(path: &Path, records: &[Record], recipient: Option<&Recipient>) -> Result<(), PasswordError> {
+    let error = |reason: String| PasswordError::HistoryWrite(format!("{}: {}", path.display(), reason));
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|err| error(err.to_string()))?;
+    }
+
+    let mut file = match outfile::create_private(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+            OpenOptions::new().append(true).open(path).map_err(|err| error(err.to_string()))?
+        }
+        Err(err) => return Err(error(err.to_string())),
+    };
+
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&line(record, recipient)?);
+        lines.push('\n');
+    }
+
+    file.write_all(lines.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|err| error(err.to_string()))
+}
+
+/// Every record in the history file at `path`, oldest first, with sealed
+/// ones decrypted by gpg or with the age `identity` file. A missing file has
+/// no records yet.
+pub fn read(path: &Path, identity: Option<&Path>) -> Result<Vec<Record>, PasswordError> {
+    let error = |reason: String| PasswordError::HistoryRead(format!("{}: {}", path.display(), reason));
+
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(error(err.to_string())),
+    };
+
+    let mut records = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| error(err.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = serde_json::from_str(&line).map_err(|err| error(format!("line {}: {}", index + 1, err)))?;
+        records.push(open(line, identity)?);
+    }
+
+    Ok(records)
+}
+
+/// The current time as an RFC 3339 timestamp in UTC
+pub fn now() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0);
+    timestamp(secs)
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`, using the
+/// days-to-civil-date conversion from Howard Hinnant's date algorithms
+fn timestamp(secs: u64) -> String {
+    let days = secs / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    let time = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(label: Option<&str>, secret: Option<&str>) -> Record {
+        Record {
+            time: "2026-10-16T09:30:00Z".to_string(),
+            label: label.map(str::to_string),
+            settings: "password length=36 charset=94".to_string(),
+            entropy: 235.93,
+            secret: secret.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(timestamp(4_107_542_399), "2100-02-28T23:59:59Z");
+    }
+
+    #[test]
+    fn test_matches_and_line() {
+        let labeled = record(Some("GitHub work"), None);
+        assert!(labeled.matches("github"));
+        assert!(labeled.matches("LENGTH=36"));
+        assert!(!labeled.matches("passphrase"));
+        assert_eq!(labeled.line(), "2026-10-16T09:30:00Z\tGitHub work\tpassword length=36 charset=94");
+        assert_eq!(record(None, None).line(), "2026-10-16T09:30:00Z\t-\tpassword length=36 charset=94");
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let dir = env::temp_dir().join(format!("password-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("history.jsonl");
+        assert_eq!(read(&path, None).unwrap(), Vec::new());
+
+        let first = record(Some("db"), None);
+        let second = record(None, None);
+        append(&path, std::slice::from_ref(&first), None).unwrap();
+        append(&path, std::slice::from_ref(&second), None).unwrap();
+        assert_eq!(read(&path, None).unwrap(), vec![first, second]);
+
+        // One record per line, without empty fields
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        assert!(!contents.lines().nth(1).unwrap().contains("label"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        fs::write(&path, "not json\n").unwrap();
+        assert!(matches!(read(&path, None), Err(PasswordError::HistoryRead(reason)) if reason.contains("line 1")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_clear_line_drops_secret() {
+        let line = line(&record(Some("db"), Some("hunter2")), None).unwrap();
+        assert!(!line.contains("hunter2"));
+        assert!(!line.contains("secret"));
+    }
+
+    #[test]
+    fn test_sealed_line() {
+        let armor = "-----BEGIN AGE ENCRYPTED FILE-----\nYWdl\n-----END AGE ENCRYPTED FILE-----";
+        let sealed = serde_json::to_string(&Line::Sealed { sealed: armor.to_string() }).unwrap();
+        assert!(matches!(serde_json::from_str(&sealed).unwrap(), Line::Sealed { sealed } if sealed == armor));
+
+        // Nothing about a sealed record is readable without the key
+        let line = serde_json::from_str(&sealed).unwrap();
+        assert!(matches!(open(line, None), Err(PasswordError::Decrypt(_))));
+
+        let json = record_json(&record(Some("db"), Some("hunter2"))).unwrap();
+        let clear: Line = serde_json::from_slice(&json).unwrap();
+        assert!(matches!(open(clear, None).unwrap().secret.as_deref(), Some("hunter2")));
+    }
+}
@@ -0,0 +1,201 @@
+//! Digits and symbols injected into a passphrase (`--inject-digits 2
+//! --inject-symbols 1`), for complexity rules that word-based passwords
+//! otherwise fail.
+//!
+//! The characters are drawn uniformly, put in a random order and spread over
+//! the allowed insertion points, every arrangement being equally likely. The
+//! pools leave out any character that could occur in the words or the
+//! separator, so every choice is visible in the output and the entropy is
+//! the log of the number of arrangements.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::seq::{index, SliceRandom};
+use rand::Rng;
+use zeroize::Zeroizing;
+
+use crate::{exclude_chars, PasswordError, Secret, CHARS_SYMBOLS};
+
+/// Where injected characters may be inserted
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InjectPosition {
+    /// At the start or end of a word, so the words stay whole
+    #[default]
+    Boundary,
+    /// Between any two characters
+    Anywhere,
+}
+
+impl InjectPosition {
+    /// Names accepted by `--inject-at`
+    pub const NAMES: [&'static str; 2] = ["boundary", "anywhere"];
+}
+
+impl FromStr for InjectPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "boundary" => Ok(InjectPosition::Boundary),
+            "anywhere" => Ok(InjectPosition::Anywhere),
+            _ => Err(format!(
+                "Unknown injection position '{}' (expected {})", s, InjectPosition::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for InjectPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(InjectPosition::NAMES[*self as usize])
+    }
+}
+
+/// How many digits and symbols to inject, where, and the pools they come from
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Injection {
+    pub digits: u16,
+    pub symbols: u16,
+    pub position: InjectPosition,
+    digit_pool: Vec<char>,
+    symbol_pool: Vec<char>,
+}
+
+impl Injection {
+    /// Inject `digits` digits and `symbols` symbols, leaving the `excluded`
+    /// characters (those of the words and separator) out of the pools
+    pub fn new(digits: u16, symbols: u16, position: InjectPosition, excluded: &[char]) -> Result<Self, PasswordError> {
+        let all_digits: Vec<char> = ('0'..='9').collect();
+        let digit_pool = exclude_chars(&all_digits, excluded).to_vec();
+        let symbol_pool = exclude_chars(&CHARS_SYMBOLS, excluded).to_vec();
+
+        if digits > 0 && digit_pool.is_empty() {
+            return Err(PasswordError::InjectPoolEmpty("digit"));
+        }
+        if symbols > 0 && symbol_pool.is_empty() {
+            return Err(PasswordError::InjectPoolEmpty("symbol"));
+        }
+
+        Ok(Injection { digits, symbols, position, digit_pool, symbol_pool })
+    }
+
+    /// Whether nothing is injected
+    pub fn is_empty(&self) -> bool {
+        self.digits == 0 && self.symbols == 0
+    }
+
+    /// Insert the characters into `text` at the char offsets in `slots`
+    /// (sorted, without repeats). Characters sharing a slot are kept
+    /// together in a random order.
+    pub fn apply<R: Rng + ?Sized>(&self, rng: &mut R, text: &str, slots: &[usize]) -> Secret {
+        if self.is_empty() {
+            return Secret::new(text.to_string());
+        }
+        let count = (self.digits + self.symbols) as usize;
+        let mut injected = Zeroizing::new(Vec::with_capacity(count));
+        injected.extend((0..self.digits).map(|_| self.digit_pool[rng.gen_range(0..self.digit_pool.len())]));
+        injected.extend((0..self.symbols).map(|_| self.symbol_pool[rng.gen_range(0..self.symbol_pool.len())]));
+        injected.shuffle(rng);
+
+        // Stars and bars: choosing `count` of `count + slots - 1` places
+        // uniformly gives every spread over the slots the same chance
+        let mut places = Zeroizing::new(index::sample(rng, count + slots.len() - 1, count).into_vec());
+        places.sort_unstable();
+        let mut offsets = Zeroizing::new(Vec::with_capacity(count));
+        offsets.extend(places.iter().enumerate().map(|(i, place)| slots[place - i]));
+
+        let mut output = Secret::new(String::with_capacity(text.len() + count * 4));
+        let mut pending = offsets.iter().zip(injected.iter()).peekable();
+        for (offset, c) in text.chars().enumerate() {
+            while let Some((_, injected)) = pending.next_if(|(slot, _)| **slot == offset) {
+                output.push(*injected);
+            }
+            output.push(c);
+        }
+        output.extend(pending.map(|(_, injected)| *injected));
+        output
+    }
+
+    /// Bits of entropy added when the characters are spread over `slots`
+    /// insertion points
+    pub fn entropy(&self, slots: usize) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let (digits, symbols) = (self.digits as usize, self.symbols as usize);
+        let count = digits + symbols;
+        log2_binomial(count + slots - 1, count)
+            + log2_binomial(count, digits)
+            + digits as f64 * (self.digit_pool.len() as f64).log2()
+            + symbols as f64 * (self.symbol_pool.len() as f64).log2()
+    }
+}
+
+/// `log2` of `n` choose `k`
+fn log2_binomial(n: usize, k: usize) -> f64 {
+    (0..k).map(|i| ((n - i) as f64).log2() - ((i + 1) as f64).log2()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_apply() {
+        let mut rng = get_secure_rng().unwrap();
+        let injection = Injection::new(2, 1, InjectPosition::Boundary, &[' ']).unwrap();
+
+        for _ in 0..50 {
+            let output = injection.apply(&mut rng, "ab cd", &[0, 2, 3, 5]);
+            assert_eq!(output.chars().count(), 8);
+            assert_eq!(output.chars().filter(char::is_ascii_digit).count(), 2);
+            assert_eq!(output.chars().filter(|c| CHARS_SYMBOLS.contains(c)).count(), 1);
+            // The words stay whole
+            let letters: String = output.chars().filter(|c| c.is_ascii_lowercase() || *c == ' ').collect();
+            assert_eq!(letters, "ab cd");
+            assert!(output.contains("ab") && output.contains("cd"));
+        }
+
+        assert_eq!(*Injection::default().apply(&mut rng, "ab cd", &[0, 5]), "ab cd");
+    }
+
+    #[test]
+    fn test_every_slot_is_used() {
+        let mut rng = get_secure_rng().unwrap();
+        let injection = Injection::new(1, 0, InjectPosition::Anywhere, &[]).unwrap();
+        let mut seen = [false; 4];
+        for _ in 0..200 {
+            let output = injection.apply(&mut rng, "abc", &[0, 1, 2, 3]);
+            seen[output.find(|c: char| c.is_ascii_digit()).unwrap()] = true;
+        }
+        assert_eq!(seen, [true; 4]);
+    }
+
+    #[test]
+    fn test_entropy() {
+        // One digit in one of 3 slots
+        let injection = Injection::new(1, 0, InjectPosition::Boundary, &[]).unwrap();
+        assert!((injection.entropy(3) - (3f64.log2() + 10f64.log2())).abs() < 1e-9);
+
+        // Two symbols and a digit over 4 slots: 20 spreads, 3 orders of the classes
+        let injection = Injection::new(1, 2, InjectPosition::Boundary, &['-']).unwrap();
+        let expected = 20f64.log2() + 3f64.log2() + 10f64.log2() + 2.0 * 25f64.log2();
+        assert!((injection.entropy(4) - expected).abs() < 1e-9);
+
+        assert_eq!(Injection::default().entropy(4), 0.0);
+    }
+
+    #[test]
+    fn test_pools() {
+        let digits: Vec<char> = ('0'..='9').collect();
+        assert!(matches!(
+            Injection::new(1, 0, InjectPosition::Boundary, &digits),
+            Err(PasswordError::InjectPoolEmpty("digit"))
+        ));
+        assert!(Injection::new(0, 1, InjectPosition::Boundary, &digits).is_ok());
+        assert_eq!("Anywhere".parse::<InjectPosition>(), Ok(InjectPosition::Anywhere));
+        assert!("middle".parse::<InjectPosition>().is_err());
+    }
+}
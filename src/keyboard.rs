@@ -0,0 +1,202 @@
+//! Keyboard-walk detection.
+//!
+//! A walk is a run of characters typed on neighbouring keys, like `qwerty`,
+//! `asdf` or the column `1qaz`. Random output occasionally contains one, and
+//! policy scanners flag them regardless of how the password was made.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Keyboard layouts that walks are detected on
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Layout {
+    #[default]
+    Qwerty,
+    Qwertz,
+    Azerty,
+}
+
+/// Unshifted and shifted characters of the four main rows of each layout
+const QWERTY_ROWS: [(&str, &str); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("\tqwertyuiop[]", "\tQWERTYUIOP{}"),
+    ("\tasdfghjkl;'", "\tASDFGHJKL:\""),
+    ("\tzxcvbnm,./", "\tZXCVBNM<>?"),
+];
+
+const QWERTZ_ROWS: [(&str, &str); 4] = [
+    ("^1234567890ß´", "°!\"§$%&/()=?`"),
+    ("\tqwertzuiopü+", "\tQWERTZUIOPÜ*"),
+    ("\tasdfghjklöä#", "\tASDFGHJKLÖÄ'"),
+    ("\tyxcvbnm,.-", "\tYXCVBNM;:_"),
+];
+
+const AZERTY_ROWS: [(&str, &str); 4] = [
+    ("²&é\"'(-è_çà)=", "²1234567890°+"),
+    ("\tazertyuiop^$", "\tAZERTYUIOP¨£"),
+    ("\tqsdfghjklmù*", "\tQSDFGHJKLM%µ"),
+    ("\twxcvbn,;:!", "\tWXCVBN?./§"),
+];
+
+/// Where a character is typed: the row and column of its key, and whether
+/// it needs Shift
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key {
+    pub row: i32,
+    pub column: i32,
+    pub shifted: bool,
+}
+
+/// The key of every character on the layout. The leading tab stands in for
+/// the Tab/Caps/Shift keys so columns line up with the row above.
+fn key_positions(rows: &[(&str, &str); 4]) -> HashMap<char, Key> {
+    let mut positions = HashMap::new();
+    for (row, (unshifted, shifted)) in rows.iter().enumerate() {
+        for (keys, is_shifted) in [(unshifted, false), (shifted, true)] {
+            for (column, c) in keys.chars().enumerate() {
+                if c != '\t' {
+                    positions.entry(c).or_insert(Key { row: row as i32, column: column as i32, shifted: is_shifted });
+                }
+            }
+        }
+    }
+    positions
+}
+
+static QWERTY: Lazy<HashMap<char, Key>> = Lazy::new(|| key_positions(&QWERTY_ROWS));
+static QWERTZ: Lazy<HashMap<char, Key>> = Lazy::new(|| key_positions(&QWERTZ_ROWS));
+static AZERTY: Lazy<HashMap<char, Key>> = Lazy::new(|| key_positions(&AZERTY_ROWS));
+
+impl Layout {
+    fn positions(&self) -> &'static HashMap<char, Key> {
+        match self {
+            Layout::Qwerty => &QWERTY,
+            Layout::Qwertz => &QWERTZ,
+            Layout::Azerty => &AZERTY,
+        }
+    }
+
+    /// The key `c` is typed on, if it is on one of the four main rows
+    pub fn key(&self, c: char) -> Option<Key> {
+        self.positions().get(&c).copied()
+    }
+
+    /// Whether `a` and `b` are on different, touching keys. Each row is offset
+    /// half a key to the right of the one above, so a key touches the two
+    /// keys above it at its own column and the next, and the two below at its
+    /// own column and the previous.
+    pub fn adjacent(&self, a: char, b: char) -> bool {
+        let (Some(a), Some(b)) = (self.key(a), self.key(b)) else {
+            return false;
+        };
+
+        match b.row - a.row {
+            0 => (b.column - a.column).abs() == 1,
+            1 => b.column == a.column || b.column == a.column - 1,
+            -1 => b.column == a.column || b.column == a.column + 1,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "qwerty" => Ok(Layout::Qwerty),
+            "qwertz" => Ok(Layout::Qwertz),
+            "azerty" => Ok(Layout::Azerty),
+            _ => Err(format!("Unknown keyboard layout '{}' (expected qwerty, qwertz or azerty)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Layout::Qwerty => write!(f, "qwerty"),
+            Layout::Qwertz => write!(f, "qwertz"),
+            Layout::Azerty => write!(f, "azerty"),
+        }
+    }
+}
+
+/// Reject passwords containing a keyboard walk of `run` or more keys
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WalkFilter {
+    pub layout: Layout,
+    pub run: u16,
+}
+
+/// Default length of walk that is rejected
+pub const DEFAULT_WALK_RUN: u16 = 4;
+
+/// Whether `password` contains `run` or more consecutive characters typed on
+/// neighbouring keys of `layout`
+pub fn has_keyboard_walk(password: &str, layout: Layout, run: u16) -> bool {
+    let run = run.max(2) as usize;
+    let mut length = 1;
+    let mut previous: Option<char> = None;
+
+    for c in password.chars() {
+        length = match previous {
+            Some(prev) if layout.adjacent(prev, c) => length + 1,
+            _ => 1,
+        };
+        if length >= run {
+            return true;
+        }
+        previous = Some(c);
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qwerty_walks() {
+        assert!(has_keyboard_walk("xqwertyx", Layout::Qwerty, 6));
+        assert!(has_keyboard_walk("asdf", Layout::Qwerty, 4));
+        assert!(has_keyboard_walk("1qaz", Layout::Qwerty, 4));
+        assert!(has_keyboard_walk("!QAZ", Layout::Qwerty, 4));
+        assert!(has_keyboard_walk("zaq1", Layout::Qwerty, 4));
+        assert!(!has_keyboard_walk("qwer", Layout::Qwerty, 5));
+        assert!(!has_keyboard_walk("qaz1", Layout::Qwerty, 4));
+        assert!(!has_keyboard_walk("aaaa", Layout::Qwerty, 2));
+    }
+
+    #[test]
+    fn test_other_layouts() {
+        assert!(has_keyboard_walk("qwertz", Layout::Qwertz, 6));
+        assert!(!has_keyboard_walk("qwertz", Layout::Qwerty, 6));
+        assert!(has_keyboard_walk("yxcv", Layout::Qwertz, 4));
+        assert!(has_keyboard_walk("azerty", Layout::Azerty, 6));
+        assert!(has_keyboard_walk("qsdf", Layout::Azerty, 4));
+        assert!(!has_keyboard_walk("asdf", Layout::Azerty, 4));
+    }
+
+    #[test]
+    fn test_adjacent_rows() {
+        let layout = Layout::Qwerty;
+        assert!(layout.adjacent('w', 'a'));
+        assert!(layout.adjacent('w', 's'));
+        assert!(layout.adjacent('w', '2'));
+        assert!(layout.adjacent('w', '3'));
+        assert!(!layout.adjacent('w', 'd'));
+        assert!(!layout.adjacent('w', '1'));
+        assert!(!layout.adjacent('w', ' '));
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!("QWERTZ".parse::<Layout>(), Ok(Layout::Qwertz));
+        assert!("dvorak".parse::<Layout>().is_err());
+        assert_eq!(Layout::Azerty.to_string(), "azerty");
+    }
+}
@@ -0,0 +1,103 @@
+//! Leetspeak substitutions (`a` → `@`, `e` → `3`, ...) for word-based
+//! passwords that must contain digits or symbols.
+//!
+//! Each substitutable letter is independently kept or replaced by one of its
+//! substitutes, chosen uniformly by the RNG. Because the substitutes never
+//! occur in the generated words, every choice is visible in the output and
+//! adds `log2(1 + substitutes)` bits for that letter.
+
+use rand::Rng;
+
+use crate::Secret;
+
+/// Letters that can be replaced, and what they can be replaced with
+pub const SUBSTITUTIONS: &[(char, &[char])] = &[
+    ('a', &['@', '4']),
+    ('e', &['3']),
+    ('i', &['1', '!']),
+    ('o', &['0']),
+    ('s', &['$', '5']),
+    ('t', &['7']),
+];
+
+/// The substitutes for `c` (case-insensitive), empty if it has none
+pub fn substitutes(c: char) -> &'static [char] {
+    let lower = c.to_ascii_lowercase();
+    SUBSTITUTIONS
+        .iter()
+        .find(|(letter, _)| *letter == lower)
+        .map_or(&[], |(_, substitutes)| *substitutes)
+}
+
+/// Whether `c` is one of the substitute characters
+pub fn is_substitute(c: char) -> bool {
+    SUBSTITUTIONS.iter().any(|(_, substitutes)| substitutes.contains(&c))
+}
+
+/// Randomly keep or substitute every substitutable letter of `text`
+pub fn leetify<R: Rng + ?Sized>(rng: &mut R, text: &str) -> Secret {
+    let mut output = Secret::new(String::with_capacity(text.len()));
+    for c in text.chars() {
+        let substitutes = substitutes(c);
+        match rng.gen_range(0..=substitutes.len()) {
+            0 => output.push(c),
+            choice => output.push(substitutes[choice - 1]),
+        }
+    }
+    output
+}
+
+/// Bits of entropy the substitutions add to `text`
+pub fn leet_entropy(text: &str) -> f64 {
+    text.chars()
+        .map(|c| (1.0 + substitutes(c).len() as f64).log2())
+        .sum()
+}
+
+/// Average bits added per character drawn uniformly from `pool`
+pub fn mean_leet_entropy(pool: &str) -> f64 {
+    let count = pool.chars().count();
+    if count == 0 {
+        return 0.0;
+    }
+    leet_entropy(pool) / count as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_substitutes() {
+        assert_eq!(substitutes('a'), &['@', '4']);
+        assert_eq!(substitutes('E'), &['3']);
+        assert!(substitutes('b').is_empty());
+        assert!(is_substitute('$'));
+        assert!(!is_substitute('s'));
+    }
+
+    #[test]
+    fn test_leetify() {
+        let mut rng = get_secure_rng().unwrap();
+        let text = "the quick brown fox jumps over the lazy dog".repeat(5);
+        let leet = leetify(&mut rng, &text);
+
+        assert_eq!(leet.chars().count(), text.chars().count());
+        assert_ne!(leet.as_str(), text);
+        for (original, output) in text.chars().zip(leet.chars()) {
+            assert!(original == output || substitutes(original).contains(&output));
+        }
+
+        // Text without substitutable letters is unchanged
+        assert_eq!(leetify(&mut rng, "bcd fgh").as_str(), "bcd fgh");
+    }
+
+    #[test]
+    fn test_leet_entropy() {
+        assert_eq!(leet_entropy("bcd"), 0.0);
+        assert!((leet_entropy("ae") - (3f64.log2() + 1.0)).abs() < 1e-9);
+        assert!((mean_leet_entropy("aeiou") - (2.0 * 3f64.log2() + 2.0) / 5.0).abs() < 1e-9);
+        assert_eq!(mean_leet_entropy(""), 0.0);
+    }
+}
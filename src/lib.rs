@@ -0,0 +1,864 @@
+//! Cryptographically secure password generation.
+//!
+//! Passwords, passphrases and pronounceable passwords are drawn from a
+//! ChaCha20 generator seeded by the operating system. Pass the same RNG to
+//! several calls to generate a batch from a single seed.
+//!
+//! ```
+//! use password::{generate_password, get_secure_rng};
+//!
+//! let mut rng = get_secure_rng()?;
+//! let password = generate_password(&mut rng, 24, true, false, false)?;
+//! assert_eq!(password.len(), 24);
+//! # Ok::<(), password::PasswordError>(())
+//! ```
+//!
+//! Generated secrets are returned as [`Secret`]s, which overwrite their memory
+//! when dropped so passwords don't linger in freed heap allocations.
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, SeedableRng};
+use once_cell::sync::Lazy;
+use unicode_segmentation::UnicodeSegmentation;
+use zeroize::Zeroizing;
+
+use policy::CharClass;
+
+pub mod acrostic;
+pub mod analyze;
+pub mod blocklist;
+pub mod derive;
+pub mod dictionary;
+pub mod drbg;
+pub mod emoji;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod hash;
+pub mod inject;
+pub mod keyboard;
+pub mod leet;
+pub mod markov;
+pub mod mnemonic;
+pub mod options;
+pub mod passphrase;
+pub mod phonetic;
+pub mod policy;
+pub mod profanity;
+pub mod pronounceable;
+pub mod rng;
+pub mod strength;
+pub mod template;
+pub mod token;
+pub mod totp;
+pub mod typing;
+pub mod unicode;
+pub mod username;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod weights;
+pub mod wifi;
+
+/// A generated password, wiped from memory when dropped
+pub type Secret = Zeroizing<String>;
+
+/// Digits, lowercase and uppercase ASCII letters
+pub static CHARS_ALPHA_NUM: Lazy<Vec<char>> = Lazy::new(|| {
+    let mut chars = Vec::new();
+    chars.extend('0'..='9');
+    chars.extend('a'..='z');
+    chars.extend('A'..='Z');
+    chars
+});
+
+/// Symbols included by default
+pub static CHARS_SYMBOLS: Lazy<Vec<char>> = Lazy::new(|| {
+    // Special characters (carefully chosen set)
+    "!@#$%^&*()-_=+[]{}|;:,.<>?".chars().collect()
+});
+
+/// Symbols that are valid in most systems but often need escaping
+pub static CHARS_SYMBOLS_EXTENDED: Lazy<Vec<char>> = Lazy::new(|| {
+    // AWS valid but potentially problematic
+    "`\"'/\\".chars().collect()
+});
+
+/// RFC 3986 unreserved characters, which need no percent-encoding anywhere in a URL
+pub static CHARS_URL_SAFE: Lazy<Vec<char>> = Lazy::new(|| {
+    let mut chars = CHARS_ALPHA_NUM.clone();
+    chars.extend("-._~".chars());
+    chars
+});
+
+/// Characters that are easily confused with one another when read or transcribed
+pub static CHARS_AMBIGUOUS: Lazy<Vec<char>> = Lazy::new(|| {
+    "0Oo1lI|".chars().collect()
+});
+
+/// Longest password generated, so a bad length can't ask for gigabytes
+pub const MAX_LENGTH_LIMIT: u32 = 1 << 20;
+
+/// Everything that can go wrong while generating a password
+#[derive(Debug)]
+pub enum PasswordError {
+    InvalidSymbolCombination,
+    ExtendedWithoutSymbolClass,
+    EmptyCharacterSet,
+    RngInitializationError,
+    EmptyWordlist,
+    WordlistRead(String),
+    WordlistTooSmall(usize),
+    DuplicateWord(String),
+    /// Two wordlist words that the chosen word case writes the same way
+    WordCaseCollision(String, String, passphrase::WordCase),
+    Clipboard(String),
+    Config(String),
+    PolicyExceedsLength(u32, u32),
+    PolicyClassMissing(&'static str),
+    PolicyMaxBelowMin(&'static str, u16, u16),
+    PolicyMaxBelowLength(u32, u32),
+    PolicyEdgeMissing(&'static str),
+    AdjacentRepeatUnavoidable,
+    WeightsWithRepeatRule(&'static str),
+    InjectPoolEmpty(&'static str),
+    PatternUnmatched(String, u32),
+    PolicyUnsatisfiable(u32),
+    InvalidTemplate(String),
+    QrCode(String),
+    Derivation(String),
+    MasterPassphrase(String),
+    BreachCheck(String),
+    BlocklistRead(String),
+    Blocklisted(u32),
+    BatchRead(String),
+    BatchSpec(usize, String),
+    UniqueExceedsCharset(u32, usize),
+    Server(String),
+    UnknownPreset(String),
+    UnknownProfile(String),
+    Hash(String),
+    Htpasswd(String),
+    LeetWithoutWords,
+    OutputFile(String),
+    SilentWithoutDestination,
+    ForceWithoutDestination,
+    WifiOptionWithoutPreset(&'static str),
+    NoUsernameFits(usize, usize),
+    Stream(String),
+    LengthExceedsLimit(u32, u32),
+    LengthOutOfRange(u32),
+    WordCountOutOfRange(u16),
+    BitsUnreachable(u32, u32),
+    AnalyzeRead(String),
+    AcrosticSentence(String),
+    CorpusRead(String),
+    CorpusTooSmall(usize),
+    Store(String),
+    Env(String),
+    Dotenv(String),
+    K8sSecret(String),
+    Terraform(String),
+    CasWithoutVault,
+    EscapeWithFormat,
+    Encrypt(String),
+    Decrypt(String),
+    HistoryRead(String),
+    HistoryWrite(String),
+    Labels(String),
+    Terminal(String),
+}
+
+impl std::fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PasswordError::InvalidSymbolCombination => 
+                write!(f, "Cannot use extended symbols (-e) when symbols are excluded (-n)"),
+            PasswordError::ExtendedWithoutSymbolClass =>
+                write!(f, "Cannot use extended symbols (-e) unless --classes includes symbol"),
+            PasswordError::EmptyCharacterSet => 
+                write!(f, "No character sets available for password generation"),
+            PasswordError::RngInitializationError => 
+                write!(f, "Failed to initialize secure random number generator"),
+            PasswordError::EmptyWordlist =>
+                write!(f, "No words available for passphrase generation"),
+            PasswordError::WordlistRead(reason) =>
+                write!(f, "Failed to read wordlist {}", reason),
+            PasswordError::WordlistTooSmall(found) =>
+                write!(f, "Wordlist must contain at least {} unique words. Found: {}",
+                    passphrase::MIN_WORDLIST_SIZE, found),
+            PasswordError::DuplicateWord(word) =>
+                write!(f, "Wordlist contains duplicate word: {}", word),
+            PasswordError::WordCaseCollision(first, second, case) =>
+                write!(f, "Wordlist words {:?} and {:?} are the same in {} case, which would overstate the entropy; \
+                    remove one or choose another --word-case", first, second, case),
+            PasswordError::Clipboard(reason) =>
+                write!(f, "Failed to copy to clipboard: {}", reason),
+            PasswordError::Config(reason) =>
+                write!(f, "Invalid config file {}", reason),
+            PasswordError::PolicyExceedsLength(required, length) =>
+                write!(f, "Minimum character counts ({}) exceed the password length ({})", required, length),
+            PasswordError::PolicyClassMissing(class) =>
+                write!(f, "A minimum number of {} is required but the character set contains none", class),
+            PasswordError::PolicyMaxBelowMin(class, max, min) =>
+                write!(f, "The maximum number of {} ({}) is below the minimum ({})", class, max, min),
+            PasswordError::PolicyMaxBelowLength(room, length) =>
+                write!(f, "Maximum character counts allow only {} characters, short of the password length ({})", room, length),
+            PasswordError::PatternUnmatched(pattern, attempts) =>
+                write!(f, "No secret matching --match '{}' was found after {} attempts; check that the character set and length can produce a match", pattern, attempts),
+            PasswordError::InjectPoolEmpty(class) =>
+                write!(f, "Every {} occurs in the wordlist or separator, so none can be injected unambiguously", class),
+            PasswordError::AdjacentRepeatUnavoidable =>
+                write!(f, "A single character can't fill the password without repeating itself"),
+            PasswordError::WeightsWithRepeatRule(option) =>
+                write!(f, "--weights can't be combined with {}: the weighted draw doesn't enforce it", option),
+            PasswordError::PolicyEdgeMissing(class) =>
+                write!(f, "The password must start or end with {} but the character set contains none", class),
+            PasswordError::PolicyUnsatisfiable(attempts) =>
+                write!(f, "No password satisfying the policy was found after {} attempts", attempts),
+            PasswordError::InvalidTemplate(reason) =>
+                write!(f, "Invalid template: {}", reason),
+            PasswordError::QrCode(reason) =>
+                write!(f, "Failed to render QR code: {}", reason),
+            PasswordError::Derivation(reason) =>
+                write!(f, "Failed to derive password: {}", reason),
+            PasswordError::MasterPassphrase(reason) =>
+                write!(f, "Failed to read master passphrase: {}", reason),
+            PasswordError::BreachCheck(reason) =>
+                write!(f, "Have I Been Pwned check failed: {}", reason),
+            PasswordError::BlocklistRead(reason) =>
+                write!(f, "Failed to read blocklist {}", reason),
+            PasswordError::Blocklisted(attempts) =>
+                write!(f, "Every candidate was on the blocklist, in a known breach or profane after {} attempts", attempts),
+            PasswordError::BatchRead(reason) =>
+                write!(f, "Failed to read batch specs: {}", reason),
+            PasswordError::BatchSpec(line, reason) =>
+                write!(f, "Invalid batch spec on line {}: {}", line, reason),
+            PasswordError::UniqueExceedsCharset(length, available) =>
+                write!(f, "Cannot pick {} distinct characters from a set of {}", length, available),
+            PasswordError::Server(reason) =>
+                write!(f, "HTTP server failed: {}", reason),
+            PasswordError::UnknownPreset(reason) =>
+                write!(f, "Unknown preset {}", reason),
+            PasswordError::UnknownProfile(reason) =>
+                write!(f, "Unknown profile {}", reason),
+            PasswordError::Hash(reason) =>
+                write!(f, "Failed to hash password: {}", reason),
+            PasswordError::Htpasswd(reason) =>
+                write!(f, "Cannot write htpasswd lines: {}", reason),
+            PasswordError::LeetWithoutWords =>
+                write!(f, "--leet only applies to passphrases (-w) and pronounceable passwords (-p)"),
+            PasswordError::OutputFile(reason) =>
+                write!(f, "Failed to write output file {}", reason),
+            PasswordError::SilentWithoutDestination =>
+                write!(f, "--silent needs --copy, --out, --store or --dotenv to deliver the password"),
+            PasswordError::ForceWithoutDestination =>
+                write!(f, "--force only applies to --out or --store"),
+            PasswordError::WifiOptionWithoutPreset(option) =>
+                write!(f, "{} only applies to --preset wifi", option),
+            PasswordError::NoUsernameFits(min, max) =>
+                write!(f, "No username fits between {} and {} characters", min, max),
+            PasswordError::Stream(reason) =>
+                write!(f, "Failed to write to stdout: {}", reason),
+            PasswordError::LengthExceedsLimit(length, limit) =>
+                write!(f, "Password length {} exceeds the limit of {}; raise it with --length-limit", length, limit),
+            PasswordError::LengthOutOfRange(length) =>
+                write!(f, "Password length must be between 1 and {} characters. Got: {}", MAX_LENGTH_LIMIT, length),
+            PasswordError::WordCountOutOfRange(count) =>
+                write!(f, "Passphrase must contain between 1 and {} words. Got: {}", passphrase::MAX_WORD_COUNT, count),
+            PasswordError::BitsUnreachable(bits, limit) =>
+                write!(f, "No length up to {} reaches {} bits with this character set", limit, bits),
+            PasswordError::AnalyzeRead(reason) =>
+                write!(f, "Failed to read the password to analyze: {}", reason),
+            PasswordError::AcrosticSentence(reason) =>
+                write!(f, "Can't build an acrostic from the sentence: {}", reason),
+            PasswordError::CorpusRead(reason) =>
+                write!(f, "Failed to read corpus {}", reason),
+            PasswordError::CorpusTooSmall(words) =>
+                write!(f, "The corpus has only {} distinct words; at least {} are needed", words, markov::MIN_CORPUS_WORDS),
+            PasswordError::Store(reason) =>
+                write!(f, "Failed to store the password in {}", reason),
+            PasswordError::Env(reason) =>
+                write!(f, "Cannot write environment variables: {}", reason),
+            PasswordError::Dotenv(reason) =>
+                write!(f, "Failed to update dotenv file {}", reason),
+            PasswordError::K8sSecret(reason) =>
+                write!(f, "Cannot write a Kubernetes Secret: {}", reason),
+            PasswordError::Terraform(reason) =>
+                write!(f, "Cannot write Terraform variables: {}", reason),
+            PasswordError::CasWithoutVault =>
+                write!(f, "--cas only applies to --store vault:..."),
+            PasswordError::EscapeWithFormat =>
+                write!(f, "--escape only applies to --format text; the other formats escape passwords themselves"),
+            PasswordError::Encrypt(reason) =>
+                write!(f, "Failed to encrypt the output with {}", reason),
+            PasswordError::Decrypt(reason) =>
+                write!(f, "Failed to decrypt a history record with {}", reason),
+            PasswordError::HistoryRead(reason) =>
+                write!(f, "Failed to read history file {}", reason),
+            PasswordError::HistoryWrite(reason) =>
+                write!(f, "Failed to append to history file {}", reason),
+            PasswordError::Labels(reason) =>
+                write!(f, "Failed to read labels: {}", reason),
+            PasswordError::Terminal(reason) =>
+                write!(f, "Cannot show a masked password: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for PasswordError {}
+
+/// Exit status for invalid or conflicting options (the same status clap uses
+/// for arguments it rejects itself)
+pub const EXIT_USAGE: i32 = 2;
+/// Exit status when the options can't produce a secret: an empty character
+/// set, unsatisfiable minimums, or a length or entropy out of reach
+pub const EXIT_UNSATISFIABLE: i32 = 3;
+/// Exit status for an input file or stream that can't be read or is invalid
+/// (wordlist, corpus, blocklist, config, batch specs, passphrase prompt,
+/// history file)
+pub const EXIT_INPUT: i32 = 4;
+/// Exit status when every candidate was rejected by the blocklist, the breach
+/// check or the profanity filter
+pub const EXIT_REJECTED: i32 = 5;
+/// Exit status for a network failure (breach check or `--serve`)
+pub const EXIT_NETWORK: i32 = 6;
+/// Exit status when the secret couldn't be delivered (stdout, file,
+/// clipboard, QR code, password store, dotenv file or history file)
+pub const EXIT_OUTPUT: i32 = 7;
+/// Exit status for a failure of the random number generator, key derivation,
+/// hashing, encryption or decryption
+pub const EXIT_CRYPTO: i32 = 8;
+
+/// Every exit status and what it means, for documentation
+pub const EXIT_STATUSES: [(i32, &str); 8] = [
+    (0, "Success."),
+    (EXIT_USAGE, "Invalid or conflicting options, including arguments the parser rejects."),
+    (EXIT_UNSATISFIABLE, "The options can't produce a secret: an empty character set, unsatisfiable minimum counts, no match for the pattern, or a length or entropy target out of reach."),
+    (EXIT_INPUT, "An input couldn't be read or is invalid: wordlist, corpus, blocklist, config, presets, batch specs, labels, the acrostic sentence, the master passphrase or the history file."),
+    (EXIT_REJECTED, "Every candidate was rejected by the blocklist, the breach check or the profanity filter."),
+    (EXIT_NETWORK, "Network failure: the breach check, or binding the --serve address."),
+    (EXIT_OUTPUT, "The secret couldn't be delivered to stdout, a file, the clipboard, a QR code, a password store, a dotenv file, the history file or the terminal."),
+    (EXIT_CRYPTO, "The random number generator, key derivation, hashing, encryption or decryption failed."),
+];
+
+impl PasswordError {
+    /// The process exit status for this error. The statuses are stable, so
+    /// scripts can branch on them.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PasswordError::InvalidSymbolCombination
+            | PasswordError::ExtendedWithoutSymbolClass
+            | PasswordError::InvalidTemplate(_)
+            | PasswordError::UnknownPreset(_)
+            | PasswordError::UnknownProfile(_)
+            | PasswordError::Htpasswd(_)
+            | PasswordError::LeetWithoutWords
+            | PasswordError::SilentWithoutDestination
+            | PasswordError::ForceWithoutDestination
+            | PasswordError::WifiOptionWithoutPreset(_)
+            | PasswordError::LengthExceedsLimit(..)
+            | PasswordError::LengthOutOfRange(_)
+            | PasswordError::WordCountOutOfRange(_)
+            | PasswordError::Env(_)
+            | PasswordError::K8sSecret(_)
+            | PasswordError::Terraform(_)
+            | PasswordError::CasWithoutVault
+            | PasswordError::EscapeWithFormat
+            | PasswordError::WeightsWithRepeatRule(_) => EXIT_USAGE,
+            PasswordError::EmptyCharacterSet
+            | PasswordError::PolicyExceedsLength(..)
+            | PasswordError::PolicyClassMissing(_)
+            | PasswordError::PolicyMaxBelowMin(..)
+            | PasswordError::PolicyMaxBelowLength(..)
+            | PasswordError::PolicyEdgeMissing(_)
+            | PasswordError::AdjacentRepeatUnavoidable
+            | PasswordError::InjectPoolEmpty(_)
+            | PasswordError::PatternUnmatched(..)
+            | PasswordError::PolicyUnsatisfiable(_)
+            | PasswordError::UniqueExceedsCharset(..)
+            | PasswordError::NoUsernameFits(..)
+            | PasswordError::BitsUnreachable(..) => EXIT_UNSATISFIABLE,
+            PasswordError::EmptyWordlist
+            | PasswordError::WordlistRead(_)
+            | PasswordError::WordlistTooSmall(_)
+            | PasswordError::DuplicateWord(_)
+            | PasswordError::WordCaseCollision(..)
+            | PasswordError::Config(_)
+            | PasswordError::MasterPassphrase(_)
+            | PasswordError::BlocklistRead(_)
+            | PasswordError::BatchRead(_)
+            | PasswordError::BatchSpec(..)
+            | PasswordError::AnalyzeRead(_)
+            | PasswordError::AcrosticSentence(_)
+            | PasswordError::CorpusRead(_)
+            | PasswordError::CorpusTooSmall(_)
+            | PasswordError::HistoryRead(_)
+            | PasswordError::Labels(_) => EXIT_INPUT,
+            PasswordError::Blocklisted(_) => EXIT_REJECTED,
+            PasswordError::BreachCheck(_) | PasswordError::Server(_) => EXIT_NETWORK,
+            PasswordError::Clipboard(_)
+            | PasswordError::QrCode(_)
+            | PasswordError::OutputFile(_)
+            | PasswordError::Stream(_)
+            | PasswordError::Store(_)
+            | PasswordError::Dotenv(_)
+            | PasswordError::HistoryWrite(_)
+            | PasswordError::Terminal(_) => EXIT_OUTPUT,
+            PasswordError::RngInitializationError
+            | PasswordError::Derivation(_)
+            | PasswordError::Hash(_)
+            | PasswordError::Encrypt(_)
+            | PasswordError::Decrypt(_) => EXIT_CRYPTO,
+        }
+    }
+}
+
+/// A ChaCha20 generator seeded from the operating system's entropy source
+pub fn get_secure_rng() -> Result<ChaCha20Rng, PasswordError> {
+    ChaCha20Rng::from_rng(&mut OsRng)
+        .map_err(|_| PasswordError::RngInitializationError)
+}
+
+/// A ChaCha20 generator seeded with a fixed value. Anyone who knows the seed
+/// can reproduce every password drawn from it, so this is only for tests and
+/// documentation examples.
+pub fn insecure_seeded_rng(seed: [u8; 32]) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(seed)
+}
+
+/// The pool of characters a password is drawn from
+pub fn build_charset(
+    include_symbols: bool,
+    include_extended: bool,
+    allow_space: bool
+) -> Zeroizing<Vec<char>> {
+    let capacity = CHARS_ALPHA_NUM.len() + CHARS_SYMBOLS.len() + CHARS_SYMBOLS_EXTENDED.len() + 1;
+    let mut chars = Zeroizing::new(Vec::with_capacity(capacity));
+    chars.extend(CHARS_ALPHA_NUM.iter());
+
+    if include_symbols {
+        chars.extend(CHARS_SYMBOLS.iter());
+        if include_extended {
+            chars.extend(CHARS_SYMBOLS_EXTENDED.iter());
+        }
+    }
+
+    if allow_space {
+        chars.push(' ');
+    }
+
+    chars
+}
+
+/// The pool of characters from the selected `classes` only, in the same
+/// order as [`build_charset`]. Extended symbols and the space are added as
+/// with `build_charset`; extended symbols are only kept with [`CharClass::Symbol`].
+pub fn class_charset(
+    classes: &[CharClass],
+    include_extended: bool,
+    allow_space: bool
+) -> Zeroizing<Vec<char>> {
+    let all = build_charset(true, include_extended, allow_space);
+    let mut chars = Zeroizing::new(Vec::with_capacity(all.len()));
+    chars.extend(all.iter().filter(|c| **c == ' ' || classes.iter().any(|class| class.contains(**c))));
+    chars
+}
+
+/// Number of user-perceived characters (extended grapheme clusters) in `text`,
+/// which is what people count when they read or type a password
+pub fn grapheme_len(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// Theoretical entropy in bits of `length` characters drawn uniformly from `charset_size`
+pub fn password_entropy(length: u32, charset_size: usize) -> f64 {
+    length as f64 * (charset_size as f64).log2()
+}
+
+/// Entropy in bits of a password of `length` distinct characters drawn from
+/// `charset_size` characters (each pick has one fewer choice than the last)
+pub fn unique_password_entropy(length: u32, charset_size: usize) -> f64 {
+    (0..length as usize)
+        .map(|i| (charset_size.saturating_sub(i) as f64).log2())
+        .sum()
+}
+
+/// Entropy in bits of a password of `length` characters drawn from
+/// `charset_size` characters without an adjacent repeat (each pick after the
+/// first has one fewer choice)
+pub fn no_repeat_password_entropy(length: u32, charset_size: usize) -> f64 {
+    if length == 0 {
+        return 0.0;
+    }
+    (charset_size as f64).log2() + (length - 1) as f64 * (charset_size.saturating_sub(1) as f64).log2()
+}
+
+/// Shortest length up to `max_length` whose `entropy` reaches `bits`, for an
+/// entropy that never shrinks as the length grows
+pub fn length_for_entropy<F: Fn(u32) -> f64>(bits: f64, max_length: u32, entropy: F) -> Option<u32> {
+    // Allow for rounding, so 60 bits from 64 characters is 10 and not 11
+    let reaches = |length: u32| entropy(length) >= bits - 1e-9;
+    if max_length == 0 || !reaches(max_length) {
+        return None;
+    }
+
+    let (mut low, mut high) = (1, max_length);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if reaches(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    Some(low)
+}
+
+/// Generate `length` characters drawn uniformly from the selected character sets
+pub fn generate_password<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: u32,
+    include_symbols: bool,
+    include_extended: bool,
+    allow_space: bool
+) -> Result<Secret, PasswordError> {
+    let chars = build_charset(include_symbols, include_extended, allow_space);
+    generate_from_charset(rng, length, &chars)
+}
+
+/// Remove every character in `excluded` from `charset`
+pub fn exclude_chars(charset: &[char], excluded: &[char]) -> Zeroizing<Vec<char>> {
+    let mut remaining = Zeroizing::new(Vec::with_capacity(charset.len()));
+    remaining.extend(charset.iter().filter(|c| !excluded.contains(c)));
+    remaining
+}
+
+/// Parse a user-supplied character set, dropping repeated characters so that
+/// every character is equally likely to be drawn
+pub fn custom_charset(chars: &str) -> Result<Zeroizing<Vec<char>>, PasswordError> {
+    let mut charset = Zeroizing::new(Vec::with_capacity(chars.chars().count()));
+    for c in chars.chars() {
+        if !charset.contains(&c) {
+            charset.push(c);
+        }
+    }
+
+    if charset.is_empty() {
+        return Err(PasswordError::EmptyCharacterSet);
+    }
+
+    Ok(charset)
+}
+
+/// Generate `length` characters drawn uniformly from `chars`
+pub fn generate_from_charset<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: u32,
+    chars: &[char]
+) -> Result<Secret, PasswordError> {
+    if chars.is_empty() {
+        return Err(PasswordError::EmptyCharacterSet);
+    }
+
+    // Reserve the worst case up front so the buffer never reallocates and
+    // leaves a stray copy of a partial password behind
+    let max_char_len = chars.iter().map(|c| c.len_utf8()).max().unwrap_or(1);
+    let char_distribution = Uniform::from(0..chars.len());
+    let mut password = Secret::new(String::with_capacity(length as usize * max_char_len));
+
+    for _ in 0..length {
+        let index = char_distribution.sample(rng);
+        password.push(chars[index]);
+    }
+
+    Ok(password)
+}
+
+/// Generate `length` distinct characters drawn uniformly without replacement from `chars`
+pub fn generate_unique_from_charset<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: u32,
+    chars: &[char]
+) -> Result<Secret, PasswordError> {
+    if chars.is_empty() {
+        return Err(PasswordError::EmptyCharacterSet);
+    }
+    if length as usize > chars.len() {
+        return Err(PasswordError::UniqueExceedsCharset(length, chars.len()));
+    }
+
+    // Partial Fisher-Yates shuffle on a wiped copy of the pool
+    let mut pool = Zeroizing::new(chars.to_vec());
+    let max_char_len = chars.iter().map(|c| c.len_utf8()).max().unwrap_or(1);
+    let mut password = Secret::new(String::with_capacity(length as usize * max_char_len));
+
+    for i in 0..length as usize {
+        let j = rng.gen_range(i..pool.len());
+        pool.swap(i, j);
+        password.push(pool[i]);
+    }
+
+    Ok(password)
+}
+
+/// Generate `length` characters drawn uniformly from `chars`, never the same
+/// character twice in a row. After the first, each character is drawn from
+/// the pool minus the one before it, so every password without an adjacent
+/// repeat is equally likely.
+pub fn generate_no_repeat_from_charset<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: u32,
+    chars: &[char]
+) -> Result<Secret, PasswordError> {
+    if chars.is_empty() {
+        return Err(PasswordError::EmptyCharacterSet);
+    }
+    if chars.len() < 2 && length > 1 {
+        return Err(PasswordError::AdjacentRepeatUnavoidable);
+    }
+
+    let max_char_len = chars.iter().map(|c| c.len_utf8()).max().unwrap_or(1);
+    let mut password = Secret::new(String::with_capacity(length as usize * max_char_len));
+    let mut previous: Option<usize> = None;
+
+    for _ in 0..length {
+        let index = match previous {
+            None => rng.gen_range(0..chars.len()),
+            // Skip over the previous character
+            Some(previous) => {
+                let index = rng.gen_range(0..chars.len() - 1);
+                if index >= previous { index + 1 } else { index }
+            }
+        };
+        password.push(chars[index]);
+        previous = Some(index);
+    }
+
+    Ok(password)
+}
+
+/// Split `text` into groups of `size` characters joined by `separator`, like
+/// `hT7k-Pq2M-9xLr`. The last group may be shorter.
+pub fn group_chars(text: &str, size: usize, separator: char) -> Secret {
+    let size = size.max(1);
+    let count = grapheme_len(text);
+    let separators = count.saturating_sub(1) / size;
+    let mut grouped = Secret::new(String::with_capacity(text.len() + separators * separator.len_utf8()));
+
+    for (i, grapheme) in text.graphemes(true).enumerate() {
+        if i > 0 && i % size == 0 {
+            grouped.push(separator);
+        }
+        grouped.push_str(grapheme);
+    }
+
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(PasswordError::LeetWithoutWords.exit_code(), 2);
+        assert_eq!(PasswordError::EmptyCharacterSet.exit_code(), 3);
+        assert_eq!(PasswordError::WordlistTooSmall(10).exit_code(), 4);
+        assert_eq!(PasswordError::Blocklisted(100).exit_code(), 5);
+        assert_eq!(PasswordError::BreachCheck(String::new()).exit_code(), 6);
+        assert_eq!(PasswordError::Store(String::new()).exit_code(), 7);
+        assert_eq!(PasswordError::Encrypt(String::new()).exit_code(), 8);
+
+        // Distinct from success, from a panic (101) and from clap's own statuses
+        let codes = [EXIT_USAGE, EXIT_UNSATISFIABLE, EXIT_INPUT, EXIT_REJECTED, EXIT_NETWORK, EXIT_OUTPUT, EXIT_CRYPTO];
+        assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(codes.iter().all(|code| (2..64).contains(code)));
+    }
+
+    #[test]
+    fn test_password_length() {
+        let password = generate_password(&mut get_secure_rng().unwrap(), 123, true, false, false).unwrap();
+        assert_eq!(password.len(), 123);
+    }
+
+    #[test]
+    fn test_no_symbols() {
+        let password = generate_password(&mut get_secure_rng().unwrap(), 100, false, false, false).unwrap();
+        assert!(password.chars().all(|c| c.is_alphanumeric()));
+    }
+
+    #[test]
+    fn test_with_spaces() {
+        let password = generate_password(&mut get_secure_rng().unwrap(), 1000, true, false, true).unwrap();
+        assert!(password.chars().any(|c| c == ' '));
+    }
+
+    #[test]
+    fn test_with_extended_symbols() {
+        let password = generate_password(&mut get_secure_rng().unwrap(), 1000, true, true, false).unwrap();
+        // Check if at least one extended symbol is present
+        assert!(password.chars().any(|c| CHARS_SYMBOLS_EXTENDED.contains(&c)));
+    }
+
+    #[test]
+    fn test_class_charset() {
+        let charset = class_charset(&[CharClass::Digit, CharClass::Symbol], false, false);
+        assert_eq!(charset.len(), 10 + CHARS_SYMBOLS.len());
+        assert!(charset.iter().all(|c| c.is_ascii_digit() || CHARS_SYMBOLS.contains(c)));
+
+        // The order of the classes doesn't change the pool
+        let charset = class_charset(&[CharClass::Upper, CharClass::Lower], false, true);
+        assert_eq!(*charset, *class_charset(&[CharClass::Lower, CharClass::Upper], false, true));
+        assert_eq!(charset.len(), 53);
+
+        assert_eq!(class_charset(&[CharClass::Lower], true, false).len(), 26);
+        assert_eq!(class_charset(&[CharClass::Symbol], true, false).len(), CHARS_SYMBOLS.len() + CHARS_SYMBOLS_EXTENDED.len());
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        // Test minimum length
+        let min_password = generate_password(&mut get_secure_rng().unwrap(), 1, true, false, false).unwrap();
+        assert_eq!(min_password.len(), 1);
+
+        // Test maximum length
+        let max_password = generate_password(&mut get_secure_rng().unwrap(), 512, true, false, false).unwrap();
+        assert_eq!(max_password.len(), 512);
+    }
+
+    #[test]
+    fn test_password_entropy() {
+        assert_eq!(password_entropy(10, 64), 60.0);
+        assert_eq!(password_entropy(1, 1), 0.0);
+
+        // 62 alphanumeric + 26 symbols
+        assert_eq!(build_charset(true, false, false).len(), 88);
+        assert_eq!(build_charset(false, false, true).len(), 63);
+        assert_eq!(build_charset(true, true, true).len(), 94);
+    }
+
+    #[test]
+    fn test_custom_charset() {
+        assert_eq!(*custom_charset("abca").unwrap(), vec!['a', 'b', 'c']);
+        assert!(matches!(custom_charset(""), Err(PasswordError::EmptyCharacterSet)));
+
+        let charset = custom_charset("xyz!").unwrap();
+        let password = generate_from_charset(&mut get_secure_rng().unwrap(), 200, &charset).unwrap();
+        assert_eq!(password.len(), 200);
+        assert!(password.chars().all(|c| "xyz!".contains(c)));
+    }
+
+    #[test]
+    fn test_exclude_ambiguous() {
+        let charset = exclude_chars(&build_charset(true, true, false), &CHARS_AMBIGUOUS);
+        assert_eq!(charset.len(), 93 - CHARS_AMBIGUOUS.len());
+
+        let password = generate_from_charset(&mut get_secure_rng().unwrap(), 2000, &charset).unwrap();
+        assert!(password.chars().all(|c| !CHARS_AMBIGUOUS.contains(&c)));
+    }
+
+    #[test]
+    fn test_unique_characters() {
+        let mut rng = get_secure_rng().unwrap();
+        let charset = build_charset(true, false, false);
+        for _ in 0..20 {
+            let password = generate_unique_from_charset(&mut rng, 88, &charset).unwrap();
+            let distinct: std::collections::HashSet<char> = password.chars().collect();
+            assert_eq!(distinct.len(), 88);
+        }
+
+        assert!(matches!(
+            generate_unique_from_charset(&mut rng, 11, &custom_charset("0123456789").unwrap()),
+            Err(PasswordError::UniqueExceedsCharset(11, 10))
+        ));
+        assert_eq!(unique_password_entropy(2, 4), 12f64.log2());
+        assert_eq!(unique_password_entropy(1, 64), password_entropy(1, 64));
+    }
+
+    #[test]
+    fn test_no_adjacent_repeat() {
+        let mut rng = get_secure_rng().unwrap();
+        let password = generate_no_repeat_from_charset(&mut rng, 10_000, &['a', 'b', 'c']).unwrap();
+        let chars: Vec<char> = password.chars().collect();
+        assert!(chars.windows(2).all(|pair| pair[0] != pair[1]));
+        // Every character is still drawn about as often as the others
+        for c in ['a', 'b', 'c'] {
+            let count = chars.iter().filter(|&&x| x == c).count();
+            assert!((3_000..3_700).contains(&count), "{} drawn {} times", c, count);
+        }
+
+        assert_eq!(generate_no_repeat_from_charset(&mut rng, 1, &['x']).unwrap().as_str(), "x");
+        assert!(matches!(
+            generate_no_repeat_from_charset(&mut rng, 2, &['x']),
+            Err(PasswordError::AdjacentRepeatUnavoidable)
+        ));
+        assert_eq!(no_repeat_password_entropy(3, 3), 3f64.log2() + 2.0);
+        assert_eq!(no_repeat_password_entropy(1, 64), password_entropy(1, 64));
+        assert_eq!(no_repeat_password_entropy(0, 64), 0.0);
+    }
+
+    #[test]
+    fn test_password_uniqueness() {
+        let pass1 = generate_password(&mut get_secure_rng().unwrap(), 36, true, false, false).unwrap();
+        let pass2 = generate_password(&mut get_secure_rng().unwrap(), 36, true, false, false).unwrap();
+        assert_ne!(pass1, pass2, "Passwords should be unique");
+    }
+
+    #[test]
+    fn test_insecure_seeded_rng_is_reproducible() {
+        let pass1 = generate_password(&mut insecure_seeded_rng([7; 32]), 36, true, false, false).unwrap();
+        let pass2 = generate_password(&mut insecure_seeded_rng([7; 32]), 36, true, false, false).unwrap();
+        let pass3 = generate_password(&mut insecure_seeded_rng([8; 32]), 36, true, false, false).unwrap();
+        assert_eq!(pass1, pass2);
+        assert_ne!(pass1, pass3);
+    }
+
+    #[test]
+    fn test_length_for_entropy() {
+        let entropy = |length| password_entropy(length, 64);
+        assert_eq!(length_for_entropy(60.0, 512, entropy), Some(10));
+        assert_eq!(length_for_entropy(61.0, 512, entropy), Some(11));
+        assert_eq!(length_for_entropy(128.0, 512, |length| password_entropy(length, 88)), Some(20));
+        assert_eq!(length_for_entropy(0.5, 512, entropy), Some(1));
+        assert_eq!(length_for_entropy(128.0, 20, entropy), None);
+        assert_eq!(length_for_entropy(1.0, 512, |length| password_entropy(length, 1)), None);
+    }
+
+    #[test]
+    fn test_group_chars() {
+        assert_eq!(group_chars("hT7kPq2M9xLr", 4, '-').as_str(), "hT7k-Pq2M-9xLr");
+        assert_eq!(group_chars("abcdefg", 3, ' ').as_str(), "abc def g");
+        assert_eq!(group_chars("abc", 5, '-').as_str(), "abc");
+        assert_eq!(group_chars("🐙🚀é", 2, '.').as_str(), "🐙🚀.é");
+        assert_eq!(group_chars("", 4, '-').as_str(), "");
+    }
+
+    #[test]
+    fn test_all_character_sets() {
+        let password = generate_password(&mut get_secure_rng().unwrap(), 1000, true, true, true).unwrap();
+
+        // Test the presence of each character set
+        assert!(password.chars().any(|c| c.is_ascii_lowercase()), "Missing lowercase letters");
+        assert!(password.chars().any(|c| c.is_ascii_uppercase()), "Missing uppercase letters");
+        assert!(password.chars().any(|c| c.is_ascii_digit()), "Missing numbers");
+        assert!(password.chars().any(|c| CHARS_SYMBOLS.contains(&c)), "Missing symbols");
+        assert!(password.chars().any(|c| CHARS_SYMBOLS_EXTENDED.contains(&c)), "Missing extended symbols");
+        assert!(password.chars().any(|c| c == ' '), "Missing space");
+    }
+
+    #[test]
+    fn test_character_distribution() {
+        let password = generate_password(&mut get_secure_rng().unwrap(), 10000, true, true, true).unwrap();
+        let char_counts: std::collections::HashMap<char, usize> =
+            password.chars().fold(std::collections::HashMap::new(), |mut map, c| {
+                *map.entry(c).or_insert(0) += 1;
+                map
+            });
+
+        // Check that each character type appears at least once
+        assert!(char_counts.keys().any(|c| c.is_ascii_lowercase()));
+        assert!(char_counts.keys().any(|c| c.is_ascii_uppercase()));
+        assert!(char_counts.keys().any(|c| c.is_ascii_digit()));
+    }
+}
@@ -0,0 +1,86 @@
+//! `--generate-man`: a roff man page for distribution packages, with the
+//! options and subcommands from the parser plus the built-in presets and
+//! the exit statuses.
+
+use std::io::{self, Write};
+
+use password::EXIT_STATUSES;
+
+use crate::presets::BUILTIN_PRESETS;
+
+/// Escape `text` for a roff text line: backslashes and hyphens are escaped and a leading
+/// `.` or `'` can't start a request
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with(['.', '\'']) {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// A section of tagged paragraphs, one per `(tag, text)`
+fn tagged_section(title: &str, intro: &str, items: &[(String, &str)]) -> String {
+    let mut section = format!(".SH {}\n{}\n", title, escape(intro));
+    for (tag, text) in items {
+        section.push_str(&format!(".TP\n\\fB{}\\fR\n{}\n", escape(tag), escape(text)));
+    }
+    section
+}
+
+/// The PRESETS section, listing every built-in preset
+fn presets_section() -> String {
+    let items: Vec<(String, &str)> =
+        BUILTIN_PRESETS.iter().map(|(name, description, _)| (name.to_string(), *description)).collect();
+    tagged_section(
+        "PRESETS",
+        "Select with --preset NAME. User presets can be added in ~/.config/password-generator/presets.toml.",
+        &items,
+    )
+}
+
+/// The EXIT STATUS section
+fn exit_status_section() -> String {
+    let items: Vec<(String, &str)> = EXIT_STATUSES.iter().map(|(code, meaning)| (code.to_string(), *meaning)).collect();
+    tagged_section("EXIT STATUS", "The statuses are stable, so scripts can branch on them.", &items)
+}
+
+/// Render the man page for `command`
+pub fn render(command: clap::Command, out: &mut dyn Write) -> io::Result<()> {
+    let man = clap_mangen::Man::new(command);
+    man.render_title(out)?;
+    man.render_name_section(out)?;
+    man.render_synopsis_section(out)?;
+    man.render_description_section(out)?;
+    man.render_options_section(out)?;
+    man.render_subcommands_section(out)?;
+    out.write_all(presets_section().as_bytes())?;
+    out.write_all(exit_status_section().as_bytes())?;
+    man.render_version_section(out)?;
+    man.render_authors_section(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("--preset a\\b"), "\\-\\-preset a\\eb");
+        assert_eq!(escape(".hidden"), "\\&.hidden");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut page = Vec::new();
+        render(crate::cli(), &mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.starts_with(".ie"));
+        assert!(page.contains(".SH OPTIONS"));
+        assert!(page.contains("\\-\\-length"));
+        assert!(page.contains(".SH SUBCOMMANDS"));
+        assert!(page.contains("\\fBaws\\-iam\\fR"));
+        assert!(page.contains(".SH \"EXIT STATUS\"") || page.contains(".SH EXIT STATUS"));
+        assert!(page.contains("\\fB5\\fR"));
+    }
+}
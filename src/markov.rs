@@ -0,0 +1,223 @@
+//! Pronounceable non-words sampled from a character n-gram (Markov) model
+//! trained on real words, so they read like a language instead of
+//! alternating consonants and vowels.
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::passphrase::{Language, Wordlist};
+use crate::{PasswordError, Secret};
+
+/// Letters of context each character is drawn from
+pub const ORDER: usize = 2;
+
+/// Fewest distinct words a user-supplied corpus must contain
+pub const MIN_CORPUS_WORDS: usize = 100;
+
+/// Pads the context before the first letter of a word
+const START: char = '^';
+
+/// Where the model can go from one context: the letters seen after it, how
+/// often, and the context each of them leads to
+#[derive(Debug)]
+struct State {
+    letters: Vec<char>,
+    weights: WeightedIndex<u32>,
+    bits: Vec<f64>,
+    next: Vec<usize>,
+}
+
+/// A character model of a corpus, unrolled into every context a generated
+/// password can reach
+#[derive(Debug)]
+pub struct MarkovModel {
+    states: Vec<State>,
+    words: usize,
+}
+
+impl MarkovModel {
+    /// The model of the embedded wordlist for `language`
+    pub fn builtin(language: Language) -> Self {
+        let wordlist = Wordlist::builtin(language);
+        Self::train(wordlist.words().iter().map(String::as_str)).expect("the builtin wordlists train a model")
+    }
+
+    /// Train a model on the text of the file at `path`
+    pub fn from_file(path: &Path) -> Result<Self, PasswordError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| PasswordError::CorpusRead(format!("{}: {}", path.display(), err)))?;
+        Self::parse(&contents)
+    }
+
+    /// Train a model on the words of `text`: runs of letters, lowercased,
+    /// so prose works as well as a wordlist
+    pub fn parse(text: &str) -> Result<Self, PasswordError> {
+        Self::train(text.split(|c: char| !c.is_alphabetic()))
+    }
+
+    /// Train a model on `words`. Each distinct word counts once, so a word
+    /// repeated throughout a text doesn't make its letters more predictable.
+    pub fn train<'a, I: IntoIterator<Item = &'a str>>(words: I) -> Result<Self, PasswordError> {
+        let words: HashSet<String> = words
+            .into_iter()
+            .map(str::to_lowercase)
+            .filter(|word| !word.is_empty() && word.chars().all(char::is_alphabetic))
+            .collect();
+        if words.len() < MIN_CORPUS_WORDS {
+            return Err(PasswordError::CorpusTooSmall(words.len()));
+        }
+
+        let mut counts: HashMap<Vec<char>, BTreeMap<char, u32>> = HashMap::new();
+        for word in &words {
+            let mut context = vec![START; ORDER];
+            for letter in word.chars() {
+                *counts.entry(context.clone()).or_default().entry(letter).or_default() += 1;
+                context.remove(0);
+                context.push(letter);
+            }
+        }
+
+        Ok(MarkovModel { states: unroll(&counts), words: words.len() })
+    }
+
+    /// Distinct words the model was trained on
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    /// Sample `length` letters from the model
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R, length: u32) -> Secret {
+        let mut password = Secret::new(String::with_capacity(length as usize));
+        let mut state = 0;
+        for _ in 0..length {
+            let current = &self.states[state];
+            let choice = current.weights.sample(rng);
+            password.push(current.letters[choice]);
+            state = current.next[choice];
+        }
+        password
+    }
+
+    /// Min-entropy in bits of `length` letters: how hard the single most
+    /// likely output is to guess for an attacker who knows the model. This
+    /// is lower than the average, since common letter sequences are drawn
+    /// more often.
+    pub fn entropy(&self, length: u32) -> f64 {
+        let mut bits = vec![0.0; self.states.len()];
+        for _ in 0..length {
+            bits = self.step(&bits);
+        }
+        bits[0]
+    }
+
+    /// Shortest length up to `max_length` whose min-entropy reaches `bits`
+    pub fn length_for_entropy(&self, bits: f64, max_length: u32) -> Option<u32> {
+        let mut entropy = vec![0.0; self.states.len()];
+        for length in 1..=max_length {
+            entropy = self.step(&entropy);
+            if entropy[0] >= bits - 1e-9 {
+                return Some(length);
+            }
+        }
+        None
+    }
+
+    /// The fewest bits of one more letter from each state, given the fewest
+    /// bits of the letters after it
+    fn step(&self, after: &[f64]) -> Vec<f64> {
+        self.states
+            .iter()
+            .map(|state| {
+                state
+                    .bits
+                    .iter()
+                    .zip(&state.next)
+                    .map(|(bits, &next)| bits + after[next])
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect()
+    }
+}
+
+/// Every context reachable from the start of a word, numbered from 0.
+/// A context no corpus word continues from (the end of a word) draws from
+/// the start of a word instead, but keeps its own letters as context, so
+/// each password still has exactly one path through the model.
+fn unroll(counts: &HashMap<Vec<char>, BTreeMap<char, u32>>) -> Vec<State> {
+    let start = vec![START; ORDER];
+    let mut index: HashMap<Vec<char>, usize> = HashMap::from([(start.clone(), 0)]);
+    let mut contexts = vec![start.clone()];
+    let mut states = Vec::new();
+
+    while states.len() < contexts.len() {
+        let context = contexts[states.len()].clone();
+        let followers = counts.get(&context).unwrap_or(&counts[&start]);
+        let total: u32 = followers.values().sum();
+
+        let mut next = Vec::with_capacity(followers.len());
+        for &letter in followers.keys() {
+            let mut following = context[1..].to_vec();
+            following.push(letter);
+            let id = *index.entry(following.clone()).or_insert_with(|| {
+                contexts.push(following);
+                contexts.len() - 1
+            });
+            next.push(id);
+        }
+
+        states.push(State {
+            letters: followers.keys().copied().collect(),
+            weights: WeightedIndex::new(followers.values()).expect("every context has a follower"),
+            bits: followers.values().map(|&count| (total as f64 / count as f64).log2()).collect(),
+            next,
+        });
+    }
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_markov_generate() {
+        let model = MarkovModel::builtin(Language::English);
+        assert!(model.words() > 7700);
+        let password = model.generate(&mut get_secure_rng().unwrap(), 40);
+        assert_eq!(password.chars().count(), 40);
+        assert!(password.chars().all(|c| c.is_ascii_lowercase()));
+    }
+
+    #[test]
+    fn test_markov_entropy() {
+        // Runs of 'a' or 'b': only the first letter is a choice
+        let corpus: Vec<String> = (0..MIN_CORPUS_WORDS).map(|i| format!("{}", ['a', 'b'][i % 2]).repeat(i / 2 + 1)).collect();
+        let model = MarkovModel::train(corpus.iter().map(String::as_str)).unwrap();
+        assert!((model.entropy(1) - 1.0).abs() < 1e-9);
+        assert!((model.entropy(3) - 1.0).abs() < 1e-9);
+
+        let model = MarkovModel::builtin(Language::English);
+        assert_eq!(model.entropy(0), 0.0);
+        // Honest, so below a uniform draw of the same letters...
+        assert!(model.entropy(16) < 16.0 * 26f64.log2());
+        // ...and never shrinking as the password grows
+        assert!(model.entropy(17) >= model.entropy(16));
+        let length = model.length_for_entropy(60.0, 256).unwrap();
+        assert!(model.entropy(length) >= 60.0 - 1e-9);
+        assert!(model.entropy(length - 1) < 60.0);
+        assert_eq!(model.length_for_entropy(1e6, 64), None);
+    }
+
+    #[test]
+    fn test_markov_corpus() {
+        assert!(matches!(MarkovModel::parse("too few words"), Err(PasswordError::CorpusTooSmall(3))));
+        // Words are runs of letters in any script, counted once
+        let text: String = (0..200).map(|i| format!("Wort{}, wort{}! 42 ", "ä".repeat(i + 1), "ä".repeat(i + 1))).collect();
+        let model = MarkovModel::parse(&text).unwrap();
+        assert_eq!(model.words(), 200);
+    }
+}
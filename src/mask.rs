@@ -0,0 +1,146 @@
+//! `--mask`: show the password as bullets on the terminal and reveal it only
+//! while a key is held down, for generating credentials during a screen
+//! share. Terminals report key presses but not releases, so a held key is
+//! recognised by its auto-repeat: the password is masked again once no key
+//! has arrived for a moment.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+
+use password::PasswordError;
+
+const BULLET: char = '•';
+
+/// Tenths of a second without a key before the password is masked again.
+/// Longer than the usual auto-repeat delay, so holding a key doesn't flicker.
+const REPEAT_TIMEOUT: &str = "7";
+
+const ENTER: u8 = b'\r';
+const NEWLINE: u8 = b'\n';
+const ESCAPE: u8 = 0x1b;
+const CTRL_C: u8 = 0x03;
+const CTRL_D: u8 = 0x04;
+
+/// What a key press, or the lack of one, does to the display
+#[derive(Debug, PartialEq)]
+enum Action {
+    Reveal,
+    Hide,
+    Finish,
+    Keep,
+}
+
+/// The next action given whether the password is showing and the key read
+/// (`None` when the repeat timeout passed without one)
+fn step(revealed: bool, key: Option<u8>) -> Action {
+    match key {
+        Some(ENTER | NEWLINE | ESCAPE | CTRL_C | CTRL_D | b'q') => Action::Finish,
+        Some(_) if !revealed => Action::Reveal,
+        Some(_) => Action::Keep,
+        None if revealed => Action::Hide,
+        None => Action::Keep,
+    }
+}
+
+/// One bullet per character of `secret`
+fn masked(secret: &str) -> String {
+    secret.chars().map(|_| BULLET).collect()
+}
+
+/// The terminal in non-canonical mode without echo or signals, restored to
+/// its saved settings when dropped
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Result<Self, PasswordError> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "0", "time", REPEAT_TIMEOUT])?;
+        Ok(RawMode { saved: saved.trim().to_string() })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Run `stty` on the controlling terminal
+fn stty(args: &[&str]) -> Result<String, PasswordError> {
+    let tty = File::open("/dev/tty").map_err(|err| PasswordError::Terminal(format!("/dev/tty: {}", err)))?;
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(tty)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| PasswordError::Terminal(format!("stty: {}", err)))?;
+
+    if !output.status.success() {
+        return Err(PasswordError::Terminal("stty could not change the terminal mode".to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Show `secret` masked on the terminal until Enter, Escape or `q`, revealing
+/// it while any other key is held. Only bullets are left on the screen.
+pub fn show(secret: &str) -> Result<(), PasswordError> {
+    if !cfg!(unix) {
+        return Err(PasswordError::Terminal("--mask needs a Unix terminal".to_string()));
+    }
+
+    let error = |err: std::io::Error| PasswordError::Terminal(format!("/dev/tty: {}", err));
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").map_err(error)?;
+    let hidden = masked(secret);
+
+    eprintln!("Hold any key to reveal the password; press Enter to finish.");
+    let raw = RawMode::enable()?;
+    write!(tty, "{}", hidden).and_then(|_| tty.flush()).map_err(error)?;
+
+    let mut revealed = false;
+    let mut key = [0u8; 1];
+    loop {
+        let read = tty.read(&mut key).map_err(error)?;
+        let shown = match step(revealed, (read > 0).then_some(key[0])) {
+            Action::Reveal => secret,
+            Action::Hide => hidden.as_str(),
+            Action::Finish => break,
+            Action::Keep => continue,
+        };
+        revealed = !revealed;
+        write!(tty, "\r\x1b[K{}", shown).and_then(|_| tty.flush()).map_err(error)?;
+    }
+
+    let result = writeln!(tty, "\r\x1b[K{}", hidden).map_err(error);
+    drop(raw);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked() {
+        assert_eq!(masked("s3cr#t"), "••••••");
+        assert_eq!(masked("пароль"), "••••••");
+        assert_eq!(masked(""), "");
+    }
+
+    #[test]
+    fn test_step() {
+        // A key press reveals, auto-repeat keeps it revealed, and the
+        // timeout after the key is released masks it again
+        assert_eq!(step(false, Some(b' ')), Action::Reveal);
+        assert_eq!(step(true, Some(b' ')), Action::Keep);
+        assert_eq!(step(true, None), Action::Hide);
+        assert_eq!(step(false, None), Action::Keep);
+
+        for key in [ENTER, NEWLINE, ESCAPE, CTRL_C, CTRL_D, b'q'] {
+            assert_eq!(step(false, Some(key)), Action::Finish);
+            assert_eq!(step(true, Some(key)), Action::Finish);
+        }
+    }
+}
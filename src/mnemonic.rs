@@ -0,0 +1,78 @@
+//! Mnemonic sentences (`--mnemonic`) for learning a random password by
+//! heart: every letter becomes a word starting with it, in the same case,
+//! and digits and symbols stand as themselves, so `h7Tq` might read
+//! `horses 7 Take quinoa`.
+//!
+//! The words come from the EFF large wordlist and are picked at random. They
+//! are a memory aid derived from the password, so they add no entropy and
+//! reveal exactly as much as the password itself.
+
+use once_cell::sync::Lazy;
+use rand::Rng;
+
+use crate::Secret;
+
+/// Stands in for a space in the password, which would otherwise vanish
+/// between the words
+const SPACE: char = '␣';
+
+/// The alphabetic words of the EFF large wordlist, by first letter
+static WORDS: Lazy<[Vec<&'static str>; 26]> = Lazy::new(|| {
+    let mut words: [Vec<&'static str>; 26] = Default::default();
+    let list = include_str!("wordlists/eff_large.txt").lines().filter_map(|line| line.split('\t').nth(1));
+    for word in list.filter(|word| word.chars().all(|c| c.is_ascii_lowercase())) {
+        words[(word.as_bytes()[0] - b'a') as usize].push(word);
+    }
+    words
+});
+
+/// A word for each character of `text`, separated by spaces
+pub fn sentence<R: Rng + ?Sized>(rng: &mut R, text: &str) -> Secret {
+    let mut sentence = Secret::new(String::with_capacity(text.len() * 8));
+
+    for (i, c) in text.chars().enumerate() {
+        if i > 0 {
+            sentence.push(' ');
+        }
+        if !c.is_ascii_alphabetic() {
+            sentence.push(if c == ' ' { SPACE } else { c });
+            continue;
+        }
+
+        let words = &WORDS[(c.to_ascii_lowercase() as u8 - b'a') as usize];
+        let word = words[rng.gen_range(0..words.len())];
+        if c.is_ascii_uppercase() {
+            sentence.push(c);
+            sentence.push_str(&word[1..]);
+        } else {
+            sentence.push_str(word);
+        }
+    }
+
+    sentence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_every_letter_has_words() {
+        assert!(WORDS.iter().all(|words| !words.is_empty()));
+        assert!(WORDS[0].contains(&"abacus"));
+    }
+
+    #[test]
+    fn test_sentence() {
+        let mut rng = get_secure_rng().unwrap();
+        let sentence = sentence(&mut rng, "h7Tq$ é");
+        let words: Vec<&str> = sentence.split(' ').collect();
+        assert_eq!(words.len(), 7);
+        assert!(words[0].starts_with('h') && words[0].len() > 1);
+        assert_eq!(words[1], "7");
+        assert!(words[2].starts_with('T') && words[2][1..].chars().all(|c| c.is_ascii_lowercase()));
+        assert!(words[3].starts_with('q'));
+        assert_eq!(words[4..], ["$", "␣", "é"]);
+    }
+}
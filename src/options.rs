@@ -0,0 +1,113 @@
+//! A single options struct covering the common generation modes, shared by
+//! the WebAssembly and C bindings.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::passphrase::{generate_passphrase, Wordlist, MAX_WORD_COUNT};
+use crate::pronounceable::generate_pronounceable;
+use crate::{
+    build_charset, exclude_chars, generate_from_charset, get_secure_rng, PasswordError, Secret,
+    CHARS_AMBIGUOUS, MAX_LENGTH_LIMIT,
+};
+
+/// What to generate. Defaults match the command-line tool: 36 characters
+/// with letters, digits and symbols.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub length: u32,
+    pub symbols: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = extendedSymbols))]
+    pub extended_symbols: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = allowSpace))]
+    pub allow_space: bool,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = excludeAmbiguous))]
+    pub exclude_ambiguous: bool,
+    /// Generate a passphrase of this many EFF words instead
+    pub words: Option<u16>,
+    /// Generate a pronounceable password of `length` characters instead
+    pub pronounceable: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            length: 36,
+            symbols: true,
+            extended_symbols: false,
+            allow_space: false,
+            exclude_ambiguous: false,
+            words: None,
+            pronounceable: false,
+        }
+    }
+}
+
+/// Generate a password, passphrase or pronounceable password from a freshly
+/// seeded generator
+pub fn generate(options: &Options) -> Result<Secret, PasswordError> {
+    validate(options)?;
+    let mut rng = get_secure_rng()?;
+
+    if let Some(words) = options.words {
+        return generate_passphrase(&mut rng, words, &Wordlist::eff_large());
+    }
+    if options.pronounceable {
+        return generate_pronounceable(&mut rng, options.length);
+    }
+    if options.extended_symbols && !options.symbols {
+        return Err(PasswordError::InvalidSymbolCombination);
+    }
+
+    let mut charset = build_charset(options.symbols, options.extended_symbols, options.allow_space);
+    if options.exclude_ambiguous {
+        charset = exclude_chars(&charset, &CHARS_AMBIGUOUS);
+    }
+    generate_from_charset(&mut rng, options.length, &charset)
+}
+
+/// Check the length, or the word count of a passphrase, is in range
+fn validate(options: &Options) -> Result<(), PasswordError> {
+    match options.words {
+        Some(words) if !(1..=MAX_WORD_COUNT).contains(&words) => Err(PasswordError::WordCountOutOfRange(words)),
+        Some(_) => Ok(()),
+        None if !(1..=MAX_LENGTH_LIMIT).contains(&options.length) => Err(PasswordError::LengthOutOfRange(options.length)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options() {
+        let password = generate(&Options::default()).unwrap();
+        assert_eq!(password.chars().count(), 36);
+    }
+
+    #[test]
+    fn test_passphrase_options() {
+        let options = Options { words: Some(5), ..Options::default() };
+        assert_eq!(generate(&options).unwrap().split(' ').count(), 5);
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let options = Options { symbols: false, extended_symbols: true, ..Options::default() };
+        assert!(matches!(generate(&options), Err(PasswordError::InvalidSymbolCombination)));
+
+        let options = Options { length: 0, ..Options::default() };
+        assert!(matches!(generate(&options), Err(PasswordError::LengthOutOfRange(0))));
+        let options = Options { length: 0, pronounceable: true, ..Options::default() };
+        assert!(matches!(generate(&options), Err(PasswordError::LengthOutOfRange(0))));
+        // The bindings take any u32, which must not turn into a huge allocation
+        let options = Options { length: u32::MAX, ..Options::default() };
+        assert!(matches!(generate(&options), Err(PasswordError::LengthOutOfRange(u32::MAX))));
+        let options = Options { words: Some(0), ..Options::default() };
+        assert!(matches!(generate(&options), Err(PasswordError::WordCountOutOfRange(0))));
+        let options = Options { words: Some(65), ..Options::default() };
+        assert!(generate(&options).is_err());
+    }
+}
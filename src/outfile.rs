@@ -0,0 +1,123 @@
+//! `--out`: write generated passwords to a file only the current user can
+//! read, instead of relying on the umask of a shell redirection.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use password::PasswordError;
+
+/// Write `contents` to a new file at `path` readable only by its owner.
+/// An existing file is replaced only when `force` is set.
+pub fn write(path: &Path, contents: &str, force: bool) -> Result<(), PasswordError> {
+    let error = |reason: String| PasswordError::OutputFile(format!("{}: {}", path.display(), reason));
+
+    // Replace rather than truncate, so the new file gets fresh permissions
+    // and a symlink at `path` is never written through
+    if force {
+        match fs::remove_file(path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(error(err.to_string())),
+        }
+    }
+
+    let mut file = create_private(path).map_err(|err| match err.kind() {
+        ErrorKind::AlreadyExists => error("file exists (use --force to overwrite)".to_string()),
+        _ => error(err.to_string()),
+    })?;
+
+    file.write_all(contents.as_bytes())
+        .and_then(|_| file.write_all(b"\n"))
+        .and_then(|_| file.sync_all())
+        .map_err(|err| error(err.to_string()))
+}
+
+/// Create a file that doesn't exist yet with mode 0600
+#[cfg(unix)]
+pub fn create_private(path: &Path) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)
+}
+
+/// Create a file that doesn't exist yet and replace its inherited ACL with
+/// one granting access to the current user only
+#[cfg(windows)]
+pub fn create_private(path: &Path) -> std::io::Result<File> {
+    use std::process::{Command, Stdio};
+
+    let file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    let user = std::env::var("USERNAME").map_err(|_| {
+        std::io::Error::new(ErrorKind::Other, "USERNAME is not set")
+    })?;
+
+    let status = Command::new("icacls")
+        .arg(path)
+        .args(["/inheritance:r", "/grant:r"])
+        .arg(format!("{}:F", user))
+        .stdout(Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => Ok(file),
+        _ => {
+            drop(file);
+            let _ = fs::remove_file(path);
+            Err(std::io::Error::new(ErrorKind::Other, "icacls could not restrict access to the file"))
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn create_private(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+
+    fn scratch_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("password-out-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_write_new_file() {
+        let path = scratch_path("new");
+        write(&path, "secret", false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_refuse_to_overwrite() {
+        let path = scratch_path("existing");
+        fs::write(&path, "keep").unwrap();
+
+        match write(&path, "secret", false) {
+            Err(PasswordError::OutputFile(reason)) => assert!(reason.contains("--force")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep");
+
+        write(&path, "secret", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "secret\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,847 @@
+use clap::ValueEnum;
+use password::analyze::Analysis;
+use password::strength::{self, Strength};
+use password::{grapheme_len, Secret};
+use serde::Serialize;
+
+use crate::export::{self, Account};
+
+/// How generated passwords are written to stdout
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// One password per line
+    #[default]
+    Text,
+    /// One JSON object per line with the password and its statistics
+    Json,
+    /// CSV with a header row: index, password, length and entropy
+    Csv,
+    /// `user:hash` lines for an Apache/nginx htpasswd file (passwords go to stderr)
+    Htpasswd,
+    /// KeePassXC CSV import: group, title, username, password, URL and notes
+    KeepassCsv,
+    /// KeePass 2.x XML import, one entry per password
+    KeepassXml,
+    /// Bitwarden JSON import, one login item per password
+    BitwardenJson,
+    /// 1Password CSV import: title, website, username, password and notes
+    #[value(name = "1password-csv")]
+    OnepasswordCsv,
+    /// `export NAME='password'` lines for a shell to eval or source
+    Env,
+    /// A Kubernetes Secret manifest with each password base64-encoded under --key
+    K8sSecret,
+    /// `NAME = "password"` lines for a Terraform .tfvars file
+    Tfvars,
+    /// A sensitive Terraform variable block per password, defaulting to it
+    Hcl,
+}
+
+/// How a password is escaped in text output, so it can be pasted into
+/// another context as-is
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Escape {
+    /// The password exactly as generated
+    #[default]
+    None,
+    /// A single-quoted shell word
+    Shell,
+    /// A JSON string literal, quotes included
+    Json,
+    /// Percent-encoded for a URL
+    Url,
+}
+
+/// When passwords printed as text are highlighted by character class
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ColorMode {
+    /// When stdout is a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// The ANSI color of `c` in highlighted output: blue digits, green
+/// uppercase letters and red symbols. Lowercase letters and spaces keep the
+/// terminal's color.
+fn class_color(c: char) -> Option<&'static str> {
+    if c.is_ascii_digit() {
+        Some("\x1b[34m")
+    } else if c.is_uppercase() {
+        Some("\x1b[32m")
+    } else if c.is_alphabetic() || c.is_whitespace() {
+        None
+    } else {
+        Some("\x1b[31m")
+    }
+}
+
+/// `value` with its digits, uppercase letters and symbols in distinct colors,
+/// so `l`, `1` and `I` can be told apart at a glance
+pub fn highlight(value: &str) -> Secret {
+    let mut highlighted = Secret::new(String::with_capacity(value.len() * 4));
+    let mut current = None;
+    for c in value.chars() {
+        let color = class_color(c);
+        if color != current {
+            highlighted.push_str(color.unwrap_or(RESET));
+            current = color;
+        }
+        highlighted.push(c);
+    }
+    if current.is_some() {
+        highlighted.push_str(RESET);
+    }
+    highlighted
+}
+
+/// `value` escaped for `escape`
+pub fn escape(escape: Escape, value: &str) -> Secret {
+    match escape {
+        Escape::None => Secret::new(value.to_string()),
+        Escape::Shell => shell_quote(value),
+        Escape::Json => Secret::new(serde_json::to_string(value).expect("strings serialize")),
+        Escape::Url => percent_encode(value),
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> Secret {
+    let mut encoded = Secret::new(String::with_capacity(value.len() * 3));
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// A password and its statistics, as written by `--format json`
+#[derive(Serialize)]
+pub struct JsonRecord<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    password: &'a str,
+    length: usize,
+    charset_size: Option<usize>,
+    entropy_bits: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guesses_log10: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<&'a str>,
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// One line with the expected time to crack a secret of `entropy_bits` at
+/// `guesses_per_second`, e.g. `Crack time: 20 million years at 1e12 guesses/s (md5)`
+pub fn crack_time_line(entropy_bits: f64, guesses_per_second: f64, model: &str) -> String {
+    let seconds = strength::crack_seconds(entropy_bits, guesses_per_second);
+    let rate = if guesses_per_second < 1e4 {
+        format!("{}", guesses_per_second)
+    } else {
+        format!("{:e}", guesses_per_second)
+    };
+    format!("Crack time: {} at {} guesses/s ({})", strength::format_duration(seconds), rate, model)
+}
+
+/// One line summarising zxcvbn's estimate for a password
+pub fn score_line(strength: &Strength) -> String {
+    let mut line = format!(
+        "Score: {}/4 (~10^{:.2} guesses)",
+        strength.score, strength.guesses_log10
+    );
+    if let Some(warning) = &strength.warning {
+        line.push_str(&format!(" - {}", warning));
+    }
+    line
+}
+
+/// Breach-list verdicts for `password analyze`; `None` when that list wasn't checked
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BreachChecks {
+    pub blocklisted: Option<bool>,
+    pub pwned: Option<bool>,
+}
+
+/// The report of `password analyze --format json`. The password itself is
+/// never included.
+#[derive(Serialize)]
+struct AnalysisRecord<'a> {
+    length: usize,
+    classes: &'a [&'static str],
+    pool_size: usize,
+    charset_entropy_bits: f64,
+    estimated_entropy_bits: f64,
+    score: u8,
+    guesses_log10: f64,
+    findings: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blocklisted: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pwned: Option<bool>,
+}
+
+pub fn analysis_json(analysis: &Analysis, checks: BreachChecks) -> String {
+    let record = AnalysisRecord {
+        length: analysis.length,
+        classes: &analysis.classes,
+        pool_size: analysis.pool_size,
+        charset_entropy_bits: round2(analysis.charset_entropy),
+        estimated_entropy_bits: round2(analysis.estimated_entropy()),
+        score: analysis.strength.score,
+        guesses_log10: round2(analysis.strength.guesses_log10),
+        findings: &analysis.findings,
+        blocklisted: checks.blocklisted,
+        pwned: checks.pwned,
+    };
+    serde_json::to_string(&record).expect("JSON record is always serializable")
+}
+
+/// The human-readable report of `password analyze`
+pub fn analysis_lines(analysis: &Analysis, checks: BreachChecks) -> Vec<String> {
+    let classes = if analysis.classes.is_empty() { "none".to_string() } else { analysis.classes.join(", ") };
+    let mut lines = vec![
+        format!("Length: {}", analysis.length),
+        format!("Classes: {} (pool of {})", classes, analysis.pool_size),
+        format!("Charset entropy: {:.2} bits", analysis.charset_entropy),
+        format!("Score: {}/4 (~10^{:.2} guesses)", analysis.strength.score, analysis.strength.guesses_log10),
+        format!("Estimated entropy: {:.2} bits", analysis.estimated_entropy()),
+    ];
+
+    if analysis.findings.is_empty() {
+        lines.push("Findings: none".to_string());
+    } else {
+        lines.push("Findings:".to_string());
+        lines.extend(analysis.findings.iter().map(|finding| format!("  - {}", finding)));
+    }
+
+    if let Some(blocklisted) = checks.blocklisted {
+        lines.push(format!("Blocklist: {}", if blocklisted { "listed" } else { "not listed" }));
+    }
+    if let Some(pwned) = checks.pwned {
+        lines.push(format!("Have I Been Pwned: {}", if pwned { "found in a breach" } else { "not found" }));
+    }
+    lines
+}
+
+impl<'a> JsonRecord<'a> {
+    /// `charset_size` is `None` for modes where positions draw from different
+    /// pools (templates, pronounceable passwords, encoded tokens).
+    pub fn new(
+        password: &'a str,
+        charset_size: Option<usize>,
+        entropy_bits: f64,
+        strength: Option<&Strength>,
+    ) -> Self {
+        JsonRecord {
+            label: None,
+            password,
+            length: grapheme_len(password),
+            charset_size,
+            // Two decimal places, matching --show-entropy
+            entropy_bits: round2(entropy_bits),
+            score: strength.map(|strength| strength.score),
+            guesses_log10: strength.map(|strength| round2(strength.guesses_log10)),
+            hash: None,
+        }
+    }
+
+    /// Include a hash of the password (`--hash`)
+    pub fn with_hash(mut self, hash: Option<&'a str>) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Include the label the password was generated for (`--labels`)
+    pub fn with_label(mut self, label: Option<&'a str>) -> Self {
+        self.label = label;
+        self
+    }
+}
+
+/// Describe a single password as a JSON object, with its label when it has
+/// one
+pub fn json_record(
+    label: Option<&str>,
+    password: &str,
+    charset_size: Option<usize>,
+    entropy_bits: f64,
+    strength: Option<&Strength>,
+    hash: Option<&str>,
+) -> String {
+    let record = JsonRecord::new(password, charset_size, entropy_bits, strength)
+        .with_hash(hash)
+        .with_label(label);
+    serde_json::to_string(&record).expect("JSON record is always serializable")
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, line break or
+/// surrounding whitespace (RFC 4180)
+fn csv_field(value: &str) -> Secret {
+    let needs_quotes = value.contains([',', '"', '\n', '\r'])
+        || value.starts_with(' ')
+        || value.ends_with(' ');
+    if !needs_quotes {
+        return Secret::new(value.to_string());
+    }
+
+    let mut quoted = Secret::new(String::with_capacity(value.len() * 2 + 2));
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' {
+            quoted.push('"');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// One CSV row for the password at (1-based) `index`, with its label when it
+/// has one
+pub fn csv_record(
+    index: usize,
+    label: Option<&str>,
+    password: &str,
+    entropy_bits: f64,
+    strength: Option<&Strength>,
+    hash: Option<&str>,
+) -> Secret {
+    let field = csv_field(password);
+    let mut row = Secret::new(String::with_capacity(field.len() + 32));
+    row.push_str(&format!("{},", index));
+    if let Some(label) = label {
+        row.push_str(&csv_field(label));
+        row.push(',');
+    }
+    row.push_str(&field);
+    row.push_str(&format!(",{},{:.2}", grapheme_len(password), entropy_bits));
+    if let Some(strength) = strength {
+        row.push_str(&format!(",{}", strength.score));
+    }
+    if let Some(hash) = hash {
+        row.push(',');
+        row.push_str(&csv_field(hash));
+    }
+    row
+}
+
+/// A generated password with the statistics of the generator that produced it
+pub struct Generated {
+    pub password: Secret,
+    pub charset_size: Option<usize>,
+    pub entropy_bits: f64,
+    /// Hash of the password requested with `--hash`
+    pub hash: Option<String>,
+    /// Account details for password manager exports, from batch spec keys
+    pub account: Account,
+    /// The label from `--labels` the password was generated for
+    pub label: Option<String>,
+    /// Sentence to remember the password by, with `--mnemonic`
+    pub mnemonic: Option<Secret>,
+}
+
+/// Format a batch of passwords for stdout. With `with_score`, JSON and CSV records
+/// also carry zxcvbn's estimate. Hashes follow their password, tab-separated in
+/// text output. htpasswd lines need a user name and are built by
+/// [`htpasswd_lines`] instead; this formats them as text.
+pub fn format_passwords(format: OutputFormat, entries: &[Generated], with_score: bool) -> Secret {
+    let with_hash = entries.iter().any(|entry| entry.hash.is_some());
+    let with_label = entries.iter().any(|entry| entry.label.is_some());
+    let folders = export::folders(entries.iter().map(|entry| &entry.account));
+    let header = format_header(format, with_score, with_hash, with_label, &folders).map(Secret::new);
+    let footer = format_footer(format).map(|footer| Secret::new(footer.to_string()));
+    let lines: Vec<Secret> = header
+        .into_iter()
+        .chain(entries.iter().enumerate().map(|(i, entry)| format_record(format, i + 1, entry, with_score)))
+        .chain(footer)
+        .collect();
+
+    join_lines(&lines.iter().map(|line| line.as_str()).collect::<Vec<_>>())
+}
+
+/// What comes before the first record, if the format has a header.
+/// `folders` are the password manager folders the records are filed under.
+pub fn format_header(
+    format: OutputFormat,
+    with_score: bool,
+    with_hash: bool,
+    with_label: bool,
+    folders: &[&str],
+) -> Option<String> {
+    match format {
+        OutputFormat::Csv => Some(csv_header(with_score, with_hash, with_label)),
+        OutputFormat::KeepassCsv => Some(export::KEEPASS_CSV_HEADER.to_string()),
+        OutputFormat::KeepassXml => Some(export::KEEPASS_XML_HEADER.to_string()),
+        OutputFormat::BitwardenJson => Some(export::bitwarden_header(folders)),
+        OutputFormat::OnepasswordCsv => Some(export::ONEPASSWORD_CSV_HEADER.to_string()),
+        _ => None,
+    }
+}
+
+/// What comes after the last record, if the format needs closing
+pub fn format_footer(format: OutputFormat) -> Option<&'static str> {
+    match format {
+        OutputFormat::KeepassXml => Some(export::KEEPASS_XML_FOOTER),
+        OutputFormat::BitwardenJson => Some(export::BITWARDEN_FOOTER),
+        _ => None,
+    }
+}
+
+/// The CSV header row, with a score column for `with_score`, a hash column
+/// for `with_hash` and a label column for `with_label`
+fn csv_header(with_score: bool, with_hash: bool, with_label: bool) -> String {
+    let mut header = if with_label { "index,label," } else { "index," }.to_string();
+    header.push_str("password,length,entropy_bits");
+    if with_score {
+        header.push_str(",score");
+    }
+    if with_hash {
+        header.push_str(",hash");
+    }
+    header
+}
+
+/// One line of [`format_passwords`] output for the entry at (1-based) `index`
+pub fn format_record(format: OutputFormat, index: usize, entry: &Generated, with_score: bool) -> Secret {
+    match format {
+        OutputFormat::Text
+        | OutputFormat::Htpasswd
+        | OutputFormat::Env
+        | OutputFormat::K8sSecret
+        | OutputFormat::Tfvars
+        | OutputFormat::Hcl => text_record(entry, Escape::None, false),
+        OutputFormat::Json => {
+            let strength = with_score.then(|| strength::estimate(&entry.password));
+            Secret::new(json_record(
+                entry.label.as_deref(),
+                &entry.password,
+                entry.charset_size,
+                entry.entropy_bits,
+                strength.as_ref(),
+                entry.hash.as_deref(),
+            ))
+        }
+        OutputFormat::Csv => {
+            let strength = with_score.then(|| strength::estimate(&entry.password));
+            let label = entry.label.as_deref();
+            csv_record(index, label, &entry.password, entry.entropy_bits, strength.as_ref(), entry.hash.as_deref())
+        }
+        OutputFormat::KeepassCsv => export::keepass_csv_record(entry),
+        OutputFormat::KeepassXml => export::keepass_xml_record(entry),
+        OutputFormat::BitwardenJson => export::bitwarden_record(index, entry),
+        OutputFormat::OnepasswordCsv => export::onepassword_csv_record(entry),
+    }
+}
+
+/// One line of text output: the password escaped for `escape` and
+/// highlighted with `color`, after its label and followed by its hash,
+/// tab-separated
+pub fn text_record(entry: &Generated, escape: Escape, color: bool) -> Secret {
+    let password = self::escape(escape, &entry.password);
+    let password = if color { highlight(&password) } else { password };
+    if entry.label.is_none() && entry.hash.is_none() {
+        return password;
+    }
+
+    let mut line = Secret::new(String::with_capacity(password.len() + 64));
+    if let Some(label) = &entry.label {
+        line.push_str(label);
+        line.push('\t');
+    }
+    line.push_str(&password);
+    if let Some(hash) = &entry.hash {
+        line.push('\t');
+        line.push_str(hash);
+    }
+    line
+}
+
+/// One `user:hash` line per password, ready to append to an htpasswd file
+pub fn htpasswd_lines(user: &str, entries: &[Generated]) -> String {
+    entries
+        .iter()
+        .filter_map(|entry| entry.hash.as_ref())
+        .map(|hash| match hash.strip_prefix("$2b$") {
+            // Same algorithm, but older APR releases only recognise the prefix htpasswd writes
+            Some(rest) => format!("{}:$2y${}", user, rest),
+            None => format!("{}:{}", user, hash),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Quote `value` for a POSIX shell: in single quotes, with each `'` written as `'\''`
+pub fn shell_quote(value: &str) -> Secret {
+    let mut quoted = Secret::new(String::with_capacity(value.len() + 2));
+    quoted.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => quoted.push_str("'\\''"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// An `export NAME='password'` line
+pub fn env_line(name: &str, password: &str) -> Secret {
+    let quoted = shell_quote(password);
+    let mut line = Secret::new(String::with_capacity(name.len() + quoted.len() + 8));
+    line.push_str("export ");
+    line.push_str(name);
+    line.push('=');
+    line.push_str(&quoted);
+    line
+}
+
+/// Quote `value` as an HCL string, escaping `\`, `"`, control characters and
+/// the `${` and `%{` that would otherwise start a template
+pub fn hcl_quote(value: &str) -> Secret {
+    let mut quoted = Secret::new(String::with_capacity(value.len() + 2));
+    quoted.push('"');
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '$' | '%' if chars.peek() == Some(&'{') => {
+                quoted.push(c);
+                quoted.push(c);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A `name = "password"` line for a `.tfvars` file
+pub fn tfvars_line(name: &str, password: &str) -> Secret {
+    let quoted = hcl_quote(password);
+    let mut line = Secret::new(String::with_capacity(name.len() + quoted.len() + 3));
+    line.push_str(name);
+    line.push_str(" = ");
+    line.push_str(&quoted);
+    line
+}
+
+/// A sensitive Terraform `variable` block defaulting to the password
+pub fn hcl_variable(name: &str, password: &str) -> Secret {
+    let quoted = hcl_quote(password);
+    let mut block = Secret::new(String::with_capacity(name.len() + quoted.len() + 80));
+    block.push_str("variable \"");
+    block.push_str(name);
+    block.push_str("\" {\n  type      = string\n  sensitive = true\n  default   = ");
+    block.push_str(&quoted);
+    block.push_str("\n}");
+    block
+}
+
+/// Start of a `--format k8s-secret` manifest for the Secret `name`
+pub fn k8s_secret_header(name: &str) -> String {
+    format!("apiVersion: v1\nkind: Secret\nmetadata:\n  name: {}\ntype: Opaque\ndata:", name)
+}
+
+/// One `data` entry of a `--format k8s-secret` manifest
+pub fn k8s_secret_line(key: &str, password: &str) -> Secret {
+    let encoded = Secret::new(password::token::encode_base64(password.as_bytes()));
+    let mut line = Secret::new(String::with_capacity(key.len() + encoded.len() + 4));
+    line.push_str("  ");
+    line.push_str(key);
+    line.push_str(": ");
+    line.push_str(&encoded);
+    line
+}
+
+/// `name` for the password at (1-based) `index`, suffixed with the index
+/// when the batch holds more than one
+pub fn numbered_name(name: &str, index: usize, numbered: bool) -> String {
+    if numbered {
+        format!("{}_{}", name, index)
+    } else {
+        name.to_string()
+    }
+}
+
+/// The record binding `password` to `name` in a format that names its
+/// secrets: a variable (env, tfvars, hcl) or a Secret key (k8s-secret)
+pub fn named_record(format: OutputFormat, name: &str, password: &str) -> Secret {
+    match format {
+        OutputFormat::Env => env_line(name, password),
+        OutputFormat::Tfvars => tfvars_line(name, password),
+        OutputFormat::Hcl => hcl_variable(name, password),
+        OutputFormat::K8sSecret => k8s_secret_line(name, password),
+        _ => unreachable!("{:?} doesn't name its records", format),
+    }
+}
+
+/// One [`named_record`] per password. When there is more than one, the names
+/// are numbered from `NAME_1`.
+pub fn named_records(format: OutputFormat, name: &str, entries: &[Generated]) -> Secret {
+    let records: Vec<Secret> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| named_record(format, &numbered_name(name, i + 1, entries.len() > 1), &entry.password))
+        .collect();
+    join_lines(&records.iter().map(|record| record.as_str()).collect::<Vec<_>>())
+}
+
+/// A Secret manifest holding every password, under keys numbered from
+/// `KEY_1` when there is more than one
+pub fn k8s_secret(name: &str, key: &str, entries: &[Generated]) -> Secret {
+    let header = k8s_secret_header(name);
+    let data = named_records(OutputFormat::K8sSecret, key, entries);
+    join_lines(&[&header, &data])
+}
+
+/// Join secrets one per line into a single buffer sized up front, so no
+/// partial copies are left behind by reallocation
+pub fn join_lines(lines: &[&str]) -> Secret {
+    let capacity = lines.iter().map(|line| line.len() + 1).sum();
+    let mut joined = Secret::new(String::with_capacity(capacity));
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            joined.push('\n');
+        }
+        joined.push_str(line);
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A password without hash, account or label, for tests to override
+    fn generated(password: &str) -> Generated {
+        Generated {
+            password: Secret::new(password.to_string()),
+            charset_size: None,
+            entropy_bits: 1.0,
+            hash: None,
+            account: Account::default(),
+            label: None,
+            mnemonic: None,
+        }
+    }
+
+    #[test]
+    fn test_json_record() {
+        let record: serde_json::Value =
+            serde_json::from_str(&json_record(None, "a\"b\\c d\n", Some(94), 26.21928, None, None)).unwrap();
+        assert_eq!(record["password"], "a\"b\\c d\n");
+        assert_eq!(record["length"], 8);
+        assert_eq!(record["charset_size"], 94);
+        assert_eq!(record["entropy_bits"], 26.22);
+    }
+
+    #[test]
+    fn test_length_counts_graphemes() {
+        let record: serde_json::Value =
+            serde_json::from_str(&json_record(None, "🐙a🚀e\u{301}", None, 1.0, None, None)).unwrap();
+        assert_eq!(record["length"], 4);
+        assert_eq!(*csv_record(1, None, "🐙ж", 1.0, None, None), "1,🐙ж,2,1.00");
+    }
+
+    #[test]
+    fn test_json_record_without_charset() {
+        let record = json_record(None, "bakomi", None, 12.0, None, None);
+        assert_eq!(
+            record,
+            r#"{"password":"bakomi","length":6,"charset_size":null,"entropy_bits":12.0}"#
+        );
+    }
+
+    #[test]
+    fn test_json_record_with_score() {
+        let strength = Strength { score: 1, guesses_log10: 4.5678, warning: None };
+        let record: serde_json::Value =
+            serde_json::from_str(&json_record(None, "qwerty12", Some(88), 51.67, Some(&strength), None)).unwrap();
+        assert_eq!(record["score"], 1);
+        assert_eq!(record["guesses_log10"], 4.57);
+    }
+
+    #[test]
+    fn test_score_line() {
+        let strength = Strength {
+            score: 0,
+            guesses_log10: 2.0,
+            warning: Some("This is a top-10 common password.".to_string()),
+        };
+        assert_eq!(
+            score_line(&strength),
+            "Score: 0/4 (~10^2.00 guesses) - This is a top-10 common password."
+        );
+    }
+
+    #[test]
+    fn test_analysis_report() {
+        let analysis = password::analyze::analyze("qwerty1234", password::keyboard::Layout::Qwerty);
+        let checks = BreachChecks { blocklisted: Some(true), pwned: None };
+
+        let lines = analysis_lines(&analysis, checks);
+        assert_eq!(lines[0], "Length: 10");
+        assert_eq!(lines[1], "Classes: lowercase, digits (pool of 36)");
+        assert!(lines.contains(&"Findings:".to_string()));
+        assert!(lines.contains(&"  - Shorter than 12 characters".to_string()));
+        assert_eq!(lines.last().unwrap(), "Blocklist: listed");
+        assert!(!lines.iter().any(|line| line.contains("qwerty1234")));
+
+        let json: serde_json::Value = serde_json::from_str(&analysis_json(&analysis, checks)).unwrap();
+        assert_eq!(json["length"], 10);
+        assert_eq!(json["pool_size"], 36);
+        assert_eq!(json["blocklisted"], true);
+        assert!(json.get("pwned").is_none());
+        assert!(json.get("password").is_none());
+    }
+
+    #[test]
+    fn test_crack_time_line() {
+        assert_eq!(crack_time_line(1.0, 10.0, "online"), "Crack time: less than a second at 10 guesses/s (online)");
+        assert_eq!(
+            crack_time_line(80.0, 1e12, "md5"),
+            "Crack time: 19 thousand years at 1e12 guesses/s (md5)"
+        );
+    }
+
+    #[test]
+    fn test_csv_record() {
+        assert_eq!(*csv_record(1, None, "abc", 12.3456, None, None), "1,abc,3,12.35");
+        assert_eq!(*csv_record(2, None, "a,b\"c", 1.0, None, None), "2,\"a,b\"\"c\",5,1.00");
+        assert_eq!(*csv_record(3, None, " ab", 1.0, None, None), "3,\" ab\",3,1.00");
+
+        let strength = Strength { score: 4, guesses_log10: 12.0, warning: None };
+        assert_eq!(*csv_record(4, None, "abc", 1.0, Some(&strength), None), "4,abc,3,1.00,4");
+        assert_eq!(*csv_record(5, None, "abc", 1.0, None, Some("$argon2id$v=19$x")), "5,abc,3,1.00,$argon2id$v=19$x");
+        assert_eq!(*csv_record(6, Some("ops, db"), "abc", 1.0, None, None), "6,\"ops, db\",abc,3,1.00");
+    }
+
+    #[test]
+    fn test_format_passwords() {
+        let entries: Vec<Generated> = ["one", "two"]
+            .iter()
+            .map(|password| Generated { charset_size: Some(3), ..generated(password) })
+            .collect();
+        assert_eq!(*format_passwords(OutputFormat::Text, &entries, false), "one\ntwo");
+
+        let json = format_passwords(OutputFormat::Json, &entries, false);
+        assert_eq!(json.lines().count(), 2);
+        assert!(json.lines().all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok()));
+
+        let csv = format_passwords(OutputFormat::Csv, &entries, false);
+        assert_eq!(*csv, "index,password,length,entropy_bits\n1,one,3,1.00\n2,two,3,1.00");
+
+        let xml = format_passwords(OutputFormat::KeepassXml, &entries, false);
+        assert!(xml.starts_with(export::KEEPASS_XML_HEADER) && xml.ends_with(export::KEEPASS_XML_FOOTER));
+        assert_eq!(xml.matches("<Entry>").count(), 2);
+
+        let bitwarden: serde_json::Value =
+            serde_json::from_str(&format_passwords(OutputFormat::BitwardenJson, &entries, false)).unwrap();
+        assert_eq!(bitwarden["items"][1]["login"]["password"], "two");
+    }
+
+    #[test]
+    fn test_env_lines() {
+        assert_eq!(*shell_quote("a'b c$"), "'a'\\''b c$'");
+        assert_eq!(*env_line("DB_PASSWORD", "it's"), "export DB_PASSWORD='it'\\''s'");
+
+        let entries: Vec<Generated> = ["one", "two"]
+            .iter()
+            .map(|password| Generated { charset_size: Some(3), ..generated(password) })
+            .collect();
+        assert_eq!(*named_records(OutputFormat::Env, "KEY", &entries[..1]), "export KEY='one'");
+        assert_eq!(*named_records(OutputFormat::Env, "KEY", &entries), "export KEY_1='one'\nexport KEY_2='two'");
+    }
+
+    #[test]
+    fn test_terraform_records() {
+        assert_eq!(*hcl_quote(r#"a"b\c${x}%{y}$z%"#), r#""a\"b\\c$${x}%%{y}$z%""#);
+        assert_eq!(*named_record(OutputFormat::Tfvars, "db_password", "p\"w"), r#"db_password = "p\"w""#);
+        assert_eq!(
+            *named_record(OutputFormat::Hcl, "db_password", "pw"),
+            "variable \"db_password\" {\n  type      = string\n  sensitive = true\n  default   = \"pw\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_k8s_secret() {
+        let entries: Vec<Generated> = ["hunter2", "two"]
+            .into_iter()
+            .map(generated)
+            .collect();
+        assert_eq!(
+            *k8s_secret("db-credentials", "password", &entries[..1]),
+            "apiVersion: v1\nkind: Secret\nmetadata:\n  name: db-credentials\ntype: Opaque\ndata:\n  password: aHVudGVyMg=="
+        );
+        assert!(k8s_secret("db", "password", &entries).ends_with("  password_1: aHVudGVyMg==\n  password_2: dHdv"));
+    }
+
+    #[test]
+    fn test_htpasswd_lines() {
+        let entries: Vec<Generated> = ["$apr1$a$x", "$2b$12$y"]
+            .iter()
+            .map(|hash| Generated { hash: Some(hash.to_string()), ..generated("pw") })
+            .collect();
+        assert_eq!(htpasswd_lines("alice", &entries), "alice:$apr1$a$x\nalice:$2y$12$y");
+    }
+
+    #[test]
+    fn test_format_passwords_with_hash() {
+        let entries = vec![Generated {
+            charset_size: Some(3),
+            hash: Some("$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA".to_string()),
+            ..generated("one")
+        }];
+        assert_eq!(
+            *format_passwords(OutputFormat::Text, &entries, false),
+            "one\t$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_passwords(OutputFormat::Json, &entries, false)).unwrap();
+        assert_eq!(json["hash"], "$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA");
+
+        let csv = format_passwords(OutputFormat::Csv, &entries, true);
+        assert!(csv.starts_with("index,password,length,entropy_bits,score,hash\n1,one,3,1.00,"));
+        assert!(csv.ends_with(",\"$argon2id$v=19$m=19456,t=2,p=1$c2FsdA$aGFzaA\""));
+    }
+
+    #[test]
+    fn test_escape() {
+        let password = "a$b\"c\\d'e f";
+        assert_eq!(*escape(Escape::None, password), password);
+        assert_eq!(*escape(Escape::Shell, password), "'a$b\"c\\d'\\''e f'");
+        assert_eq!(*escape(Escape::Json, password), "\"a$b\\\"c\\\\d'e f\"");
+        assert_eq!(*escape(Escape::Url, password), "a%24b%22c%5Cd%27e%20f");
+        assert_eq!(*escape(Escape::Url, "ü-._~"), "%C3%BC-._~");
+
+        let entry = Generated { hash: Some("$2b$12$abc".to_string()), ..generated("p&q") };
+        assert_eq!(*text_record(&entry, Escape::Url, false), "p%26q\t$2b$12$abc");
+    }
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(*highlight("ab1lI!"), "ab\x1b[34m1\x1b[0ml\x1b[32mI\x1b[31m!\x1b[0m");
+        assert_eq!(*highlight("12"), "\x1b[34m12\x1b[0m");
+        assert_eq!(*highlight("horse staple"), "horse staple");
+
+        // Only the password is highlighted, not its hash
+        let entry = Generated { hash: Some("$2b$12$abc".to_string()), ..generated("aB") };
+        assert_eq!(*text_record(&entry, Escape::None, true), "a\x1b[32mB\x1b[0m\t$2b$12$abc");
+    }
+}
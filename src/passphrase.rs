@@ -0,0 +1,616 @@
+//! Diceware-style passphrases drawn from the EFF large wordlist, a bundled
+//! wordlist in another language or a user-supplied list.
+
+use once_cell::sync::Lazy;
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+use crate::inject::{InjectPosition, Injection};
+use crate::{leet, PasswordError, Secret};
+
+/// Separator placed between words of a generated passphrase
+pub const WORD_SEPARATOR: &str = " ";
+
+/// How the words of a passphrase are capitalised
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WordCase {
+    /// `correct horse`
+    #[default]
+    Lower,
+    /// `CORRECT HORSE`
+    Upper,
+    /// `Correct Horse`
+    Title,
+    /// Each word lower, upper or title case, chosen by the RNG
+    Random,
+}
+
+impl WordCase {
+    /// Every case a word can be written in
+    const FIXED: [WordCase; 3] = [WordCase::Lower, WordCase::Upper, WordCase::Title];
+
+    fn apply(self, word: &str, out: &mut String) {
+        match self {
+            WordCase::Lower | WordCase::Random => out.extend(word.chars().flat_map(char::to_lowercase)),
+            WordCase::Upper => out.extend(word.chars().flat_map(char::to_uppercase)),
+            WordCase::Title => {
+                let mut chars = word.chars();
+                if let Some(first) = chars.next() {
+                    out.extend(first.to_uppercase());
+                }
+                out.extend(chars.flat_map(char::to_lowercase));
+            }
+        }
+    }
+}
+
+impl FromStr for WordCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lower" => Ok(WordCase::Lower),
+            "upper" => Ok(WordCase::Upper),
+            "title" => Ok(WordCase::Title),
+            "random" => Ok(WordCase::Random),
+            _ => Err(format!("Unknown word case '{}' (expected lower, upper, title or random)", s)),
+        }
+    }
+}
+
+impl fmt::Display for WordCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordCase::Lower => write!(f, "lower"),
+            WordCase::Upper => write!(f, "upper"),
+            WordCase::Title => write!(f, "title"),
+            WordCase::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// How the words of a passphrase are joined and capitalised
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassphraseStyle {
+    pub separator: String,
+    pub case: WordCase,
+}
+
+impl Default for PassphraseStyle {
+    fn default() -> Self {
+        PassphraseStyle { separator: WORD_SEPARATOR.to_string(), case: WordCase::Lower }
+    }
+}
+
+/// Language of a built-in wordlist
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Language {
+    /// The EFF large wordlist
+    #[default]
+    English,
+    German,
+    /// The BIP39 French list
+    French,
+    /// The BIP39 Spanish list
+    Spanish,
+    /// The BIP39 Italian list
+    Italian,
+    /// The BIP39 Portuguese list
+    Portuguese,
+}
+
+impl Language {
+    /// ISO 639-1 codes of every built-in wordlist
+    pub const CODES: [&'static str; 6] = ["en", "de", "fr", "es", "it", "pt"];
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" => Ok(Language::English),
+            "de" => Ok(Language::German),
+            "fr" => Ok(Language::French),
+            "es" => Ok(Language::Spanish),
+            "it" => Ok(Language::Italian),
+            "pt" => Ok(Language::Portuguese),
+            _ => Err(format!(
+                "Unknown wordlist language '{}' (expected {})", s, Language::CODES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Language::English => "en",
+            Language::German => "de",
+            Language::French => "fr",
+            Language::Spanish => "es",
+            Language::Italian => "it",
+            Language::Portuguese => "pt",
+        })
+    }
+}
+
+/// Smallest user-supplied wordlist accepted (~10 bits per word)
+pub const MIN_WORDLIST_SIZE: usize = 1024;
+
+static EFF_LARGE_WORDLIST: Lazy<Vec<&'static str>> = Lazy::new(|| {
+    // EFF large wordlist (7776 words), one `<dice roll>\t<word>` entry per line
+    include_str!("wordlists/eff_large.txt")
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .collect()
+});
+
+/// A validated list of unique words to draw passphrases from
+#[derive(Debug)]
+pub struct Wordlist {
+    words: Vec<String>,
+}
+
+impl Wordlist {
+    /// The embedded EFF large wordlist
+    pub fn eff_large() -> Self {
+        Wordlist {
+            words: EFF_LARGE_WORDLIST.iter().map(|w| w.to_string()).collect(),
+        }
+    }
+
+    /// The embedded wordlist for `language`. The non-English lists hold 2048
+    /// words each (11 bits per word), one word per line.
+    pub fn builtin(language: Language) -> Self {
+        let contents = match language {
+            Language::English => return Self::eff_large(),
+            Language::German => include_str!("wordlists/de.txt"),
+            Language::French => include_str!("wordlists/fr.txt"),
+            Language::Spanish => include_str!("wordlists/es.txt"),
+            Language::Italian => include_str!("wordlists/it.txt"),
+            Language::Portuguese => include_str!("wordlists/pt.txt"),
+        };
+        Wordlist {
+            words: contents.lines().map(|word| word.to_string()).collect(),
+        }
+    }
+
+    /// Load a newline-delimited wordlist from disk.
+    ///
+    /// Blank lines are skipped and a leading dice-roll column (as used by the
+    /// EFF lists) is ignored, so `11111\tabacus` and `abacus` are equivalent.
+    /// Any other line with more than one field, like `ice cream`, is an error.
+    pub fn from_file(path: &Path) -> Result<Self, PasswordError> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            PasswordError::WordlistRead(format!("{}: {}", path.display(), err))
+        })?;
+
+        Self::parse(&contents)
+    }
+
+    /// Parse a newline-delimited wordlist, checking its size and that every word is unique
+    pub fn parse(contents: &str) -> Result<Self, PasswordError> {
+        let mut words = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let word = match fields[..] {
+                [] => continue,
+                [word] => word,
+                [roll, word] if roll.bytes().all(|b| b.is_ascii_digit()) => word,
+                _ => return Err(PasswordError::WordlistRead(format!(
+                    "line {}: expected one word, optionally after a dice roll like 11111. Got: {:?}",
+                    index + 1,
+                    line.trim()
+                ))),
+            };
+            words.push(word.to_string());
+        }
+
+        let mut seen = HashSet::with_capacity(words.len());
+        if let Some(duplicate) = words.iter().find(|word| !seen.insert(word.as_str())) {
+            return Err(PasswordError::DuplicateWord(duplicate.clone()));
+        }
+
+        if words.len() < MIN_WORDLIST_SIZE {
+            return Err(PasswordError::WordlistTooSmall(words.len()));
+        }
+
+        Ok(Wordlist { words })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub(crate) fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Whether any word contains `c`
+    pub fn uses_char(&self, c: char) -> bool {
+        self.words.iter().any(|word| word.contains(c))
+    }
+
+    /// Length in characters of the shortest word
+    pub fn shortest_word(&self) -> usize {
+        self.words.iter().map(|word| word.chars().count()).min().unwrap_or(0)
+    }
+
+    /// Check that no two words are written the same way in `case` (random
+    /// case counts as lower, since every other form follows from it), so
+    /// the entropy counted over the raw list holds for the passphrase
+    pub fn check_case(&self, case: WordCase) -> Result<(), PasswordError> {
+        let mut seen = HashMap::with_capacity(self.words.len());
+        for word in &self.words {
+            let mut form = String::with_capacity(word.len());
+            case.apply(word, &mut form);
+            if let Some(first) = seen.insert(form, word) {
+                return Err(PasswordError::WordCaseCollision(first.clone(), word.clone(), case));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bits of entropy contributed by each word drawn from this list
+    pub fn entropy_per_word(&self) -> f64 {
+        (self.words.len() as f64).log2()
+    }
+
+    /// Average bits added to each word by leetspeak substitutions. Lists that
+    /// already contain substitute characters get no credit, since a
+    /// substituted word could then be mistaken for another.
+    pub fn leet_entropy_per_word(&self) -> f64 {
+        if self.words.iter().any(|word| word.chars().any(leet::is_substitute)) {
+            return 0.0;
+        }
+        self.words.iter().map(|word| leet::leet_entropy(word)).sum::<f64>() / self.words.len() as f64
+    }
+
+    /// Average bits added to each word by `case`. Only random casing adds
+    /// any, and only for words whose cased forms differ (`a` has two, not three).
+    pub fn case_entropy_per_word(&self, case: WordCase) -> f64 {
+        if case != WordCase::Random {
+            return 0.0;
+        }
+        let variants = |word: &str| {
+            let forms: HashSet<String> = WordCase::FIXED
+                .iter()
+                .map(|case| {
+                    let mut form = String::new();
+                    case.apply(word, &mut form);
+                    form
+                })
+                .collect();
+            (forms.len() as f64).log2()
+        };
+        self.words.iter().map(|word| variants(word)).sum::<f64>() / self.words.len() as f64
+    }
+}
+
+/// Most words in a passphrase
+pub const MAX_WORD_COUNT: u16 = 64;
+
+/// Parse a passphrase word count (between 1 and [`MAX_WORD_COUNT`])
+pub fn validate_word_count(s: &str) -> Result<u16, String> {
+    let count: u16 = s.parse().map_err(|_| format!(
+        "The word count must be a positive number between 1 and {}. Got: {}", MAX_WORD_COUNT, s
+    ))?;
+
+    if !(1..=MAX_WORD_COUNT).contains(&count) {
+        return Err(PasswordError::WordCountOutOfRange(count).to_string());
+    }
+
+    Ok(count)
+}
+
+/// Draw `word_count` words uniformly from `wordlist`, joined by [`WORD_SEPARATOR`]
+pub fn generate_passphrase<R: Rng + ?Sized>(
+    rng: &mut R,
+    word_count: u16,
+    wordlist: &Wordlist
+) -> Result<Secret, PasswordError> {
+    generate_styled_passphrase(rng, word_count, wordlist, &PassphraseStyle::default())
+}
+
+/// Draw `word_count` words uniformly from `wordlist`, capitalised and joined
+/// as `style` says
+pub fn generate_styled_passphrase<R: Rng + ?Sized>(
+    rng: &mut R,
+    word_count: u16,
+    wordlist: &Wordlist,
+    style: &PassphraseStyle,
+) -> Result<Secret, PasswordError> {
+    styled_words(rng, word_count, wordlist, style, &mut Vec::new())
+}
+
+/// Draw a styled passphrase and insert the digits and symbols of `injection`
+/// at word boundaries or anywhere, as it says
+pub fn generate_injected_passphrase<R: Rng + ?Sized>(
+    rng: &mut R,
+    word_count: u16,
+    wordlist: &Wordlist,
+    style: &PassphraseStyle,
+    injection: &Injection,
+) -> Result<Secret, PasswordError> {
+    let mut edges = Zeroizing::new(Vec::with_capacity(word_count as usize * 2));
+    let passphrase = styled_words(rng, word_count, wordlist, style, &mut edges)?;
+
+    let slots = match injection.position {
+        InjectPosition::Boundary => {
+            edges.dedup();
+            edges
+        }
+        InjectPosition::Anywhere => Zeroizing::new((0..=passphrase.chars().count()).collect()),
+    };
+    Ok(injection.apply(rng, &passphrase, &slots))
+}
+
+/// The fewest insertion points a passphrase of `word_count` words offers at
+/// `position`: the start and end of every word (shared when there is no
+/// separator), or every gap between characters of the shortest passphrase
+pub fn injection_slots(word_count: u16, wordlist: &Wordlist, style: &PassphraseStyle, position: InjectPosition) -> usize {
+    let words = word_count as usize;
+    match position {
+        InjectPosition::Boundary if style.separator.is_empty() => words + 1,
+        InjectPosition::Boundary => words * 2,
+        InjectPosition::Anywhere => {
+            words * wordlist.shortest_word() + words.saturating_sub(1) * style.separator.chars().count() + 1
+        }
+    }
+}
+
+/// Draw a styled passphrase, recording the char offset of the start and end
+/// of every word in `edges`
+fn styled_words<R: Rng + ?Sized>(
+    rng: &mut R,
+    word_count: u16,
+    wordlist: &Wordlist,
+    style: &PassphraseStyle,
+    edges: &mut Vec<usize>,
+) -> Result<Secret, PasswordError> {
+    if wordlist.is_empty() {
+        return Err(PasswordError::EmptyWordlist);
+    }
+
+    let words = &wordlist.words;
+    let word_distribution = Uniform::from(0..words.len());
+    let mut passphrase = Secret::new(String::new());
+    let mut length = 0;
+
+    for i in 0..word_count {
+        if i > 0 {
+            passphrase.push_str(&style.separator);
+            length += style.separator.chars().count();
+        }
+        edges.push(length);
+        let start = passphrase.len();
+        let word = &words[word_distribution.sample(rng)];
+        let case = match style.case {
+            WordCase::Random => WordCase::FIXED[rng.gen_range(0..WordCase::FIXED.len())],
+            case => case,
+        };
+        case.apply(word, &mut passphrase);
+        length += passphrase[start..].chars().count();
+        edges.push(length);
+    }
+
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    fn numbered_words(count: usize) -> String {
+        (0..count).map(|i| format!("word{}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_wordlist_loaded() {
+        assert_eq!(EFF_LARGE_WORDLIST.len(), 7776);
+        assert_eq!(EFF_LARGE_WORDLIST[0], "abacus");
+        assert_eq!(EFF_LARGE_WORDLIST[7775], "zoom");
+    }
+
+    #[test]
+    fn test_builtin_wordlists() {
+        assert_eq!(Wordlist::builtin(Language::English).len(), 7776);
+        for code in Language::CODES.iter().skip(1) {
+            let wordlist = Wordlist::builtin(code.parse().unwrap());
+            assert_eq!(wordlist.len(), 2048, "{}", code);
+
+            // Every list is unique and usable as a custom wordlist
+            let contents: String = wordlist.words.iter().map(|word| format!("{}\n", word)).collect();
+            assert_eq!(Wordlist::parse(&contents).unwrap().len(), 2048);
+            assert!(wordlist.words.iter().all(|word| word.chars().all(char::is_lowercase)), "{}", code);
+            for case in WordCase::FIXED {
+                assert!(wordlist.check_case(case).is_ok(), "{} {}", code, case);
+            }
+        }
+
+        let passphrase = generate_passphrase(&mut get_secure_rng().unwrap(), 4, &Wordlist::builtin(Language::French)).unwrap();
+        assert_eq!(passphrase.split(WORD_SEPARATOR).count(), 4);
+    }
+
+    #[test]
+    fn test_language_codes() {
+        assert_eq!("DE".parse::<Language>(), Ok(Language::German));
+        assert_eq!(Language::Portuguese.to_string(), "pt");
+        assert!("nl".parse::<Language>().is_err());
+    }
+
+    #[test]
+    fn test_passphrase_word_count() {
+        let wordlist = Wordlist::eff_large();
+        let passphrase = generate_passphrase(&mut get_secure_rng().unwrap(), 6, &wordlist).unwrap();
+        let words: Vec<&str> = passphrase.split(WORD_SEPARATOR).collect();
+        assert_eq!(words.len(), 6);
+        assert!(words.iter().all(|w| EFF_LARGE_WORDLIST.contains(w)));
+    }
+
+    #[test]
+    fn test_validate_word_count_input() {
+        assert!(validate_word_count("1").is_ok());
+        assert!(validate_word_count("64").is_ok());
+        assert!(validate_word_count("0").is_err());
+        assert!(validate_word_count("65").is_err());
+        assert!(validate_word_count("six").is_err());
+    }
+
+    #[test]
+    fn test_custom_wordlist() {
+        let wordlist = Wordlist::parse(&numbered_words(2048)).unwrap();
+        assert_eq!(wordlist.len(), 2048);
+        assert_eq!(wordlist.entropy_per_word(), 11.0);
+
+        let passphrase = generate_passphrase(&mut get_secure_rng().unwrap(), 4, &wordlist).unwrap();
+        assert!(passphrase.split(WORD_SEPARATOR).all(|w| w.starts_with("word")));
+    }
+
+    #[test]
+    fn test_wordlist_dice_prefix_and_blank_lines() {
+        let contents: String = (0..MIN_WORDLIST_SIZE)
+            .map(|i| format!("{}\tword{}\n\n", i, i))
+            .collect();
+        let wordlist = Wordlist::parse(&contents).unwrap();
+        assert_eq!(wordlist.len(), MIN_WORDLIST_SIZE);
+    }
+
+    #[test]
+    fn test_wordlist_malformed_lines() {
+        for line in ["ice cream", "11111 ice cream", "1a111\tabacus"] {
+            let contents = format!("{}{}\n", numbered_words(2048), line);
+            assert!(matches!(
+                Wordlist::parse(&contents),
+                Err(PasswordError::WordlistRead(reason)) if reason.starts_with("line 2049:")
+            ), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_wordlist_too_small() {
+        assert!(matches!(
+            Wordlist::parse(&numbered_words(MIN_WORDLIST_SIZE - 1)),
+            Err(PasswordError::WordlistTooSmall(1023))
+        ));
+    }
+
+    #[test]
+    fn test_wordlist_duplicates() {
+        let contents = numbered_words(2048) + "word7\n";
+        assert!(matches!(
+            Wordlist::parse(&contents),
+            Err(PasswordError::DuplicateWord(word)) if word == "word7"
+        ));
+    }
+
+    #[test]
+    fn test_word_case_collision() {
+        // w0..w1023 and W0..W1023 are 2048 words in the file but only 1024 once cased
+        let contents: String = (0..1024).map(|i| format!("w{}\n", i)).chain((0..1024).map(|i| format!("W{}\n", i))).collect();
+        let wordlist = Wordlist::parse(&contents).unwrap();
+        assert_eq!(wordlist.len(), 2048);
+        for case in [WordCase::Lower, WordCase::Upper, WordCase::Title, WordCase::Random] {
+            assert!(matches!(
+                wordlist.check_case(case),
+                Err(PasswordError::WordCaseCollision(first, second, _)) if first == "w0" && second == "W0"
+            ), "{}", case);
+        }
+
+        assert!(Wordlist::parse(&numbered_words(2048)).unwrap().check_case(WordCase::Upper).is_ok());
+        assert!(Wordlist::eff_large().check_case(WordCase::Random).is_ok());
+    }
+
+    #[test]
+    fn test_leet_entropy_per_word() {
+        // Each word has one substitutable letter ('o', with one substitute)
+        let contents: String = (0..2048)
+            .map(|i| {
+                let letters: String = i.to_string().bytes().map(|d| b"bcdfghjklm"[(d - b'0') as usize] as char).collect();
+                format!("o{}\n", letters)
+            })
+            .collect();
+        assert_eq!(Wordlist::parse(&contents).unwrap().leet_entropy_per_word(), 1.0);
+        assert!(Wordlist::eff_large().leet_entropy_per_word() > 3.0);
+
+        // Digits in the list make substitutions ambiguous
+        let contents: String = (0..2048).map(|i| format!("w{}rd\n", i)).collect();
+        assert_eq!(Wordlist::parse(&contents).unwrap().leet_entropy_per_word(), 0.0);
+    }
+
+    #[test]
+    fn test_styled_passphrase() {
+        let mut rng = get_secure_rng().unwrap();
+        let wordlist = Wordlist::eff_large();
+
+        // No EFF word contains '.', unlike '-' (t-shirt, yo-yo)
+        assert!(!wordlist.uses_char('.'));
+        let style = PassphraseStyle { separator: ".".to_string(), case: WordCase::Title };
+        let passphrase = generate_styled_passphrase(&mut rng, 4, &wordlist, &style).unwrap();
+        assert!(!passphrase.contains(' '));
+        assert_eq!(passphrase.split('.').count(), 4);
+        for word in passphrase.split('.') {
+            assert!(word.starts_with(|c: char| c.is_uppercase()));
+        }
+
+        let style = PassphraseStyle { separator: String::new(), case: WordCase::Upper };
+        let passphrase = generate_styled_passphrase(&mut rng, 3, &wordlist, &style).unwrap();
+        assert!(!passphrase.chars().any(|c| c.is_lowercase()));
+    }
+
+    #[test]
+    fn test_random_word_case() {
+        let mut rng = get_secure_rng().unwrap();
+        let style = PassphraseStyle { case: WordCase::Random, ..PassphraseStyle::default() };
+        let passphrase = generate_styled_passphrase(&mut rng, 64, &Wordlist::eff_large(), &style).unwrap();
+
+        let words: Vec<&str> = passphrase.split(' ').collect();
+        assert!(words.iter().any(|w| w.chars().all(|c| !c.is_uppercase())));
+        assert!(words.iter().any(|w| w.chars().all(|c| !c.is_lowercase())));
+        assert!(words.iter().any(|w| w.starts_with(|c: char| c.is_uppercase()) && w.chars().skip(1).any(char::is_lowercase)));
+    }
+
+    #[test]
+    fn test_injected_passphrase() {
+        let mut rng = get_secure_rng().unwrap();
+        let wordlist = Wordlist::eff_large();
+        let injection = Injection::new(3, 0, InjectPosition::Boundary, &[]).unwrap();
+
+        for _ in 0..20 {
+            let passphrase = generate_injected_passphrase(&mut rng, 4, &wordlist, &PassphraseStyle::default(), &injection).unwrap();
+            assert_eq!(passphrase.chars().filter(char::is_ascii_digit).count(), 3);
+            // Digits only ever sit at the ends of words
+            for word in passphrase.split(' ') {
+                let letters = word.trim_matches(|c: char| c.is_ascii_digit());
+                assert!(EFF_LARGE_WORDLIST.contains(&letters), "{}", word);
+            }
+        }
+
+        let style = PassphraseStyle { separator: String::new(), case: WordCase::Lower };
+        assert_eq!(injection_slots(4, &wordlist, &style, InjectPosition::Boundary), 5);
+        assert_eq!(injection_slots(4, &wordlist, &PassphraseStyle::default(), InjectPosition::Boundary), 8);
+        assert_eq!(injection_slots(4, &wordlist, &PassphraseStyle::default(), InjectPosition::Anywhere), 4 * 3 + 3 + 1);
+    }
+
+    #[test]
+    fn test_case_entropy_per_word() {
+        let wordlist = Wordlist::eff_large();
+        assert_eq!(wordlist.case_entropy_per_word(WordCase::Title), 0.0);
+        // Every EFF word has at least three letters, so three distinct forms
+        assert!((wordlist.case_entropy_per_word(WordCase::Random) - 3f64.log2()).abs() < 1e-9);
+        assert_eq!("Random".parse::<WordCase>(), Ok(WordCase::Random));
+        assert!("camel".parse::<WordCase>().is_err());
+    }
+}
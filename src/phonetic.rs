@@ -0,0 +1,124 @@
+//! Character-by-character NATO phonetic renderings, for reading passwords
+//! aloud over the phone.
+//!
+//! Letters are spelled with the ICAO alphabet and their case, digits are
+//! written in capitals so they stand out from letters, and symbols by name.
+
+use crate::Secret;
+
+/// The ICAO spelling alphabet, `a` to `z`
+const ALPHABET: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India",
+    "Juliett", "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo",
+    "Sierra", "Tango", "Uniform", "Victor", "Whiskey", "X-ray", "Yankee", "Zulu",
+];
+
+const DIGITS: [&str; 10] = [
+    "ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE",
+];
+
+/// Spoken names of the symbols the generator can produce
+const SYMBOLS: &[(char, &str)] = &[
+    (' ', "space"),
+    ('!', "exclamation mark"),
+    ('"', "double quote"),
+    ('#', "hash"),
+    ('$', "dollar"),
+    ('%', "percent"),
+    ('&', "ampersand"),
+    ('\'', "single quote"),
+    ('(', "open parenthesis"),
+    (')', "close parenthesis"),
+    ('*', "asterisk"),
+    ('+', "plus"),
+    (',', "comma"),
+    ('-', "hyphen"),
+    ('.', "period"),
+    ('/', "slash"),
+    (':', "colon"),
+    (';', "semicolon"),
+    ('<', "less than"),
+    ('=', "equals"),
+    ('>', "greater than"),
+    ('?', "question mark"),
+    ('@', "at sign"),
+    ('[', "open bracket"),
+    ('\\', "backslash"),
+    (']', "close bracket"),
+    ('^', "caret"),
+    ('_', "underscore"),
+    ('`', "backtick"),
+    ('{', "open brace"),
+    ('|', "vertical bar"),
+    ('}', "close brace"),
+    ('~', "tilde"),
+];
+
+/// How to say `c` aloud, e.g. `Alfa lower`, `Bravo UPPER`, `SEVEN` or `dollar`
+pub fn spell_char(c: char) -> String {
+    if c.is_ascii_alphabetic() {
+        let word = ALPHABET[(c.to_ascii_lowercase() as u8 - b'a') as usize];
+        let case = if c.is_ascii_uppercase() { "UPPER" } else { "lower" };
+        return format!("{} {}", word, case);
+    }
+    if let Some(digit) = c.to_digit(10) {
+        return DIGITS[digit as usize].to_string();
+    }
+    if let Some((_, name)) = SYMBOLS.iter().find(|(symbol, _)| *symbol == c) {
+        return name.to_string();
+    }
+
+    // Accented letters from non-English wordlists and anything else
+    let code = format!("U+{:04X}", c as u32);
+    if c.is_lowercase() {
+        format!("{} lower ({})", c, code)
+    } else if c.is_uppercase() {
+        format!("{} UPPER ({})", c, code)
+    } else {
+        code
+    }
+}
+
+/// One numbered line per character of `text`
+pub fn spell(text: &str) -> Vec<Secret> {
+    let width = text.chars().count().to_string().len();
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| Secret::new(format!("{:>width$}  {}", i + 1, spell_char(c), width = width)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CHARS_SYMBOLS, CHARS_SYMBOLS_EXTENDED};
+
+    #[test]
+    fn test_spell_char() {
+        assert_eq!(spell_char('a'), "Alfa lower");
+        assert_eq!(spell_char('B'), "Bravo UPPER");
+        assert_eq!(spell_char('x'), "X-ray lower");
+        assert_eq!(spell_char('7'), "SEVEN");
+        assert_eq!(spell_char('$'), "dollar");
+        assert_eq!(spell_char('é'), "é lower (U+00E9)");
+        assert_eq!(spell_char('Ü'), "Ü UPPER (U+00DC)");
+        assert_eq!(spell_char('€'), "U+20AC");
+    }
+
+    #[test]
+    fn test_every_symbol_has_a_name() {
+        for &c in CHARS_SYMBOLS.iter().chain(CHARS_SYMBOLS_EXTENDED.iter()) {
+            assert!(!spell_char(c).starts_with("U+"), "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn test_spell() {
+        let lines: Vec<String> = spell("a7B$").iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, ["1  Alfa lower", "2  SEVEN", "3  Bravo UPPER", "4  dollar"]);
+        let lines = spell("abcdefghijkl");
+        assert_eq!(lines[0].as_str(), " 1  Alfa lower");
+        assert_eq!(lines[11].as_str(), "12  Lima lower");
+        assert!(spell("").is_empty());
+    }
+}
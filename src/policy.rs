@@ -0,0 +1,511 @@
+//! Composition rules a generated password must satisfy.
+//!
+//! Candidates are drawn uniformly from the whole character set and rejected
+//! until one satisfies the policy. Because nothing is forced into place, every
+//! password that meets the policy is equally likely and no position or class
+//! is favoured over another.
+
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::dictionary;
+use crate::keyboard::{has_keyboard_walk, WalkFilter};
+use crate::{generate_from_charset, generate_no_repeat_from_charset, generate_unique_from_charset, PasswordError, Secret};
+
+/// How many candidates are tried before giving up on a policy
+pub const MAX_ATTEMPTS: u32 = 100_000;
+
+/// A class of characters that a policy can place requirements on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CharClass {
+    Digit,
+    Upper,
+    Lower,
+    Symbol,
+}
+
+impl CharClass {
+    /// Names accepted by `--classes`
+    pub const NAMES: [&'static str; 4] = ["lower", "upper", "digit", "symbol"];
+
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            CharClass::Digit => c.is_numeric(),
+            CharClass::Upper => c.is_uppercase(),
+            CharClass::Lower => c.is_lowercase(),
+            CharClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClass::Digit => "digits",
+            CharClass::Upper => "uppercase letters",
+            CharClass::Lower => "lowercase letters",
+            CharClass::Symbol => "symbols",
+        }
+    }
+}
+
+impl FromStr for CharClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lower" => Ok(CharClass::Lower),
+            "upper" => Ok(CharClass::Upper),
+            "digit" => Ok(CharClass::Digit),
+            "symbol" => Ok(CharClass::Symbol),
+            _ => Err(format!(
+                "Unknown character class '{}' (expected {})", s, CharClass::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// What the first character of a password must be (`--start-with`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StartWith {
+    Letter,
+    Upper,
+    Lower,
+    Digit,
+    Alnum,
+}
+
+impl StartWith {
+    /// Names accepted by `--start-with`
+    pub const NAMES: [&'static str; 5] = ["letter", "upper", "lower", "digit", "alnum"];
+
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            StartWith::Letter => c.is_alphabetic(),
+            StartWith::Upper => c.is_uppercase(),
+            StartWith::Lower => c.is_lowercase(),
+            StartWith::Digit => c.is_numeric(),
+            StartWith::Alnum => c.is_alphanumeric(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StartWith::Letter => "a letter",
+            StartWith::Upper => "an uppercase letter",
+            StartWith::Lower => "a lowercase letter",
+            StartWith::Digit => "a digit",
+            StartWith::Alnum => "a letter or digit",
+        }
+    }
+}
+
+impl FromStr for StartWith {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "letter" => Ok(StartWith::Letter),
+            "upper" => Ok(StartWith::Upper),
+            "lower" => Ok(StartWith::Lower),
+            "digit" => Ok(StartWith::Digit),
+            "alnum" => Ok(StartWith::Alnum),
+            _ => Err(format!(
+                "Unknown first character class '{}' (expected {})", s, StartWith::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Minimum number of characters required from each class, and whether
+/// characters may repeat
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Policy {
+    pub min_digits: u16,
+    pub min_upper: u16,
+    pub min_lower: u16,
+    pub min_symbols: u16,
+    pub max_digits: Option<u16>,
+    pub max_upper: Option<u16>,
+    pub max_lower: Option<u16>,
+    pub max_symbols: Option<u16>,
+    /// Every character in the password must be distinct
+    pub unique: bool,
+    /// No character may follow itself (`aa`, `$$`)
+    pub no_adjacent_repeat: bool,
+    /// The class the first character must belong to
+    pub start_with: Option<StartWith>,
+    /// The first and last characters must be letters or digits
+    pub no_symbol_edges: bool,
+    /// Reject ascending or descending runs (`abc`, `321`) of this many characters
+    pub sequential_run: Option<u16>,
+    /// Reject walks across neighbouring keys (`qwerty`, `1qaz`)
+    pub keyboard_walk: Option<WalkFilter>,
+    /// Reject English words of this many letters or more (`tiger` in `x7#Tiger9q`)
+    pub dictionary_words: Option<u16>,
+}
+
+impl Policy {
+    fn minimums(&self) -> [(CharClass, u16); 4] {
+        [
+            (CharClass::Digit, self.min_digits),
+            (CharClass::Upper, self.min_upper),
+            (CharClass::Lower, self.min_lower),
+            (CharClass::Symbol, self.min_symbols),
+        ]
+    }
+
+    fn maximums(&self) -> [(CharClass, Option<u16>); 4] {
+        [
+            (CharClass::Digit, self.max_digits),
+            (CharClass::Upper, self.max_upper),
+            (CharClass::Lower, self.max_lower),
+            (CharClass::Symbol, self.max_symbols),
+        ]
+    }
+
+    /// Check that the policy can be met by a password of `length` characters
+    /// drawn from `charset`
+    pub fn validate(&self, length: u32, charset: &[char]) -> Result<(), PasswordError> {
+        let required: u32 = self.minimums().iter().map(|(_, min)| *min as u32).sum();
+        if required > length {
+            return Err(PasswordError::PolicyExceedsLength(required, length));
+        }
+
+        if self.unique && length as usize > charset.len() {
+            return Err(PasswordError::UniqueExceedsCharset(length, charset.len()));
+        }
+        if self.no_adjacent_repeat && length > 1 && charset.len() < 2 {
+            return Err(PasswordError::AdjacentRepeatUnavoidable);
+        }
+
+        for (class, min) in self.minimums() {
+            if min > 0 && !charset.iter().any(|c| class.contains(*c)) {
+                return Err(PasswordError::PolicyClassMissing(class.name()));
+            }
+        }
+
+        for ((class, min), (_, max)) in self.minimums().into_iter().zip(self.maximums()) {
+            if let Some(max) = max.filter(|max| *max < min) {
+                return Err(PasswordError::PolicyMaxBelowMin(class.name(), max, min));
+            }
+        }
+
+        if let Some(start) = self.start_with.filter(|start| !charset.iter().any(|c| start.contains(*c))) {
+            return Err(PasswordError::PolicyEdgeMissing(start.name()));
+        }
+        if self.no_symbol_edges && !charset.iter().any(|c| c.is_alphanumeric()) {
+            return Err(PasswordError::PolicyEdgeMissing(StartWith::Alnum.name()));
+        }
+
+        // Unless some character is uncapped, the caps bound the length
+        let capped = self.maximums().into_iter().filter_map(|(class, max)| Some((class, max?))).collect::<Vec<_>>();
+        let uncapped = charset.iter().any(|c| !capped.iter().any(|(class, _)| class.contains(*c)));
+        if !capped.is_empty() && !uncapped {
+            let room: u32 = capped.iter()
+                .filter(|(class, _)| charset.iter().any(|c| class.contains(*c)))
+                .map(|(_, max)| *max as u32)
+                .sum();
+            if room < length {
+                return Err(PasswordError::PolicyMaxBelowLength(room, length));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether `password` meets every requirement of the policy
+    pub fn is_satisfied(&self, password: &str) -> bool {
+        let minimums_met = self.minimums().iter().all(|(class, min)| {
+            password.chars().filter(|c| class.contains(*c)).count() >= *min as usize
+        });
+
+        let maximums_met = self.maximums().iter().all(|(class, max)| {
+            max.is_none_or(|max| password.chars().filter(|c| class.contains(*c)).count() <= max as usize)
+        });
+
+        let first = password.chars().next();
+        let last = password.chars().next_back();
+        let edges_met = self.start_with.is_none_or(|start| first.is_some_and(|c| start.contains(c)))
+            && (!self.no_symbol_edges || [first, last].iter().flatten().all(|c| c.is_alphanumeric()));
+
+        let repeats_met = !self.no_adjacent_repeat
+            || !password.chars().zip(password.chars().skip(1)).any(|(a, b)| a == b);
+
+        minimums_met
+            && maximums_met
+            && edges_met
+            && repeats_met
+            && !self.sequential_run.is_some_and(|run| has_sequential_run(password, run))
+            && !self.keyboard_walk.is_some_and(|walk| has_keyboard_walk(password, walk.layout, walk.run))
+            && !self.dictionary_words.is_some_and(|min_length| dictionary::contains_word(password, min_length))
+    }
+}
+
+/// Position of `c` in the alphabet or among the digits, letters ignoring case
+fn sequence_key(c: char) -> Option<(bool, u32)> {
+    if c.is_ascii_digit() {
+        Some((true, c as u32))
+    } else if c.is_ascii_alphabetic() {
+        Some((false, c.to_ascii_lowercase() as u32))
+    } else {
+        None
+    }
+}
+
+/// Whether `password` contains `run` or more consecutive letters or digits
+/// that step up or down by one, such as `abc`, `XYZ` or `321`
+pub fn has_sequential_run(password: &str, run: u16) -> bool {
+    let run = run.max(2) as usize;
+    let mut length = 1;
+    let mut step = 0i64;
+    let mut previous: Option<(bool, u32)> = None;
+
+    for key in password.chars().map(sequence_key) {
+        match (previous, key) {
+            (Some((prev_digit, prev)), Some((digit, current))) if prev_digit == digit => {
+                let diff = current as i64 - prev as i64;
+                if diff.abs() == 1 && (length == 1 || diff == step) {
+                    length += 1;
+                } else if diff.abs() == 1 {
+                    // Direction changed: the previous character starts a new run
+                    length = 2;
+                } else {
+                    length = 1;
+                }
+                step = diff;
+            }
+            _ => length = 1,
+        }
+
+        if length >= run {
+            return true;
+        }
+        previous = key;
+    }
+
+    false
+}
+
+/// Generate `length` characters from `charset` that satisfy `policy`
+pub fn generate_with_policy<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: u32,
+    charset: &[char],
+    policy: &Policy
+) -> Result<Secret, PasswordError> {
+    policy.validate(length, charset)?;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let password = if policy.unique {
+            generate_unique_from_charset(rng, length, charset)?
+        } else if policy.no_adjacent_repeat {
+            generate_no_repeat_from_charset(rng, length, charset)?
+        } else {
+            generate_from_charset(rng, length, charset)?
+        };
+        if policy.is_satisfied(&password) {
+            return Ok(password);
+        }
+    }
+
+    Err(PasswordError::PolicyUnsatisfiable(MAX_ATTEMPTS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_charset, get_secure_rng};
+
+    #[test]
+    fn test_class_names() {
+        for name in CharClass::NAMES {
+            assert!(name.parse::<CharClass>().is_ok());
+        }
+        assert_eq!("DIGIT".parse::<CharClass>(), Ok(CharClass::Digit));
+        assert!("digits".parse::<CharClass>().is_err());
+    }
+
+    #[test]
+    fn test_policy_enforced() {
+        let policy = Policy { min_digits: 3, min_upper: 2, min_lower: 2, min_symbols: 3, ..Policy::default() };
+        let charset = build_charset(true, false, false);
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..50 {
+            let password = generate_with_policy(&mut rng, 12, &charset, &policy).unwrap();
+            assert_eq!(password.len(), 12);
+            assert!(password.chars().filter(|c| c.is_ascii_digit()).count() >= 3);
+            assert!(password.chars().filter(|c| c.is_ascii_uppercase()).count() >= 2);
+            assert!(password.chars().filter(|c| c.is_ascii_lowercase()).count() >= 2);
+            assert!(password.chars().filter(|c| !c.is_ascii_alphanumeric()).count() >= 3);
+        }
+    }
+
+    #[test]
+    fn test_policy_exceeds_length() {
+        let policy = Policy { min_digits: 5, min_symbols: 5, ..Policy::default() };
+        assert!(matches!(
+            policy.validate(8, &build_charset(true, false, false)),
+            Err(PasswordError::PolicyExceedsLength(10, 8))
+        ));
+    }
+
+    #[test]
+    fn test_policy_class_missing() {
+        let policy = Policy { min_symbols: 1, ..Policy::default() };
+        assert!(matches!(
+            policy.validate(8, &build_charset(false, false, false)),
+            Err(PasswordError::PolicyClassMissing("symbols"))
+        ));
+    }
+
+    #[test]
+    fn test_policy_maximums() {
+        let policy = Policy { max_symbols: Some(1), max_digits: Some(0), ..Policy::default() };
+        let charset = build_charset(true, false, false);
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..50 {
+            let password = generate_with_policy(&mut rng, 16, &charset, &policy).unwrap();
+            assert!(password.chars().filter(|c| !c.is_ascii_alphanumeric()).count() <= 1);
+            assert!(!password.chars().any(|c| c.is_ascii_digit()));
+        }
+        assert!(!policy.is_satisfied("ab!c#"));
+        assert!(policy.is_satisfied("ab!cD"));
+
+        let policy = Policy { min_symbols: 3, max_symbols: Some(2), ..Policy::default() };
+        assert!(matches!(
+            policy.validate(12, &charset),
+            Err(PasswordError::PolicyMaxBelowMin("symbols", 2, 3))
+        ));
+
+        // Digits only, with at most 4 of them, can't fill 6 characters
+        let digits: Vec<char> = ('0'..='9').collect();
+        let policy = Policy { max_digits: Some(4), ..Policy::default() };
+        assert!(matches!(policy.validate(6, &digits), Err(PasswordError::PolicyMaxBelowLength(4, 6))));
+        assert!(policy.validate(4, &digits).is_ok());
+        assert!(policy.validate(6, &charset).is_ok());
+    }
+
+    #[test]
+    fn test_edge_policy() {
+        let policy = Policy { start_with: Some(StartWith::Letter), no_symbol_edges: true, ..Policy::default() };
+        let charset = build_charset(true, true, true);
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..50 {
+            let password = generate_with_policy(&mut rng, 10, &charset, &policy).unwrap();
+            assert!(password.starts_with(|c: char| c.is_ascii_alphabetic()));
+            assert!(password.ends_with(|c: char| c.is_ascii_alphanumeric()));
+        }
+        assert!(!policy.is_satisfied("7bcd"));
+        assert!(!policy.is_satisfied("abc "));
+        assert!(policy.is_satisfied("a!#1"));
+
+        let digits: Vec<char> = ('0'..='9').collect();
+        assert!(matches!(policy.validate(6, &digits), Err(PasswordError::PolicyEdgeMissing("a letter"))));
+        let policy = Policy { no_symbol_edges: true, ..Policy::default() };
+        assert!(matches!(policy.validate(6, &['!', '#']), Err(PasswordError::PolicyEdgeMissing(_))));
+
+        assert_eq!("UPPER".parse::<StartWith>(), Ok(StartWith::Upper));
+        assert!("symbol".parse::<StartWith>().is_err());
+    }
+
+    #[test]
+    fn test_no_adjacent_repeat_policy() {
+        let policy = Policy { no_adjacent_repeat: true, min_digits: 2, ..Policy::default() };
+        let charset: Vec<char> = "ab12".chars().collect();
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..50 {
+            let password = generate_with_policy(&mut rng, 12, &charset, &policy).unwrap();
+            assert!(policy.is_satisfied(&password));
+        }
+        assert!(!policy.is_satisfied("a1bb2"));
+        assert!(matches!(policy.validate(3, &['x']), Err(PasswordError::AdjacentRepeatUnavoidable)));
+    }
+
+    #[test]
+    fn test_dictionary_policy() {
+        let policy = Policy { dictionary_words: Some(3), ..Policy::default() };
+        let charset: Vec<char> = "aeiourstnl".chars().collect();
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..20 {
+            let password = generate_with_policy(&mut rng, 12, &charset, &policy).unwrap();
+            assert!(!dictionary::contains_word(&password, 3));
+        }
+        assert!(!policy.is_satisfied("xxsaladxx"));
+    }
+
+    #[test]
+    fn test_unique_policy() {
+        let policy = Policy { min_digits: 4, unique: true, ..Policy::default() };
+        let charset = build_charset(false, false, false);
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..20 {
+            let password = generate_with_policy(&mut rng, 30, &charset, &policy).unwrap();
+            let distinct: std::collections::HashSet<char> = password.chars().collect();
+            assert_eq!(distinct.len(), 30);
+            assert!(policy.is_satisfied(&password));
+        }
+
+        assert!(matches!(
+            policy.validate(63, &charset),
+            Err(PasswordError::UniqueExceedsCharset(63, 62))
+        ));
+    }
+
+    #[test]
+    fn test_sequential_runs() {
+        assert!(has_sequential_run("xabcx", 3));
+        assert!(has_sequential_run("a321b", 3));
+        assert!(has_sequential_run("XyZ", 3));
+        assert!(has_sequential_run("ab", 2));
+        assert!(!has_sequential_run("abd", 3));
+        assert!(!has_sequential_run("aba", 3));
+        assert!(!has_sequential_run("ab-c", 3));
+        assert!(!has_sequential_run("9ab", 3));
+        assert!(!has_sequential_run("abcd", 5));
+        assert!(has_sequential_run("cbabc", 3));
+    }
+
+    #[test]
+    fn test_sequential_policy() {
+        let policy = Policy { sequential_run: Some(2), ..Policy::default() };
+        let charset = build_charset(false, false, false);
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..50 {
+            let password = generate_with_policy(&mut rng, 16, &charset, &policy).unwrap();
+            assert!(!has_sequential_run(&password, 2));
+        }
+        assert!(!policy.is_satisfied("a1b2c3de"));
+    }
+
+    #[test]
+    fn test_keyboard_walk_policy() {
+        let policy = Policy {
+            keyboard_walk: Some(WalkFilter { layout: crate::keyboard::Layout::Qwerty, run: 3 }),
+            ..Policy::default()
+        };
+        let charset = build_charset(true, false, false);
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..50 {
+            let password = generate_with_policy(&mut rng, 24, &charset, &policy).unwrap();
+            assert!(!has_keyboard_walk(&password, crate::keyboard::Layout::Qwerty, 3));
+        }
+        assert!(!policy.is_satisfied("x9asdx"));
+    }
+
+    #[test]
+    fn test_policy_is_satisfied() {
+        let policy = Policy { min_digits: 2, min_upper: 1, ..Policy::default() };
+        assert!(policy.is_satisfied("aB12"));
+        assert!(!policy.is_satisfied("aB1c"));
+        assert!(!policy.is_satisfied("ab12"));
+        assert!(Policy::default().is_satisfied("anything"));
+    }
+}
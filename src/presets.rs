@@ -0,0 +1,264 @@
+//! Built-in presets for systems with their own password rules, and
+//! user-defined presets from `~/.config/password-generator/presets.toml`.
+//!
+//! Each preset is written in the config file format and applied on top of
+//! the config file, so flags given on the command line still win.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use password::PasswordError;
+
+use crate::config::Config;
+
+/// Name, description and settings of every built-in preset
+pub const BUILTIN_PRESETS: &[(&str, &str, &str)] = &[
+    (
+        "aws-iam",
+        "AWS IAM users: every class, only the symbols IAM accepts",
+        r#"
+        length = 32
+        charset = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!@#$%^&*()_+-=[]{}|'"
+        min-upper = 1
+        min-lower = 1
+        min-digits = 1
+        min-symbols = 1
+        "#,
+    ),
+    (
+        "active-directory",
+        "Windows Active Directory complexity requirements",
+        r#"
+        length = 24
+        min-upper = 1
+        min-lower = 1
+        min-digits = 1
+        min-symbols = 1
+        "#,
+    ),
+    (
+        "mysql",
+        "MySQL validate_password MEDIUM/STRONG, safe inside quoted SQL strings",
+        r#"
+        length = 32
+        exclude = ";"
+        min-upper = 1
+        min-lower = 1
+        min-digits = 1
+        min-symbols = 1
+        "#,
+    ),
+    (
+        "postgres",
+        "PostgreSQL, safe in connection URIs and .pgpass files without escaping",
+        r#"
+        length = 32
+        charset = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~"
+        min-upper = 1
+        min-lower = 1
+        min-digits = 1
+        "#,
+    ),
+    (
+        "oracle-legacy",
+        "Oracle 11g and earlier: 30 characters, case-insensitive, only _ $ # as symbols, starting with a letter",
+        r#"
+        length = 30
+        charset = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_$#"
+        min-digits = 1
+        start-with = "letter"
+        "#,
+    ),
+    (
+        "wifi",
+        "WPA2/WPA3-Personal key: 63 printable ASCII characters, the longest routers accept",
+        r#"
+        length = 63
+        extended-symbols = true
+        "#,
+    ),
+    (
+        "wifi-hex",
+        "WPA2/WPA3-Personal raw key: 64 hex digits (also --preset wifi --hex)",
+        r#"
+        length = 64
+        charset = "0123456789abcdef"
+        "#,
+    ),
+];
+
+/// Named presets from a user's presets file: one table per preset
+pub type UserPresets = BTreeMap<String, Config>;
+
+/// Load the presets file at `path`. A missing file is only an error when it
+/// was requested explicitly.
+pub fn load_file(path: &Path, explicit: bool) -> Result<UserPresets, PasswordError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound && !explicit => {
+            return Ok(UserPresets::new())
+        }
+        Err(err) => {
+            return Err(PasswordError::Config(format!("{}: {}", path.display(), err)))
+        }
+    };
+
+    parse_file(&contents)
+        .map_err(|reason| PasswordError::Config(format!("{}: {}", path.display(), reason)))
+}
+
+pub fn parse_file(contents: &str) -> Result<UserPresets, String> {
+    let presets: UserPresets = toml::from_str(contents).map_err(|err| err.message().to_string())?;
+    for (name, preset) in &presets {
+        if !preset.profiles.is_empty() {
+            return Err(format!("preset '{}': profiles belong in the config file", name));
+        }
+        preset.validate().map_err(|reason| format!("preset '{}': {}", name, reason))?;
+    }
+    Ok(presets)
+}
+
+/// Names of the user's presets followed by the built-in ones they don't replace
+fn names(user: &UserPresets) -> Vec<&str> {
+    let builtin = BUILTIN_PRESETS
+        .iter()
+        .map(|(name, _, _)| *name)
+        .filter(|name| !user.contains_key(*name));
+    user.keys().map(String::as_str).chain(builtin).collect()
+}
+
+/// The settings of the preset called `name`. A user preset replaces a
+/// built-in one of the same name.
+pub fn load(name: &str, user: &UserPresets) -> Result<Config, PasswordError> {
+    if let Some(preset) = user.get(name) {
+        return Ok(preset.clone());
+    }
+
+    let (_, _, settings) = BUILTIN_PRESETS
+        .iter()
+        .find(|(preset, _, _)| *preset == name)
+        .ok_or_else(|| PasswordError::UnknownPreset(format!(
+            "'{}' (available: {})", name, names(user).join(", ")
+        )))?;
+
+    Ok(Config::parse(settings).expect("built-in presets are valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+    use crate::{Args, Generator};
+    use clap::{CommandFactory, FromArgMatches};
+    use password::{get_secure_rng, wifi};
+
+    fn apply(argv: &[&str]) -> Args {
+        let matches = Args::command().get_matches_from(argv);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        load(args.preset.as_deref().unwrap(), &UserPresets::new()).unwrap().apply(&mut args, &matches);
+        args
+    }
+
+    #[test]
+    fn test_builtin_presets_generate() {
+        let mut rng = get_secure_rng().unwrap();
+        for name in names(&UserPresets::new()) {
+            let args = apply(&["password", "--preset", name]);
+            let generator = Generator::from_args(&args).unwrap();
+            for _ in 0..20 {
+                generator.generate(&mut rng).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_preset_charset() {
+        let args = apply(&["password", "--preset", "oracle-legacy"]);
+        assert_eq!(args.length, 30);
+
+        let generator = Generator::from_args(&args).unwrap();
+        let mut rng = get_secure_rng().unwrap();
+        for _ in 0..50 {
+            let password = generator.generate(&mut rng).unwrap();
+            assert!(password.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || "_$#".contains(c)));
+            // Unquoted Oracle identifiers must start with a letter
+            assert!(password.starts_with(|c: char| c.is_ascii_alphabetic()), "{}", *password);
+        }
+    }
+
+    #[test]
+    fn test_wifi_presets() {
+        let args = apply(&["password", "--preset", "wifi"]);
+        let password = Generator::from_args(&args).unwrap().generate(&mut get_secure_rng().unwrap()).unwrap();
+        assert_eq!(password.len(), wifi::MAX_PASSPHRASE_LENGTH);
+        assert!(password.chars().all(|c| c.is_ascii_graphic()));
+
+        let args = apply(&["password", "--preset", "wifi-hex"]);
+        let key = Generator::from_args(&args).unwrap().generate(&mut get_secure_rng().unwrap()).unwrap();
+        assert_eq!(key.len(), wifi::HEX_KEY_LENGTH);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_cli_overrides_preset() {
+        let args = apply(&["password", "--preset", "postgres", "-l", "64", "-n"]);
+        assert_eq!(args.length, 64);
+        assert_eq!(args.charset, None);
+        assert_eq!(args.min_upper, 1);
+    }
+
+    #[test]
+    fn test_preset_overrides_config() {
+        let matches = Args::command().get_matches_from(["password", "--preset", "active-directory"]);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        Config::parse("length = 16\nexclude-ambiguous = true\n").unwrap().apply(&mut args, &matches);
+        load("active-directory", &UserPresets::new()).unwrap().apply(&mut args, &matches);
+
+        assert_eq!(args.length, 24);
+        assert!(args.exclude_ambiguous);
+    }
+
+    #[test]
+    fn test_user_presets() {
+        let user = parse_file(r#"
+            [myteam-db]
+            length = 40
+            charset = "abcdef0123456789"
+            min-digits = 4
+            format = "json"
+
+            [postgres]
+            length = 64
+        "#).unwrap();
+
+        let matches = Args::command().get_matches_from(["password", "--preset", "myteam-db"]);
+        let mut args = Args::from_arg_matches(&matches).unwrap();
+        load("myteam-db", &user).unwrap().apply(&mut args, &matches);
+        assert_eq!(args.length, 40);
+        assert_eq!(args.charset.as_deref(), Some("abcdef0123456789"));
+        assert_eq!(args.min_digits, 4);
+        assert_eq!(args.format, OutputFormat::Json);
+
+        assert_eq!(load("postgres", &user).unwrap().length, Some(64));
+        assert_eq!(load("mysql", &user).unwrap().length, Some(32));
+    }
+
+    #[test]
+    fn test_invalid_user_presets() {
+        assert!(parse_file("[db]\nlength = 0\n").unwrap_err().contains("'db'"));
+        assert!(parse_file("[db]\ncolour = \"blue\"\n").is_err());
+        assert!(parse_file("length = 20\n").is_err());
+        assert!(parse_file("[db]\n[db.profile.work]\nlength = 20\n").unwrap_err().contains("config file"));
+        assert!(parse_file("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_preset() {
+        let user = parse_file("[myteam-db]\nlength = 40\n").unwrap();
+        match load("sqlserver", &user) {
+            Err(PasswordError::UnknownPreset(reason)) => assert!(reason.contains("myteam-db, aws-iam")),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Rejects word-like output (passphrases, pronounceable passwords and
+//! usernames) that happens to spell profanity or a slur in one of the
+//! supported languages, including across word boundaries and through
+//! leetspeak.
+
+use once_cell::sync::Lazy;
+
+use crate::{leet, Secret};
+
+static PROFANITY: Lazy<Vec<&'static str>> = Lazy::new(|| {
+    include_str!("wordlists/profanity.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+});
+
+/// `text` lowercased with leetspeak undone and everything but letters
+/// dropped, so `Sh1t`, `sh-it` and `fresh itinerary` all read `shit...`
+fn normalize(text: &str) -> Secret {
+    let mut normalized = Secret::new(String::with_capacity(text.len()));
+    for c in text.chars() {
+        let letter = leet::SUBSTITUTIONS
+            .iter()
+            .find(|(_, substitutes)| substitutes.contains(&c))
+            .map_or(c, |(letter, _)| *letter);
+        if letter.is_alphabetic() {
+            normalized.extend(letter.to_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Whether `text` contains an entry of the built-in profanity list
+pub fn contains_profanity(text: &str) -> bool {
+    let normalized = normalize(text);
+    PROFANITY.iter().any(|word| normalized.contains(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(*normalize("C0rrect-H0r$e 7!"), "correcthorseti");
+    }
+
+    #[test]
+    fn test_contains_profanity() {
+        assert!(contains_profanity("fresh itinerary"));
+        assert!(contains_profanity("Sh1t"));
+        assert!(contains_profanity("bakoscheißa"));
+        assert!(contains_profanity("unamierdaka"));
+        assert!(!contains_profanity("correct horse battery staple"));
+        assert!(!contains_profanity("bakomipatu"));
+    }
+
+    #[test]
+    fn test_profanity_list() {
+        assert!(PROFANITY.len() > 50);
+        // Entries are matched against normalized text, so they must be normalized too
+        assert!(PROFANITY.iter().all(|word| *normalize(word) == **word));
+    }
+}
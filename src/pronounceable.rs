@@ -0,0 +1,93 @@
+//! Passwords built from alternating consonants and vowels.
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+
+use crate::{leet, PasswordError, Secret};
+
+const CONSONANTS: &str = "bcdfghjklmnprstvwz";
+const VOWELS: &str = "aeiou";
+
+/// Generate a lowercase password of alternating consonants and vowels
+/// (`ba-ko-mi-...`) so it can be read aloud and typed easily.
+pub fn generate_pronounceable<R: Rng + ?Sized>(rng: &mut R, length: u32) -> Result<Secret, PasswordError> {
+    let consonants: Vec<char> = CONSONANTS.chars().collect();
+    let vowels: Vec<char> = VOWELS.chars().collect();
+
+    let consonant_distribution = Uniform::from(0..consonants.len());
+    let vowel_distribution = Uniform::from(0..vowels.len());
+    let mut password = Secret::new(String::with_capacity(length as usize));
+
+    for i in 0..length {
+        if i % 2 == 0 {
+            password.push(consonants[consonant_distribution.sample(rng)]);
+        } else {
+            password.push(vowels[vowel_distribution.sample(rng)]);
+        }
+    }
+
+    Ok(password)
+}
+
+/// Effective entropy in bits of a pronounceable password of the given length.
+///
+/// This is lower than a random password over the same letters because every
+/// position is restricted to either the consonant or the vowel pool.
+pub fn pronounceable_entropy(length: u32) -> f64 {
+    let consonant_positions = (length as f64 / 2.0).ceil();
+    let vowel_positions = (length / 2) as f64;
+
+    consonant_positions * (CONSONANTS.len() as f64).log2()
+        + vowel_positions * (VOWELS.len() as f64).log2()
+}
+
+/// Average bits added to a pronounceable password of the given length by
+/// leetspeak substitutions
+pub fn pronounceable_leet_entropy(length: u32) -> f64 {
+    let consonant_positions = (length as f64 / 2.0).ceil();
+    let vowel_positions = (length / 2) as f64;
+
+    consonant_positions * leet::mean_leet_entropy(CONSONANTS)
+        + vowel_positions * leet::mean_leet_entropy(VOWELS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_pronounceable_length() {
+        let password = generate_pronounceable(&mut get_secure_rng().unwrap(), 37).unwrap();
+        assert_eq!(password.len(), 37);
+    }
+
+    #[test]
+    fn test_pronounceable_alternates() {
+        let password = generate_pronounceable(&mut get_secure_rng().unwrap(), 100).unwrap();
+        for (i, c) in password.chars().enumerate() {
+            if i % 2 == 0 {
+                assert!(CONSONANTS.contains(c), "Expected consonant at {}: {}", i, c);
+            } else {
+                assert!(VOWELS.contains(c), "Expected vowel at {}: {}", i, c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pronounceable_entropy() {
+        let expected = 18f64.log2() + 5f64.log2();
+        assert!((pronounceable_entropy(2) - expected).abs() < 1e-9);
+        assert!((pronounceable_entropy(3) - (expected + 18f64.log2())).abs() < 1e-9);
+        // Lower than a random lowercase password of the same length
+        assert!(pronounceable_entropy(20) < 20.0 * 26f64.log2());
+    }
+
+    #[test]
+    fn test_pronounceable_leet_entropy() {
+        // 's' and 't' among 18 consonants, every vowel but 'u' among 5
+        let consonant = (3f64.log2() + 1.0) / 18.0;
+        let vowel = (2.0 * 3f64.log2() + 2.0) / 5.0;
+        assert!((pronounceable_leet_entropy(3) - (2.0 * consonant + vowel)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,40 @@
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+
+use password::PasswordError;
+
+/// Render `contents` as a QR code using Unicode half blocks, two modules per
+/// character cell.
+///
+/// Light modules are drawn as blocks, so the code scans correctly on the usual
+/// light-text-on-dark-background terminal.
+pub fn render(contents: &str) -> Result<String, PasswordError> {
+    let code = QrCode::new(contents.as_bytes())
+        .map_err(|err| PasswordError::QrCode(err.to_string()))?;
+
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_qr() {
+        let rendered = render("correct horse battery staple").unwrap();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines.len() > 10);
+        assert!(lines.iter().all(|line| line.chars().count() == lines[0].chars().count()));
+        assert!(!rendered.contains("correct"));
+    }
+
+    #[test]
+    fn test_render_qr_too_long() {
+        assert!(matches!(render(&"x".repeat(8000)), Err(PasswordError::QrCode(_))));
+    }
+}
@@ -0,0 +1,116 @@
+//! Selectable random number generator backends.
+//!
+//! Every backend is a cryptographically secure generator behind the
+//! [`CryptoRngCore`] trait, so generation code can take any of them:
+//!
+//! - `os` reads every byte straight from the operating system (getrandom).
+//!   Nothing is kept in process memory, at the cost of a system call per draw.
+//! - `chacha20` seeds ChaCha20 once from the OS. This is the default.
+//! - `chacha20-reseeding` is ChaCha20 that pulls a fresh seed from the OS
+//!   after every [`RESEED_THRESHOLD`] bytes, for long batch runs.
+//! - `hmac-drbg` is the NIST SP 800-90A HMAC_DRBG over SHA-256, for
+//!   environments that only allow approved generators (see [`crate::drbg`]).
+
+use rand::rngs::adapter::ReseedingRng;
+use rand_chacha::ChaCha20Core;
+use rand_core::{CryptoRngCore, OsRng, SeedableRng};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::drbg::HmacDrbg;
+use crate::{get_secure_rng, PasswordError};
+
+/// Bytes `chacha20-reseeding` draws before reseeding from the OS (64 KiB)
+pub const RESEED_THRESHOLD: u64 = 64 * 1024;
+
+/// A boxed generator from any backend
+pub type BoxedRng = Box<dyn CryptoRngCore>;
+
+/// Where random bytes come from
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RngBackend {
+    /// The operating system's generator, for every draw
+    Os,
+    /// ChaCha20 seeded once from the OS
+    #[default]
+    ChaCha20,
+    /// ChaCha20 reseeded from the OS every [`RESEED_THRESHOLD`] bytes
+    ChaCha20Reseeding,
+    /// HMAC_DRBG over SHA-256, seeded from the OS
+    HmacDrbg,
+}
+
+impl RngBackend {
+    /// A new generator from this backend
+    pub fn new_rng(self) -> Result<BoxedRng, PasswordError> {
+        Ok(match self {
+            RngBackend::Os => Box::new(OsRng),
+            RngBackend::ChaCha20 => Box::new(get_secure_rng()?),
+            RngBackend::ChaCha20Reseeding => {
+                let core = ChaCha20Core::from_rng(OsRng).map_err(|_| PasswordError::RngInitializationError)?;
+                Box::new(ReseedingRng::new(core, RESEED_THRESHOLD, OsRng))
+            }
+            RngBackend::HmacDrbg => Box::new(HmacDrbg::from_os()?),
+        })
+    }
+}
+
+impl FromStr for RngBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "os" => Ok(RngBackend::Os),
+            "chacha20" => Ok(RngBackend::ChaCha20),
+            "chacha20-reseeding" => Ok(RngBackend::ChaCha20Reseeding),
+            "hmac-drbg" => Ok(RngBackend::HmacDrbg),
+            _ => Err(format!(
+                "Unknown RNG backend '{}' (expected os, chacha20, chacha20-reseeding or hmac-drbg)", s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RngBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            RngBackend::Os => "os",
+            RngBackend::ChaCha20 => "chacha20",
+            RngBackend::ChaCha20Reseeding => "chacha20-reseeding",
+            RngBackend::HmacDrbg => "hmac-drbg",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_password;
+
+    #[test]
+    fn test_every_backend_generates() {
+        for backend in [RngBackend::Os, RngBackend::ChaCha20, RngBackend::ChaCha20Reseeding, RngBackend::HmacDrbg] {
+            let mut rng = backend.new_rng().unwrap();
+            let first = generate_password(&mut rng, 36, true, false, false).unwrap();
+            let second = generate_password(&mut rng, 36, true, false, false).unwrap();
+            assert_eq!(first.len(), 36);
+            assert_ne!(first, second, "{}", backend);
+        }
+    }
+
+    #[test]
+    fn test_reseeding_past_threshold() {
+        let mut rng = RngBackend::ChaCha20Reseeding.new_rng().unwrap();
+        let mut buf = vec![0u8; RESEED_THRESHOLD as usize * 3];
+        rng.fill_bytes(&mut buf);
+        assert!(buf.iter().any(|&b| b != 0));
+    }
+
+    #[test]
+    fn test_backend_names() {
+        for name in ["os", "chacha20", "chacha20-reseeding", "hmac-drbg"] {
+            assert_eq!(name.parse::<RngBackend>().unwrap().to_string(), name);
+        }
+        assert!("mt19937".parse::<RngBackend>().is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! `--serve`: a small HTTP API for requesting passwords from a trusted host.
+//!
+//! `POST /generate` takes a JSON object of the same settings as a `--batch`
+//! spec (plus `count` and `score`) and answers with the generated passwords.
+//! Errors are JSON too: `{"error": {"status": 400, "message": "..."}}`.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::Read;
+use tiny_http::{Header, Response as HttpResponse, Server};
+use zeroize::Zeroizing;
+
+use password::blocklist::Blocklist;
+use password::{strength, PasswordError, Secret};
+
+use crate::output::JsonRecord;
+use crate::{batch, generate_unblocked, rng_backend, validate_args, Args, Generator};
+
+/// Largest request body accepted, in bytes
+pub const MAX_BODY_BYTES: usize = 16 * 1024;
+
+/// Most passwords returned by a single request
+pub const MAX_COUNT: u64 = 100;
+
+/// Status code and JSON body of a response
+pub struct Response {
+    pub status: u16,
+    pub body: Secret,
+}
+
+#[derive(Serialize)]
+struct GenerateResponse<'a> {
+    passwords: Vec<JsonRecord<'a>>,
+}
+
+fn error(status: u16, message: impl Into<String>) -> Response {
+    let body = json!({ "error": { "status": status, "message": message.into() } });
+    Response { status, body: Secret::new(body.to_string()) }
+}
+
+/// Scalar JSON values as the text a batch spec would contain
+fn setting_value(key: &str, value: &Value) -> Result<Option<String>, String> {
+    match value {
+        Value::String(s) => Ok(Some(s.clone())),
+        Value::Number(n) => Ok(Some(n.to_string())),
+        Value::Bool(b) => Ok(Some(b.to_string())),
+        Value::Null => Ok(None),
+        _ => Err(format!("'{}' must be a string, number or boolean", key)),
+    }
+}
+
+/// Answer a single request
+pub fn handle(
+    base: &Args,
+    blocklist: Option<&Blocklist>,
+    method: &str,
+    path: &str,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Response {
+    if path != "/generate" {
+        return error(404, format!("No such endpoint: {}", path));
+    }
+    if method != "POST" {
+        return error(405, "Use POST /generate");
+    }
+    if content_type.is_some_and(|ct| !ct.trim_start().starts_with("application/json")) {
+        return error(415, "Content-Type must be application/json");
+    }
+    if body.len() > MAX_BODY_BYTES {
+        return error(413, format!("Request body exceeds {} bytes", MAX_BODY_BYTES));
+    }
+
+    let body = if body.is_empty() { b"{}" } else { body };
+    let options: Value = match serde_json::from_slice(body) {
+        Ok(options) => options,
+        Err(err) => return error(400, format!("Invalid JSON: {}", err)),
+    };
+    let Some(options) = options.as_object() else {
+        return error(400, "Expected a JSON object of options");
+    };
+
+    let mut count = 1;
+    let mut with_score = false;
+    let mut settings = Vec::with_capacity(options.len());
+    for (key, value) in options {
+        match key.as_str() {
+            "count" => match value.as_u64().filter(|count| (1..=MAX_COUNT).contains(count)) {
+                Some(n) => count = n,
+                None => return error(400, format!("'count' must be between 1 and {}", MAX_COUNT)),
+            },
+            "score" => match value.as_bool() {
+                Some(score) => with_score = score,
+                None => return error(400, "'score' must be a boolean"),
+            },
+            _ => match setting_value(key, value) {
+                Ok(Some(text)) => settings.push((key.as_str(), Zeroizing::new(text))),
+                Ok(None) => {}
+                Err(reason) => return error(400, reason),
+            },
+        }
+    }
+
+    let spec = match batch::apply_settings(
+        base,
+        settings.iter().map(|(key, value)| (*key, Some(value.as_str()))),
+    ) {
+        Ok(spec) => spec,
+        Err(reason) => return error(400, reason),
+    };
+
+    match generate(&spec, blocklist, count) {
+        Ok((generator, passwords)) => {
+            let strengths: Vec<_> = passwords
+                .iter()
+                .map(|password| with_score.then(|| strength::estimate(password)))
+                .collect();
+            let response = GenerateResponse {
+                passwords: passwords
+                    .iter()
+                    .zip(&strengths)
+                    .map(|(password, strength)| JsonRecord::new(
+                        password,
+                        generator.charset_size(),
+                        generator.entropy(),
+                        strength.as_ref(),
+                    ))
+                    .collect(),
+            };
+            let body = serde_json::to_string(&response).expect("response is always serializable");
+            Response { status: 200, body: Secret::new(body) }
+        }
+        Err(err @ PasswordError::RngInitializationError) => error(500, err.to_string()),
+        Err(err @ PasswordError::BreachCheck(_)) => error(502, err.to_string()),
+        Err(err) => error(422, err.to_string()),
+    }
+}
+
+/// Generate `count` passwords for one request, along with the generator
+/// that describes them
+fn generate(
+    spec: &Args,
+    blocklist: Option<&Blocklist>,
+    count: u64,
+) -> Result<(Generator, Vec<Secret>), PasswordError> {
+    validate_args(spec)?;
+    let generator = Generator::from_args(spec)?;
+    let mut rng = rng_backend(spec).new_rng()?;
+
+    let passwords = (0..count)
+        .map(|_| generate_unblocked(&generator, &mut rng, spec, blocklist))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((generator, passwords))
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("static headers are valid")
+}
+
+/// Serve requests on `addr` until the process is stopped
+pub fn run(addr: &str, base: &Args, blocklist: Option<&Blocklist>) -> Result<(), PasswordError> {
+    let server = Server::http(addr)
+        .map_err(|err| PasswordError::Server(format!("{}: {}", addr, err)))?;
+    eprintln!("Listening on http://{}", addr);
+
+    for mut request in server.incoming_requests() {
+        let method = request.method().to_string();
+        let path = request.url().split('?').next().unwrap_or_default().to_string();
+        let content_type = request.headers()
+            .iter()
+            .find(|header| header.field.equiv("Content-Type"))
+            .map(|header| header.value.to_string());
+
+        let declared_too_large = request.body_length().is_some_and(|length| length > MAX_BODY_BYTES);
+        let mut body = Zeroizing::new(Vec::new());
+        let response = if declared_too_large {
+            error(413, format!("Request body exceeds {} bytes", MAX_BODY_BYTES))
+        } else {
+            // Read one byte past the limit so oversized chunked bodies are caught too
+            match request.as_reader().take(MAX_BODY_BYTES as u64 + 1).read_to_end(&mut body) {
+                Ok(_) => handle(base, blocklist, &method, &path, content_type.as_deref(), &body),
+                Err(err) => error(400, format!("Failed to read request body: {}", err)),
+            }
+        };
+
+        // Passwords are never logged
+        eprintln!("{} {} {}", method, path, response.status);
+
+        let mut http_response = HttpResponse::from_data(response.body.as_bytes().to_vec())
+            .with_status_code(response.status)
+            .with_header(header("Content-Type", "application/json"))
+            .with_header(header("Cache-Control", "no-store"))
+            .with_header(header("X-Content-Type-Options", "nosniff"));
+        if response.status == 405 {
+            http_response.add_header(header("Allow", "POST"));
+        }
+        if let Err(err) = request.respond(http_response) {
+            eprintln!("Error: failed to send response: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn post(body: &str) -> (u16, Value) {
+        let base = Args::try_parse_from(["password"]).unwrap();
+        let response = handle(&base, None, "POST", "/generate", Some("application/json"), body.as_bytes());
+        (response.status, serde_json::from_str(&response.body).unwrap())
+    }
+
+    #[test]
+    fn test_generate_defaults() {
+        let (status, body) = post("");
+        assert_eq!(status, 200);
+        assert_eq!(body["passwords"][0]["length"], 36);
+    }
+
+    #[test]
+    fn test_generate_with_options() {
+        let (status, body) = post(r#"{"length": 20, "classes": "alnum", "count": 3, "score": true}"#);
+        assert_eq!(status, 200);
+        let passwords = body["passwords"].as_array().unwrap();
+        assert_eq!(passwords.len(), 3);
+        assert!(passwords.iter().all(|p| p["length"] == 20 && p["score"].is_u64()));
+
+        let (status, body) = post(r#"{"charset": "ab cd", "length": 8, "exclude-ambiguous": false}"#);
+        assert_eq!(status, 200);
+        assert!(body["passwords"][0]["password"].as_str().unwrap().chars().all(|c| "ab cd".contains(c)));
+
+        let (status, body) = post(r#"{"passphrase": 5}"#);
+        assert_eq!(status, 200);
+        assert_eq!(body["passwords"][0]["password"].as_str().unwrap().split(' ').count(), 5);
+    }
+
+    #[test]
+    fn test_error_responses() {
+        assert_eq!(post("{not json").0, 400);
+        assert_eq!(post("[1, 2]").0, 400);
+        assert_eq!(post(r#"{"colour": "blue"}"#).0, 400);
+        assert_eq!(post(r#"{"count": 1000}"#).0, 400);
+        assert_eq!(post(r#"{"length": [1]}"#).0, 400);
+        assert_eq!(post(r#"{"pin": 4, "unique": true, "min-symbols": 1}"#).0, 422);
+
+        let (status, body) = post(r#"{"length": 0}"#);
+        assert_eq!(status, 400);
+        assert_eq!(body["error"]["status"], 400);
+        assert!(body["error"]["message"].as_str().unwrap().contains("length"));
+
+        let base = Args::try_parse_from(["password"]).unwrap();
+        assert_eq!(handle(&base, None, "GET", "/generate", None, b"").status, 405);
+        assert_eq!(handle(&base, None, "POST", "/other", None, b"").status, 404);
+        assert_eq!(handle(&base, None, "POST", "/generate", Some("text/plain"), b"{}").status, 415);
+        let large = vec![b' '; MAX_BODY_BYTES + 1];
+        assert_eq!(handle(&base, None, "POST", "/generate", None, &large).status, 413);
+    }
+}
@@ -0,0 +1,213 @@
+//! `--store` (or `--push`): hand the generated secret straight to a password
+//! manager or secrets service, so it is never printed to the terminal.
+//!
+//! Destinations are written `SCHEME:TARGET`, e.g. `pass:web/example.com` or
+//! `aws-sm:prod/db-password`. Password managers are driven through their
+//! own command line tools, which bring their usual configuration and
+//! credentials along; Secrets Manager and Vault are written over their APIs.
+
+use std::env;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use password::PasswordError;
+
+use crate::{aws, vault};
+
+/// Where `--store` sends the secret
+#[derive(Clone, Debug, PartialEq)]
+pub enum Store {
+    /// An entry in the standard unix password manager, via `pass insert -m`
+    Pass(String),
+    /// An AWS Secrets Manager secret (name or ARN), via the AWS SDK
+    AwsSecretsManager(String),
+    /// A key of a secret in a Vault KV v2 engine, written as `MOUNT/PATH#KEY`
+    Vault { mount: String, path: String, key: String },
+}
+
+impl Store {
+    pub const SCHEMES: [&'static str; 3] = ["pass", "aws-sm", "vault"];
+}
+
+impl FromStr for Store {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, target) = s.split_once(':').ok_or_else(|| format!(
+            "Expected SCHEME:TARGET (scheme one of {}). Got: {}", Store::SCHEMES.join(", "), s
+        ))?;
+
+        match scheme {
+            "pass" => Ok(Store::Pass(validate_pass_entry(target)?)),
+            "aws-sm" => Ok(Store::AwsSecretsManager(validate_aws_secret_id(target)?)),
+            "vault" => parse_vault_target(target),
+            _ => Err(format!(
+                "Unknown store '{}' (expected one of {})", scheme, Store::SCHEMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Store {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Store::Pass(entry) => write!(f, "pass:{}", entry),
+            Store::AwsSecretsManager(id) => write!(f, "aws-sm:{}", id),
+            Store::Vault { mount, path, key } => write!(f, "vault:{}/{}#{}", mount, path, key),
+        }
+    }
+}
+
+/// Check a pass entry name: a relative path without `.` or `..` components
+fn validate_pass_entry(entry: &str) -> Result<String, String> {
+    let valid = !entry.is_empty()
+        && !entry.starts_with(['/', '-'])
+        && entry.split('/').all(|part| !part.is_empty() && part != "." && part != "..");
+    if !valid {
+        return Err(format!("The pass entry must be a relative path like web/example.com. Got: {}", entry));
+    }
+    Ok(entry.to_string())
+}
+
+/// Check an AWS secret name or ARN: letters, digits and `/_+=.@-:`, up to
+/// 2048 characters (the limit of an ARN), not starting with `-`
+fn validate_aws_secret_id(id: &str) -> Result<String, String> {
+    let valid = !id.is_empty()
+        && id.len() <= 2048
+        && !id.starts_with('-')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || "/_+=.@-:".contains(c));
+    if !valid {
+        return Err(format!(
+            "The secret name may only contain letters, digits and /_+=.@-. Got: {}", id
+        ));
+    }
+    Ok(id.to_string())
+}
+
+/// Whether `s` is a safe Vault path segment or key: letters, digits, `-`, `_` and `.`
+fn is_vault_name(s: &str) -> bool {
+    !s.is_empty() && s != "." && s != ".." && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Parse `MOUNT/PATH#KEY`, e.g. `secret/prod/db#password`
+fn parse_vault_target(target: &str) -> Result<Store, String> {
+    let invalid = || format!("Expected vault:MOUNT/PATH#KEY, like vault:secret/prod/db#password. Got: vault:{}", target);
+
+    let (location, key) = target.split_once('#').ok_or_else(invalid)?;
+    let (mount, path) = location.split_once('/').ok_or_else(invalid)?;
+    if !is_vault_name(mount) || !is_vault_name(key) || !path.split('/').all(is_vault_name) {
+        return Err(invalid());
+    }
+
+    Ok(Store::Vault { mount: mount.to_string(), path: path.to_string(), key: key.to_string() })
+}
+
+/// The password store pass uses, honouring `PASSWORD_STORE_DIR`
+fn pass_store_dir() -> Option<PathBuf> {
+    env::var_os("PASSWORD_STORE_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".password-store")))
+}
+
+fn pass_command(entry: &str, force: bool) -> Command {
+    let mut command = Command::new("pass");
+    command.args(["insert", "--multiline"]);
+    if force {
+        command.arg("--force");
+    }
+    command.arg("--").arg(entry);
+    command
+}
+
+/// Store `secret` at `store`, returning the new version id for services that
+/// keep versions. An existing pass entry is replaced only when `force` is
+/// set; Secrets Manager and Vault keep the old value as a previous version,
+/// so they are always updated. `cas` is the version a Vault secret must be
+/// at for the write to go through.
+pub fn save(store: &Store, secret: &str, force: bool, cas: Option<u64>) -> Result<Option<String>, PasswordError> {
+    let error = |reason: String| PasswordError::Store(format!("{}: {}", store, reason));
+
+    match store {
+        Store::Pass(entry) => {
+            // pass would ask before overwriting, reading the answer from the
+            // stdin the secret is written to, so check here instead
+            let exists = pass_store_dir().is_some_and(|dir| dir.join(format!("{}.gpg", entry)).exists());
+            if exists && !force {
+                return Err(error("entry exists (use --force to overwrite)".to_string()));
+            }
+
+            let mut child = pass_command(entry, force)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn()
+                .map_err(|err| error(format!("could not run pass ({})", err)))?;
+
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin
+                    .write_all(secret.as_bytes())
+                    .and_then(|_| stdin.write_all(b"\n"))
+                    .map_err(|err| error(err.to_string()))?;
+            }
+
+            let status = child.wait().map_err(|err| error(err.to_string()))?;
+            if !status.success() {
+                return Err(error(format!("pass exited with {}", status)));
+            }
+            Ok(None)
+        }
+        Store::AwsSecretsManager(id) => aws::put(id, secret).map_err(error),
+        Store::Vault { mount, path, key } => {
+            let version = vault::write(mount, path, key, secret, cas).map_err(error)?;
+            Ok(Some(version.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_store() {
+        assert_eq!("pass:web/example.com".parse::<Store>().unwrap(), Store::Pass("web/example.com".to_string()));
+        assert_eq!(Store::Pass("email".to_string()).to_string(), "pass:email");
+
+        assert!("web/example.com".parse::<Store>().is_err());
+        assert!("keychain:email".parse::<Store>().is_err());
+
+        let arn = "arn:aws:secretsmanager:eu-west-1:123456789012:secret:prod/db-AbCdEf";
+        assert_eq!(format!("aws-sm:{}", arn).parse::<Store>().unwrap(), Store::AwsSecretsManager(arn.to_string()));
+        assert_eq!(Store::AwsSecretsManager("prod/db".to_string()).to_string(), "aws-sm:prod/db");
+        for id in ["", "-x", "db password", "db;rm"] {
+            assert!(format!("aws-sm:{}", id).parse::<Store>().is_err(), "{}", id);
+        }
+
+        let vault = "vault:secret/prod/db#password".parse::<Store>().unwrap();
+        assert_eq!(vault, Store::Vault {
+            mount: "secret".to_string(),
+            path: "prod/db".to_string(),
+            key: "password".to_string(),
+        });
+        assert_eq!(vault.to_string(), "vault:secret/prod/db#password");
+        for target in ["secret/prod/db", "secret#password", "secret/#password", "secret/prod/../x#k", "secret/db#", "secret/db#a b"] {
+            assert!(format!("vault:{}", target).parse::<Store>().is_err(), "{}", target);
+        }
+        for entry in ["", "/etc/passwd", "-f", "web/../../x", "web//x", "web/"] {
+            assert!(format!("pass:{}", entry).parse::<Store>().is_err(), "{}", entry);
+        }
+    }
+
+    #[test]
+    fn test_pass_command() {
+        let command = pass_command("web/example.com", false);
+        assert_eq!(command.get_program(), "pass");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["insert", "--multiline", "--", "web/example.com"]);
+
+        let command = pass_command("email", true);
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["insert", "--multiline", "--force", "--", "email"]);
+    }
+}
@@ -0,0 +1,209 @@
+//! Realistic strength estimates using zxcvbn.
+//!
+//! Charset entropy assumes an attacker knows nothing but the generation
+//! options. zxcvbn instead looks for dictionary words, keyboard walks,
+//! repeats and sequences, so it catches the rare random output that happens
+//! to look like something guessable.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Only the first this many characters are scored; zxcvbn slows down sharply
+/// on long inputs and anything longer is far beyond its scale anyway
+pub const MAX_SCORED_LENGTH: usize = 100;
+
+/// zxcvbn's verdict on a password
+#[derive(Clone, Debug, PartialEq)]
+pub struct Strength {
+    /// 0 (too guessable) to 4 (very unguessable)
+    pub score: u8,
+    /// Estimated number of guesses needed, as a power of ten
+    pub guesses_log10: f64,
+    /// Why the password is weak, when zxcvbn found a pattern
+    pub warning: Option<String>,
+}
+
+pub fn estimate(password: &str) -> Strength {
+    let scored: String = password.chars().take(MAX_SCORED_LENGTH).collect();
+    let entropy = zxcvbn::zxcvbn(&scored, &[]);
+
+    Strength {
+        score: entropy.score().into(),
+        guesses_log10: entropy.guesses_log10(),
+        warning: entropy
+            .feedback()
+            .and_then(|feedback| feedback.warning())
+            .map(|warning| warning.to_string()),
+    }
+}
+
+/// How fast an attacker can test guesses, by the hash the password is
+/// stored with. Offline rates are rough figures for a rig of eight current
+/// high-end GPUs running hashcat against default cost settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AttackModel {
+    /// Guessing against a live login form without rate limiting
+    Online,
+    Argon2id,
+    Bcrypt,
+    Sha512Crypt,
+    Apr1,
+    Sha256,
+    Md5,
+}
+
+impl AttackModel {
+    /// Names accepted on the command line
+    pub const NAMES: [&'static str; 7] = ["online", "argon2id", "bcrypt", "sha512-crypt", "apr1", "sha256", "md5"];
+
+    pub fn guesses_per_second(self) -> f64 {
+        match self {
+            AttackModel::Online => 10.0,
+            AttackModel::Argon2id => 1e4,
+            AttackModel::Bcrypt => 5e4,
+            AttackModel::Sha512Crypt => 2e7,
+            AttackModel::Apr1 => 5e8,
+            AttackModel::Sha256 => 1e11,
+            AttackModel::Md5 => 1e12,
+        }
+    }
+}
+
+impl FromStr for AttackModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "online" => Ok(AttackModel::Online),
+            "argon2id" => Ok(AttackModel::Argon2id),
+            "bcrypt" => Ok(AttackModel::Bcrypt),
+            "sha512-crypt" => Ok(AttackModel::Sha512Crypt),
+            "apr1" => Ok(AttackModel::Apr1),
+            "sha256" => Ok(AttackModel::Sha256),
+            "md5" => Ok(AttackModel::Md5),
+            _ => Err(format!("Unknown attack model '{}' (expected {})", s, AttackModel::NAMES.join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for AttackModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AttackModel::Online => "online",
+            AttackModel::Argon2id => "argon2id",
+            AttackModel::Bcrypt => "bcrypt",
+            AttackModel::Sha512Crypt => "sha512-crypt",
+            AttackModel::Apr1 => "apr1",
+            AttackModel::Sha256 => "sha256",
+            AttackModel::Md5 => "md5",
+        })
+    }
+}
+
+/// Expected seconds to find a secret of `entropy_bits` at `guesses_per_second`.
+/// On average an attacker searches half the space before hitting it.
+pub fn crack_seconds(entropy_bits: f64, guesses_per_second: f64) -> f64 {
+    2f64.powf(entropy_bits - 1.0) / guesses_per_second
+}
+
+/// A rough human reading of a duration, like `3 hours` or `20 million years`
+pub fn format_duration(seconds: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const YEAR: f64 = 365.25 * DAY;
+
+    let plural = |value: f64, unit: &str| {
+        let value = value.round();
+        format!("{} {}{}", value, unit, if value == 1.0 { "" } else { "s" })
+    };
+
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    if seconds < MINUTE {
+        return plural(seconds, "second");
+    }
+    if seconds < HOUR {
+        return plural(seconds / MINUTE, "minute");
+    }
+    if seconds < DAY {
+        return plural(seconds / HOUR, "hour");
+    }
+    if seconds < YEAR {
+        return plural(seconds / DAY, "day");
+    }
+
+    let years = seconds / YEAR;
+    if years < 1e3 {
+        return plural(years, "year");
+    }
+    for (scale, name) in [(1e3, "thousand"), (1e6, "million"), (1e9, "billion")] {
+        if years < scale * 1e3 {
+            return format!("{} {} years", (years / scale).round(), name);
+        }
+    }
+    format!("10^{} years", years.log10().floor())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weak_password() {
+        let strength = estimate("password1");
+        assert_eq!(strength.score, 0);
+        assert!(strength.guesses_log10 < 3.0);
+    }
+
+    #[test]
+    fn test_keyboard_walk() {
+        let strength = estimate("qwertyuiop");
+        assert!(strength.score <= 1);
+        assert!(strength.warning.is_some());
+    }
+
+    #[test]
+    fn test_random_password() {
+        let strength = estimate("r8$Hq)Zk2!mWv9#pLe");
+        assert_eq!(strength.score, 4);
+        assert!(strength.guesses_log10 > 10.0);
+    }
+
+    #[test]
+    fn test_long_password_is_truncated() {
+        let strength = estimate(&"aB3$".repeat(1000));
+        assert!(strength.guesses_log10 > 0.0);
+    }
+
+    #[test]
+    fn test_attack_models() {
+        for name in AttackModel::NAMES {
+            let model: AttackModel = name.parse().unwrap();
+            assert_eq!(model.to_string(), name);
+        }
+        assert!("sha3".parse::<AttackModel>().is_err());
+        assert!(AttackModel::Bcrypt.guesses_per_second() < AttackModel::Md5.guesses_per_second());
+    }
+
+    #[test]
+    fn test_crack_seconds() {
+        assert_eq!(crack_seconds(11.0, 1024.0), 1.0);
+        assert_eq!(crack_seconds(41.0, 1e12), 2f64.powi(40) / 1e12);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.2), "less than a second");
+        assert_eq!(format_duration(1.0), "1 second");
+        assert_eq!(format_duration(90.0), "2 minutes");
+        assert_eq!(format_duration(3.0 * 3600.0), "3 hours");
+        assert_eq!(format_duration(40.0 * 86400.0), "40 days");
+        assert_eq!(format_duration(5.0 * 31_557_600.0), "5 years");
+        assert_eq!(format_duration(2.5e4 * 31_557_600.0), "25 thousand years");
+        assert_eq!(format_duration(2e7 * 31_557_600.0), "20 million years");
+        assert_eq!(format_duration(3e11 * 31_557_600.0), "300 billion years");
+        assert_eq!(format_duration(4e15 * 31_557_600.0), "10^15 years");
+    }
+}
@@ -0,0 +1,235 @@
+//! Fixed-structure passwords described by a template such as `Ulll-dddd-ssss`.
+//!
+//! Each placeholder is replaced by a random character of its class:
+//!
+//! | Placeholder | Class                |
+//! |-------------|----------------------|
+//! | `U`         | uppercase letter     |
+//! | `l`         | lowercase letter     |
+//! | `d`         | digit                |
+//! | `s`         | symbol               |
+//!
+//! Any other character is copied through unchanged. Prefix a placeholder with
+//! `\` to use it literally (`\d` produces `d`).
+//!
+//! [`Template::parse_mask`] reads the same structure in the mask syntax of
+//! hashcat (`?u?l?l?l?d?d?d?s`), where `?l`, `?u`, `?d`, `?h`, `?H`, `?s` and
+//! `?a` stand for a character of their class and `??` is a literal `?`. Its
+//! `?s` is hashcat's: the space and every ASCII punctuation character.
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use zeroize::Zeroizing;
+
+use crate::{exclude_chars, PasswordError, Secret, CHARS_SYMBOLS};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Literal(char),
+    Class(Zeroizing<Vec<char>>),
+}
+
+/// A parsed password template
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Parse a template, removing any `excluded` characters from the placeholder classes
+    pub fn parse(template: &str, excluded: &[char]) -> Result<Self, PasswordError> {
+        let mut parts = Vec::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            let part = match c {
+                'U' => Part::Class(Zeroizing::new(('A'..='Z').collect())),
+                'l' => Part::Class(Zeroizing::new(('a'..='z').collect())),
+                'd' => Part::Class(Zeroizing::new(('0'..='9').collect())),
+                's' => Part::Class(Zeroizing::new(CHARS_SYMBOLS.clone())),
+                '\\' => match chars.next() {
+                    Some(escaped) => Part::Literal(escaped),
+                    None => return Err(PasswordError::InvalidTemplate(
+                        "ends with an unfinished escape (\\)".to_string()
+                    )),
+                },
+                literal => Part::Literal(literal),
+            };
+
+            let part = match part {
+                Part::Class(class) => {
+                    let class = exclude_chars(&class, excluded);
+                    if class.is_empty() {
+                        return Err(PasswordError::InvalidTemplate(format!(
+                            "every character for '{}' has been excluded", c
+                        )));
+                    }
+                    Part::Class(class)
+                }
+                literal => literal,
+            };
+
+            parts.push(part);
+        }
+
+        if parts.is_empty() {
+            return Err(PasswordError::InvalidTemplate("template is empty".to_string()));
+        }
+
+        Ok(Template { parts })
+    }
+
+    /// Parse a hashcat mask, removing any `excluded` characters from the
+    /// placeholder classes. Custom charsets (`?1` to `?4`) and `?b` aren't
+    /// supported.
+    pub fn parse_mask(mask: &str, excluded: &[char]) -> Result<Self, PasswordError> {
+        let lower = || ('a'..='z').collect::<Vec<char>>();
+        let upper = || ('A'..='Z').collect::<Vec<char>>();
+        let digits = || ('0'..='9').collect::<Vec<char>>();
+        let symbols = || (' '..='~').filter(|c| c.is_ascii_punctuation() || *c == ' ').collect::<Vec<char>>();
+
+        let mut parts = Vec::new();
+        let mut chars = mask.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '?' {
+                parts.push(Part::Literal(c));
+                continue;
+            }
+
+            let placeholder = chars.next().ok_or_else(|| PasswordError::InvalidTemplate(
+                "mask ends with an unfinished placeholder (?)".to_string()
+            ))?;
+            let class = match placeholder {
+                '?' => {
+                    parts.push(Part::Literal('?'));
+                    continue;
+                }
+                'l' => lower(),
+                'u' => upper(),
+                'd' => digits(),
+                'h' => digits().into_iter().chain('a'..='f').collect(),
+                'H' => digits().into_iter().chain('A'..='F').collect(),
+                's' => symbols(),
+                'a' => [lower(), upper(), digits(), symbols()].concat(),
+                other => return Err(PasswordError::InvalidTemplate(format!(
+                    "unsupported mask placeholder '?{}' (expected ?l, ?u, ?d, ?h, ?H, ?s, ?a or ??)", other
+                ))),
+            };
+
+            let class = exclude_chars(&class, excluded);
+            if class.is_empty() {
+                return Err(PasswordError::InvalidTemplate(format!(
+                    "every character for '?{}' has been excluded", placeholder
+                )));
+            }
+            parts.push(Part::Class(class));
+        }
+
+        if parts.is_empty() {
+            return Err(PasswordError::InvalidTemplate("mask is empty".to_string()));
+        }
+
+        Ok(Template { parts })
+    }
+
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Secret {
+        let capacity = self.parts.len() * 4;
+        let mut password = Secret::new(String::with_capacity(capacity));
+
+        for part in &self.parts {
+            password.push(match part {
+                Part::Literal(c) => *c,
+                Part::Class(class) => class[Uniform::from(0..class.len()).sample(rng)],
+            });
+        }
+
+        password
+    }
+
+    /// Entropy in bits contributed by the placeholders (literals add none)
+    pub fn entropy(&self) -> f64 {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(_) => 0.0,
+                Part::Class(class) => (class.len() as f64).log2(),
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_secure_rng, CHARS_AMBIGUOUS};
+
+    #[test]
+    fn test_template_structure() {
+        let template = Template::parse("Ulll-dddd-ssss", &[]).unwrap();
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..20 {
+            let password: Vec<char> = template.generate(&mut rng).chars().collect();
+            assert_eq!(password.len(), 14);
+            assert!(password[0].is_ascii_uppercase());
+            assert!(password[1..4].iter().all(|c| c.is_ascii_lowercase()));
+            assert_eq!(password[4], '-');
+            assert!(password[5..9].iter().all(|c| c.is_ascii_digit()));
+            assert_eq!(password[9], '-');
+            assert!(password[10..].iter().all(|c| CHARS_SYMBOLS.contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_template_escapes() {
+        let template = Template::parse("\\U\\l\\d\\s\\\\x", &[]).unwrap();
+        assert_eq!(*template.generate(&mut get_secure_rng().unwrap()), "Ulds\\x");
+        assert_eq!(template.entropy(), 0.0);
+    }
+
+    #[test]
+    fn test_template_entropy() {
+        let template = Template::parse("Ud-", &[]).unwrap();
+        assert!((template.entropy() - (26f64.log2() + 10f64.log2())).abs() < 1e-9);
+
+        let template = Template::parse("d", &CHARS_AMBIGUOUS).unwrap();
+        assert!((template.entropy() - 8f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hashcat_mask() {
+        let template = Template::parse_mask("?u?l?l?l?d?d?d?s-??", &[]).unwrap();
+        let mut rng = get_secure_rng().unwrap();
+
+        for _ in 0..20 {
+            let password: Vec<char> = template.generate(&mut rng).chars().collect();
+            assert_eq!(password.len(), 10);
+            assert!(password[0].is_ascii_uppercase());
+            assert!(password[1..4].iter().all(|c| c.is_ascii_lowercase()));
+            assert!(password[4..7].iter().all(|c| c.is_ascii_digit()));
+            assert!(password[7] == ' ' || password[7].is_ascii_punctuation());
+            assert_eq!(password[8..], ['-', '?']);
+        }
+
+        // hashcat's classes: 33 symbols, 95 printable characters, 16 hex digits
+        let template = Template::parse_mask("?s?a?h?H", &[]).unwrap();
+        let expected = 33f64.log2() + 95f64.log2() + 2.0 * 16f64.log2();
+        assert!((template.entropy() - expected).abs() < 1e-9);
+
+        let template = Template::parse_mask("?d", &CHARS_AMBIGUOUS).unwrap();
+        assert!((template.entropy() - 8f64.log2()).abs() < 1e-9);
+
+        for mask in ["", "?d?", "?1?d", "?b"] {
+            assert!(matches!(Template::parse_mask(mask, &[]), Err(PasswordError::InvalidTemplate(_))));
+        }
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(matches!(Template::parse("", &[]), Err(PasswordError::InvalidTemplate(_))));
+        assert!(matches!(Template::parse("dd\\", &[]), Err(PasswordError::InvalidTemplate(_))));
+        let digits: Vec<char> = ('0'..='9').collect();
+        assert!(matches!(Template::parse("Ud", &digits), Err(PasswordError::InvalidTemplate(_))));
+    }
+}
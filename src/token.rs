@@ -0,0 +1,362 @@
+//! Opaque random tokens (API keys, session secrets) encoded as text.
+
+use rand::Rng;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::Secret;
+
+/// Default token size in bytes (256 bits)
+pub const DEFAULT_TOKEN_BYTES: u16 = 32;
+
+/// `count` random bytes drawn from `rng`
+pub fn random_bytes<R: Rng + ?Sized>(rng: &mut R, count: usize) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(vec![0u8; count]);
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Encode bytes as lowercase hexadecimal
+pub fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize] as char);
+        hex.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    hex
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn encode_base64_with(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let group = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        // A chunk of n bytes produces n + 1 significant characters
+        for i in 0..=chunk.len() {
+            let index = (group >> (18 - 6 * i)) & 0x3f;
+            encoded.push(alphabet[index as usize] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Encode bytes as standard padded Base64 (RFC 4648 §4)
+pub fn encode_base64(bytes: &[u8]) -> String {
+    encode_base64_with(bytes, BASE64_ALPHABET, true)
+}
+
+/// Encode bytes as unpadded URL-safe Base64 (RFC 4648 §5)
+pub fn encode_base64url(bytes: &[u8]) -> String {
+    encode_base64_with(bytes, BASE64URL_ALPHABET, false)
+}
+
+/// Encode bytes as unpadded Base32 (RFC 4648 §6), the form authenticator apps expect
+pub fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(5));
+
+    for chunk in bytes.chunks(5) {
+        let mut b = [0u8; 8];
+        b[3..3 + chunk.len()].copy_from_slice(chunk);
+        let group = u64::from_be_bytes(b);
+
+        // A chunk of n bytes produces ceil(8n / 5) significant characters
+        for i in 0..(chunk.len() * 8).div_ceil(5) {
+            let index = (group >> (35 - 5 * i)) & 0x1f;
+            encoded.push(BASE32_ALPHABET[index as usize] as char);
+        }
+    }
+
+    encoded
+}
+
+/// Encode bytes as Base58 using the Bitcoin alphabet (no `0`, `O`, `I` or `l`)
+pub fn encode_base58(bytes: &[u8]) -> String {
+    // Each leading zero byte is written as a leading '1'
+    let leading_zeros = bytes.iter().take_while(|b| **b == 0).count();
+
+    // Repeated division of the big-endian number by 58, least significant digit first
+    let mut digits = Zeroizing::new(Vec::with_capacity(bytes.len() * 138 / 100 + 1));
+    for byte in &bytes[leading_zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut encoded = String::with_capacity(leading_zeros + digits.len());
+    encoded.extend(std::iter::repeat_n('1', leading_zeros));
+    encoded.extend(digits.iter().rev().map(|d| BASE58_ALPHABET[*d as usize] as char));
+    encoded
+}
+
+const BASE62_ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Codes in a recovery code set unless `--count` says otherwise
+pub const DEFAULT_RECOVERY_CODES: u32 = 10;
+
+/// Characters in a recovery code, printed as two groups (`xxxxx-xxxxx`, 50 bits)
+pub const RECOVERY_CODE_LENGTH: u16 = 10;
+
+/// Default length of an API key body in characters (about 190 bits)
+pub const DEFAULT_API_KEY_LENGTH: u16 = 32;
+
+/// Characters of the checksum appended to API keys (a Base62 CRC32)
+pub const API_KEY_CHECKSUM_LENGTH: usize = 6;
+
+/// CRC-32 (IEEE 802.3, as used by zlib and PNG)
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// The Base62 checksum of `text`, zero-padded to [`API_KEY_CHECKSUM_LENGTH`] characters
+fn api_key_checksum(text: &str) -> [u8; API_KEY_CHECKSUM_LENGTH] {
+    let mut value = crc32(text.as_bytes());
+    let mut checksum = [b'0'; API_KEY_CHECKSUM_LENGTH];
+    for digit in checksum.iter_mut().rev() {
+        *digit = BASE62_ALPHABET[(value % 62) as usize];
+        value /= 62;
+    }
+    checksum
+}
+
+/// An API key: `prefix` followed by `length` random Base62 characters and,
+/// with `checksum`, a CRC32 of everything before it. Base62 keeps keys
+/// URL-safe and selectable with a double click, and the checksum lets
+/// secret scanners reject look-alikes without a database lookup.
+pub fn api_key<R: Rng + ?Sized>(rng: &mut R, prefix: &str, length: u16, checksum: bool) -> Secret {
+    let capacity = prefix.len() + length as usize + API_KEY_CHECKSUM_LENGTH;
+    let mut key = Secret::new(String::with_capacity(capacity));
+    key.push_str(prefix);
+    for _ in 0..length {
+        key.push(BASE62_ALPHABET[rng.gen_range(0..BASE62_ALPHABET.len())] as char);
+    }
+    if checksum {
+        let digits = api_key_checksum(&key);
+        key.extend(digits.iter().map(|d| *d as char));
+    }
+    key
+}
+
+/// Whether `key` ends in a valid checksum, as written by [`api_key`]
+pub fn verify_api_key_checksum(key: &str) -> bool {
+    if !key.is_ascii() || key.len() < API_KEY_CHECKSUM_LENGTH {
+        return false;
+    }
+    let (text, checksum) = key.split_at(key.len() - API_KEY_CHECKSUM_LENGTH);
+    api_key_checksum(text) == checksum.as_bytes()
+}
+
+/// Check an API key prefix: up to 32 ASCII letters, digits and underscores
+pub fn validate_api_key_prefix(s: &str) -> Result<String, String> {
+    if s.len() > 32 || !s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+        return Err(format!(
+            "The prefix must be at most 32 letters, digits and underscores. Got: {}", s
+        ));
+    }
+    Ok(s.to_string())
+}
+
+/// Bits of randomness in a version 4 UUID (6 bits are fixed by the version and variant)
+pub const UUID_V4_ENTROPY: f64 = 122.0;
+
+/// A random (version 4, RFC 9562 variant) UUID in the canonical hyphenated form
+pub fn uuid_v4<R: Rng + ?Sized>(rng: &mut R) -> Secret {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = Zeroizing::new(encode_hex(&bytes));
+    bytes.zeroize();
+
+    let mut uuid = Secret::new(String::with_capacity(36));
+    for (i, range) in [0..8, 8..12, 12..16, 16..20, 20..32].into_iter().enumerate() {
+        if i > 0 {
+            uuid.push('-');
+        }
+        uuid.push_str(&hex[range]);
+    }
+    uuid
+}
+
+/// Alphabet of license keys and voucher codes: uppercase letters and digits
+/// without the easily confused `0 O 1 I`. It has 32 symbols, so every
+/// character carries exactly 5 bits.
+pub const LICENSE_KEY_ALPHABET: &str = "23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// Groups in a license key unless told otherwise
+pub const DEFAULT_LICENSE_KEY_GROUPS: u8 = 5;
+
+/// Characters per license key group unless told otherwise
+pub const DEFAULT_LICENSE_KEY_GROUP_SIZE: u8 = 5;
+
+/// Parse a token size in bytes (between 1 and 1024)
+pub fn validate_token_bytes(s: &str) -> Result<u16, String> {
+    let bytes: u16 = s.parse().map_err(|_| format!(
+        "The token size must be a positive number of bytes between 1 and 1024. Got: {}", s
+    ))?;
+
+    if !(1..=1024).contains(&bytes) {
+        return Err(format!(
+            "Token size must be between 1 and 1024 bytes. Got: {}", bytes
+        ));
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_encode_hex() {
+        assert_eq!(encode_hex(&[]), "");
+        assert_eq!(encode_hex(&[0x00, 0x0f, 0xa5, 0xff]), "000fa5ff");
+    }
+
+    #[test]
+    fn test_encode_base64() {
+        // RFC 4648 test vectors
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foob"), "Zm9vYg==");
+        assert_eq!(encode_base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(&[0xfb, 0xff]), "+/8=");
+    }
+
+    #[test]
+    fn test_encode_base64url() {
+        assert_eq!(encode_base64url(b"fooba"), "Zm9vYmE");
+        assert_eq!(encode_base64url(&[0xfb, 0xff]), "-_8");
+    }
+
+    #[test]
+    fn test_encode_base32() {
+        // RFC 4648 test vectors, without padding
+        assert_eq!(encode_base32(b""), "");
+        assert_eq!(encode_base32(b"f"), "MY");
+        assert_eq!(encode_base32(b"fo"), "MZXQ");
+        assert_eq!(encode_base32(b"foo"), "MZXW6");
+        assert_eq!(encode_base32(b"foob"), "MZXW6YQ");
+        assert_eq!(encode_base32(b"fooba"), "MZXW6YTB");
+        assert_eq!(encode_base32(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn test_encode_base58() {
+        assert_eq!(encode_base58(b""), "");
+        assert_eq!(encode_base58(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(encode_base58(&[0, 0, 1]), "112");
+        assert_eq!(encode_base58(&[0xff; 4]), "7YXq9G");
+    }
+
+    #[test]
+    fn test_random_bytes() {
+        let mut rng = get_secure_rng().unwrap();
+        let token = encode_hex(&random_bytes(&mut rng, 16));
+        assert_eq!(token.len(), 32);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+        assert_ne!(random_bytes(&mut rng, 16), random_bytes(&mut rng, 16));
+    }
+
+    #[test]
+    fn test_uuid_v4() {
+        let mut rng = get_secure_rng().unwrap();
+        for _ in 0..100 {
+            let uuid = uuid_v4(&mut rng);
+            let groups: Vec<&str> = uuid.split('-').collect();
+            assert_eq!(groups.iter().map(|g| g.len()).collect::<Vec<_>>(), vec![8, 4, 4, 4, 12]);
+            assert!(groups.iter().all(|g| g.chars().all(|c| c.is_ascii_hexdigit())));
+            assert!(groups[2].starts_with('4'));
+            assert!("89ab".contains(&groups[3][..1]));
+        }
+        assert_ne!(uuid_v4(&mut rng), uuid_v4(&mut rng));
+    }
+
+    #[test]
+    fn test_validate_token_bytes_input() {
+        assert!(validate_token_bytes("1").is_ok());
+        assert!(validate_token_bytes("1024").is_ok());
+        assert!(validate_token_bytes("0").is_err());
+        assert!(validate_token_bytes("1025").is_err());
+        assert!(validate_token_bytes("lots").is_err());
+    }
+
+    #[test]
+    fn test_license_key_alphabet() {
+        let mut chars: Vec<char> = LICENSE_KEY_ALPHABET.chars().collect();
+        assert_eq!(chars.len(), 32);
+        assert!(chars.iter().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+        assert!(!chars.iter().any(|c| "0O1I".contains(*c)));
+        chars.dedup();
+        assert_eq!(chars.len(), 32);
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_api_key() {
+        let mut rng = get_secure_rng().unwrap();
+        let key = api_key(&mut rng, "sk_live_", 32, false);
+        assert!(key.starts_with("sk_live_"));
+        assert_eq!(key.len(), 8 + 32);
+        assert!(key[8..].bytes().all(|b| b.is_ascii_alphanumeric()));
+
+        let key = api_key(&mut rng, "ghp_", 30, true);
+        assert_eq!(key.len(), 4 + 30 + API_KEY_CHECKSUM_LENGTH);
+        assert!(verify_api_key_checksum(&key));
+
+        let mut tampered = key.to_string();
+        tampered.replace_range(5..6, if &key[5..6] == "A" { "B" } else { "A" });
+        assert!(!verify_api_key_checksum(&tampered));
+        assert!(!verify_api_key_checksum("short"));
+    }
+
+    #[test]
+    fn test_validate_api_key_prefix() {
+        assert_eq!(validate_api_key_prefix("sk_live_").unwrap(), "sk_live_");
+        assert!(validate_api_key_prefix("").is_ok());
+        assert!(validate_api_key_prefix("sk-live").is_err());
+        assert!(validate_api_key_prefix(&"a".repeat(33)).is_err());
+    }
+}
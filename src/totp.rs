@@ -0,0 +1,168 @@
+//! TOTP (RFC 6238) shared secrets and the `otpauth://` URIs authenticator
+//! apps scan to enrol them.
+
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::token::{encode_base32, random_bytes};
+use crate::Secret;
+
+/// Default secret size: the 160 bits RFC 4226 recommends for HMAC-SHA1
+pub const DEFAULT_TOTP_BITS: u16 = 160;
+
+/// HMAC algorithm the one-time passwords are computed with
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Algorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha1" => Ok(Algorithm::Sha1),
+            "sha256" => Ok(Algorithm::Sha256),
+            "sha512" => Ok(Algorithm::Sha512),
+            _ => Err(format!("Unknown TOTP algorithm '{}' (expected sha1, sha256 or sha512)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        })
+    }
+}
+
+/// Everything besides the secret that goes into an `otpauth://` URI
+#[derive(Clone, Debug, PartialEq)]
+pub struct UriParams<'a> {
+    pub issuer: Option<&'a str>,
+    pub account: &'a str,
+    pub algorithm: Algorithm,
+    pub digits: u8,
+    pub period: u32,
+}
+
+impl Default for UriParams<'_> {
+    fn default() -> Self {
+        UriParams { issuer: None, account: "user", algorithm: Algorithm::Sha1, digits: 6, period: 30 }
+    }
+}
+
+/// A random secret of `bits` bits, Base32 encoded
+pub fn generate_secret<R: Rng + ?Sized>(rng: &mut R, bits: u16) -> Secret {
+    let bytes = random_bytes(rng, bits as usize / 8);
+    Secret::new(encode_base32(&bytes))
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// The Key URI for `secret`, in the format documented by Google Authenticator
+/// and understood by every common authenticator app
+pub fn otpauth_uri(secret: &str, params: &UriParams) -> Secret {
+    let label = match params.issuer {
+        Some(issuer) => format!("{}:{}", percent_encode(issuer), percent_encode(params.account)),
+        None => percent_encode(params.account),
+    };
+
+    let mut uri = Secret::new(format!("otpauth://totp/{}?secret={}", label, secret));
+    if let Some(issuer) = params.issuer {
+        uri.push_str(&format!("&issuer={}", percent_encode(issuer)));
+    }
+    uri.push_str(&format!(
+        "&algorithm={}&digits={}&period={}",
+        params.algorithm, params.digits, params.period
+    ));
+    uri
+}
+
+/// Parse a secret size in bits (a multiple of 8 between 80 and 512)
+pub fn validate_totp_bits(s: &str) -> Result<u16, String> {
+    let bits: u16 = s.parse().map_err(|_| format!(
+        "The secret size must be a positive number of bits between 80 and 512. Got: {}", s
+    ))?;
+
+    if !(80..=512).contains(&bits) || !bits.is_multiple_of(8) {
+        return Err(format!(
+            "Secret size must be a multiple of 8 between 80 and 512 bits. Got: {}", bits
+        ));
+    }
+
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_generate_secret() {
+        let mut rng = get_secure_rng().unwrap();
+        let secret = generate_secret(&mut rng, DEFAULT_TOTP_BITS);
+        assert_eq!(secret.len(), 32);
+        assert!(secret.chars().all(|c| c.is_ascii_uppercase() || ('2'..='7').contains(&c)));
+        assert_eq!(generate_secret(&mut rng, 80).len(), 16);
+        assert_ne!(generate_secret(&mut rng, 160), generate_secret(&mut rng, 160));
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        let uri = otpauth_uri("JBSWY3DPEHPK3PXP", &UriParams::default());
+        assert_eq!(
+            uri.as_str(),
+            "otpauth://totp/user?secret=JBSWY3DPEHPK3PXP&algorithm=SHA1&digits=6&period=30"
+        );
+
+        let params = UriParams {
+            issuer: Some("ACME Co"),
+            account: "build@acme.example",
+            algorithm: Algorithm::Sha256,
+            digits: 8,
+            period: 60,
+        };
+        assert_eq!(
+            otpauth_uri("JBSWY3DPEHPK3PXP", &params).as_str(),
+            "otpauth://totp/ACME%20Co:build%40acme.example?secret=JBSWY3DPEHPK3PXP\
+             &issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60"
+        );
+    }
+
+    #[test]
+    fn test_validate_totp_bits_input() {
+        assert_eq!(validate_totp_bits("160"), Ok(160));
+        assert!(validate_totp_bits("80").is_ok());
+        assert!(validate_totp_bits("512").is_ok());
+        assert!(validate_totp_bits("72").is_err());
+        assert!(validate_totp_bits("161").is_err());
+        assert!(validate_totp_bits("520").is_err());
+        assert!(validate_totp_bits("abc").is_err());
+    }
+
+    #[test]
+    fn test_algorithm_names() {
+        assert_eq!("SHA256".parse::<Algorithm>(), Ok(Algorithm::Sha256));
+        assert!("md5".parse::<Algorithm>().is_err());
+    }
+}
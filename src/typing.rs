@@ -0,0 +1,125 @@
+//! Typing effort (`--optimize-typing`): how awkward a password is to type on
+//! a keyboard layout, so that of several random candidates the easiest can be
+//! kept.
+//!
+//! Every character is charged for what slows touch typists down:
+//!
+//! | Cost | For                                                          |
+//! |------|--------------------------------------------------------------|
+//! | 1    | a key typed with the same hand as the one before             |
+//! | 1    | a different key typed with the same finger as the one before |
+//! | 0.5  | each row the hand moves from its last key                    |
+//! | 1    | holding Shift                                                |
+//! | 3    | a character that isn't on the layout's main rows             |
+//!
+//! The space is typed with a thumb and costs nothing. The effort is the mean
+//! cost per character, so passwords of any length compare, and lower is
+//! easier.
+
+use crate::keyboard::{Key, Layout};
+
+/// Most candidates `--optimize-typing` draws
+pub const MAX_CANDIDATES: u16 = 1024;
+
+/// Cost of a character the layout has no key for
+const OFF_LAYOUT: f64 = 3.0;
+
+/// Cost of each row a hand moves
+const ROW_TRAVEL: f64 = 0.5;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Hand {
+    Left,
+    Right,
+}
+
+/// The hand and finger (0 is the left pinky, 7 the right) that type a key.
+/// Rows are staggered so the same column is the same finger on every row.
+fn finger(key: Key) -> (Hand, u8) {
+    match key.column {
+        ..=1 => (Hand::Left, 0),
+        2 => (Hand::Left, 1),
+        3 => (Hand::Left, 2),
+        4 | 5 => (Hand::Left, 3),
+        6 | 7 => (Hand::Right, 4),
+        8 => (Hand::Right, 5),
+        9 => (Hand::Right, 6),
+        _ => (Hand::Right, 7),
+    }
+}
+
+/// Mean cost per character of typing `text` on `layout`
+pub fn effort(text: &str, layout: Layout) -> f64 {
+    let mut total = 0.0;
+    let mut count = 0;
+    let mut previous: Option<Key> = None;
+    // The last key each hand typed
+    let mut last = [None::<Key>; 2];
+
+    for c in text.chars() {
+        count += 1;
+        let Some(key) = layout.key(c) else {
+            if c != ' ' {
+                total += OFF_LAYOUT;
+            }
+            previous = None;
+            continue;
+        };
+
+        let (hand, finger_index) = finger(key);
+        if key.shifted {
+            total += 1.0;
+        }
+        if let Some(prev) = previous {
+            let (prev_hand, prev_finger) = finger(prev);
+            if prev_hand == hand {
+                total += 1.0;
+                let same_key = prev.row == key.row && prev.column == key.column;
+                if prev_finger == finger_index && !same_key {
+                    total += 1.0;
+                }
+            }
+        }
+        if let Some(from) = last[hand as usize] {
+            total += ROW_TRAVEL * (key.row - from.row).abs() as f64;
+        }
+
+        last[hand as usize] = Some(key);
+        previous = Some(key);
+    }
+
+    if count == 0 {
+        return 0.0;
+    }
+    total / count as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alternating_hands_is_easiest() {
+        let layout = Layout::Qwerty;
+        // Home row, alternating hands
+        assert_eq!(effort("fjdksl", layout), 0.0);
+        // One hand, one finger, up and down the rows
+        assert!(effort("frvfrv", layout) > effort("fjdksl", layout) + 2.0);
+        assert!(effort("FJDKSL", layout) > effort("fjdksl", layout));
+        assert!(effort("f€j", layout) > effort("f j", layout));
+        assert_eq!(effort("", layout), 0.0);
+    }
+
+    #[test]
+    fn test_costs() {
+        let layout = Layout::Qwerty;
+        // Same hand (1) and same finger (1), one row apart (0.5), over 2 characters
+        assert_eq!(effort("fr", layout), 1.25);
+        // Shift on the second key
+        assert_eq!(effort("aK", layout), 0.5);
+        // The same key twice is the same hand but not a finger change
+        assert_eq!(effort("aa", layout), 0.5);
+        // Layouts move the keys: y is top right on qwerty, bottom left on qwertz
+        assert!(effort("ya", Layout::Qwertz) > effort("ya", Layout::Qwerty));
+    }
+}
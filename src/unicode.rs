@@ -0,0 +1,105 @@
+//! Opt-in non-ASCII letters for random passwords.
+//!
+//! Each block adds its upper- and lowercase letters to the pool. Lengths and
+//! entropy always count characters, never UTF-8 bytes, so a 20 character
+//! password stays 20 characters even though it may take 40 bytes to store.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A Unicode block whose letters can be added to the pool
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnicodeBlock {
+    /// Accented Latin letters from Latin-1 Supplement (`À` to `ÿ`, without `×` and `÷`)
+    Latin1,
+    /// The Greek alphabet, without the final sigma `ς`
+    Greek,
+    /// The Russian Cyrillic alphabet, including `Ё` and `ё`
+    Cyrillic,
+}
+
+impl UnicodeBlock {
+    /// Names accepted on the command line
+    pub const NAMES: [&'static str; 3] = ["latin1", "greek", "cyrillic"];
+
+    /// The letters this block adds
+    pub fn chars(self) -> Vec<char> {
+        match self {
+            UnicodeBlock::Latin1 => ('\u{C0}'..='\u{FF}')
+                .filter(|c| !matches!(c, '×' | '÷'))
+                .collect(),
+            UnicodeBlock::Greek => ('Α'..='Ω')
+                .chain('α'..='ω')
+                .filter(|c| c.is_alphabetic() && *c != 'ς')
+                .collect(),
+            UnicodeBlock::Cyrillic => ('А'..='я').chain(['Ё', 'ё']).collect(),
+        }
+    }
+}
+
+/// Greek and Cyrillic letters that render like ASCII letters in most fonts,
+/// removed along with the ASCII ambiguous characters
+pub const HOMOGLYPHS: &str = "ΑΒΕΖΗΙΚΜΝΟΡΤΥΧοναАВЕКМНОРСТХаеорсух";
+
+impl FromStr for UnicodeBlock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "latin1" => Ok(UnicodeBlock::Latin1),
+            "greek" => Ok(UnicodeBlock::Greek),
+            "cyrillic" => Ok(UnicodeBlock::Cyrillic),
+            _ => Err(format!(
+                "Unknown Unicode block '{}' (expected {})", s, UnicodeBlock::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for UnicodeBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            UnicodeBlock::Latin1 => "latin1",
+            UnicodeBlock::Greek => "greek",
+            UnicodeBlock::Cyrillic => "cyrillic",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_from_charset, get_secure_rng, password_entropy};
+
+    #[test]
+    fn test_block_sizes() {
+        assert_eq!(UnicodeBlock::Latin1.chars().len(), 62);
+        assert_eq!(UnicodeBlock::Greek.chars().len(), 48);
+        assert_eq!(UnicodeBlock::Cyrillic.chars().len(), 66);
+
+        for name in UnicodeBlock::NAMES {
+            let block: UnicodeBlock = name.parse().unwrap();
+            assert_eq!(block.to_string(), name);
+            assert!(block.chars().iter().all(|c| !c.is_ascii() && c.is_alphabetic()));
+        }
+        assert!("hangul".parse::<UnicodeBlock>().is_err());
+    }
+
+    #[test]
+    fn test_homoglyphs_are_in_blocks() {
+        let letters: Vec<char> = [UnicodeBlock::Greek, UnicodeBlock::Cyrillic]
+            .iter()
+            .flat_map(|block| block.chars())
+            .collect();
+        assert!(HOMOGLYPHS.chars().all(|c| letters.contains(&c)), "{}", HOMOGLYPHS);
+    }
+
+    #[test]
+    fn test_length_counts_characters() {
+        let charset = UnicodeBlock::Cyrillic.chars();
+        let password = generate_from_charset(&mut get_secure_rng().unwrap(), 20, &charset).unwrap();
+        assert_eq!(password.chars().count(), 20);
+        assert_eq!(password.len(), 40);
+        assert!((password_entropy(20, charset.len()) - 20.0 * 66f64.log2()).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,179 @@
+//! Memorable usernames like `brave-otter-42`: an adjective, a noun and a
+//! number, drawn from built-in wordlists of 256 words each.
+//!
+//! Length limits are applied by only drawing from the adjective-noun pairs
+//! that fit, so every username that satisfies them is equally likely and the
+//! reported entropy is exact.
+
+use rand::Rng;
+
+use crate::{PasswordError, Secret};
+
+const ADJECTIVES: &str = include_str!("wordlists/adjectives.txt");
+const NOUNS: &str = include_str!("wordlists/nouns.txt");
+
+/// Digits in the trailing number unless told otherwise
+pub const DEFAULT_DIGITS: u8 = 2;
+
+/// Most digits the trailing number can have
+pub const MAX_DIGITS: u8 = 6;
+
+/// How a username is put together
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsernameStyle {
+    /// Placed between the words and before the number (may be empty)
+    pub separator: String,
+    /// Digits in the trailing number, zero-padded (0 for none)
+    pub digits: u8,
+    /// Capitalize each word (`BraveOtter42`)
+    pub title_case: bool,
+    pub min_length: usize,
+    pub max_length: usize,
+}
+
+impl Default for UsernameStyle {
+    fn default() -> Self {
+        UsernameStyle {
+            separator: "-".to_string(),
+            digits: DEFAULT_DIGITS,
+            title_case: false,
+            min_length: 1,
+            max_length: 32,
+        }
+    }
+}
+
+/// Check a separator for systems that restrict usernames: at most one
+/// character, and only `-`, `_` or `.`
+pub fn validate_separator(s: &str) -> Result<String, String> {
+    if s.chars().count() > 1 || !s.chars().all(|c| matches!(c, '-' | '_' | '.')) {
+        return Err(format!("The separator must be empty or one of - _ . Got: {}", s));
+    }
+    Ok(s.to_string())
+}
+
+/// Draws usernames in a fixed style
+#[derive(Clone, Debug)]
+pub struct UsernameGenerator {
+    style: UsernameStyle,
+    adjectives: Vec<&'static str>,
+    nouns: Vec<&'static str>,
+    /// Indices of every adjective-noun pair that fits the length limits
+    pairs: Vec<(u8, u8)>,
+}
+
+impl UsernameGenerator {
+    pub fn new(style: UsernameStyle) -> Result<Self, PasswordError> {
+        let adjectives: Vec<&str> = ADJECTIVES.lines().collect();
+        let nouns: Vec<&str> = NOUNS.lines().collect();
+
+        let number = if style.digits > 0 { style.separator.len() + style.digits as usize } else { 0 };
+        let fixed = style.separator.len() + number;
+        let mut pairs = Vec::new();
+        for (a, adjective) in adjectives.iter().enumerate() {
+            for (n, noun) in nouns.iter().enumerate() {
+                let length = adjective.len() + noun.len() + fixed;
+                if (style.min_length..=style.max_length).contains(&length) {
+                    pairs.push((a as u8, n as u8));
+                }
+            }
+        }
+
+        if pairs.is_empty() {
+            return Err(PasswordError::NoUsernameFits(style.min_length, style.max_length));
+        }
+
+        Ok(UsernameGenerator { style, adjectives, nouns, pairs })
+    }
+
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> Secret {
+        let (a, n) = self.pairs[rng.gen_range(0..self.pairs.len())];
+        let mut username = Secret::new(String::with_capacity(self.style.max_length));
+
+        for (i, word) in [self.adjectives[a as usize], self.nouns[n as usize]].iter().enumerate() {
+            if i > 0 {
+                username.push_str(&self.style.separator);
+            }
+            if self.style.title_case {
+                username.push_str(&word[..1].to_ascii_uppercase());
+                username.push_str(&word[1..]);
+            } else {
+                username.push_str(word);
+            }
+        }
+
+        if self.style.digits > 0 {
+            let number = rng.gen_range(0..10u32.pow(self.style.digits as u32));
+            username.push_str(&self.style.separator);
+            username.push_str(&format!("{:0width$}", number, width = self.style.digits as usize));
+        }
+
+        username
+    }
+
+    /// Entropy in bits of each username
+    pub fn entropy(&self) -> f64 {
+        (self.pairs.len() as f64).log2() + self.style.digits as f64 * 10f64.log2()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_secure_rng;
+
+    #[test]
+    fn test_wordlists() {
+        for list in [ADJECTIVES, NOUNS] {
+            let words: Vec<&str> = list.lines().collect();
+            assert_eq!(words.len(), 256);
+            assert!(words.iter().all(|word| word.len() >= 3 && word.bytes().all(|b| b.is_ascii_lowercase())));
+            assert!(words.windows(2).all(|pair| pair[0] < pair[1]), "wordlists are sorted and unique");
+        }
+    }
+
+    #[test]
+    fn test_default_style() {
+        let generator = UsernameGenerator::new(UsernameStyle::default()).unwrap();
+        assert!((generator.entropy() - (16.0 + 2.0 * 10f64.log2())).abs() < 1e-9);
+
+        let username = generator.generate(&mut get_secure_rng().unwrap());
+        let parts: Vec<&str> = username.split('-').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(ADJECTIVES.lines().any(|word| word == parts[0]));
+        assert!(NOUNS.lines().any(|word| word == parts[1]));
+        assert!(parts[2].len() == 2 && parts[2].bytes().all(|b| b.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_length_limits() {
+        let style = UsernameStyle {
+            separator: String::new(),
+            digits: 0,
+            title_case: true,
+            min_length: 8,
+            max_length: 10,
+        };
+        let generator = UsernameGenerator::new(style).unwrap();
+        assert!(generator.entropy() < 16.0);
+
+        let mut rng = get_secure_rng().unwrap();
+        for _ in 0..100 {
+            let username = generator.generate(&mut rng);
+            assert!((8..=10).contains(&username.len()), "{}", username.as_str());
+            assert!(username.bytes().all(|b| b.is_ascii_alphabetic()));
+            assert!(username.starts_with(|c: char| c.is_ascii_uppercase()));
+        }
+
+        let style = UsernameStyle { max_length: 8, ..UsernameStyle::default() };
+        assert!(matches!(UsernameGenerator::new(style), Err(PasswordError::NoUsernameFits(1, 8))));
+    }
+
+    #[test]
+    fn test_validate_separator() {
+        assert_eq!(validate_separator("_").unwrap(), "_");
+        assert_eq!(validate_separator("").unwrap(), "");
+        assert!(validate_separator("--").is_err());
+        assert!(validate_separator(" ").is_err());
+    }
+}
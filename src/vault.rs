@@ -0,0 +1,181 @@
+//! Write a generated secret into a HashiCorp Vault KV version 2 engine over
+//! its HTTP API, authenticating like the `vault` CLI does.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use zeroize::Zeroizing;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The server and token from `VAULT_ADDR` and `VAULT_TOKEN`, falling back
+/// to the token file `vault login` writes
+fn server() -> Result<(String, String), String> {
+    let addr = env::var("VAULT_ADDR")
+        .ok()
+        .filter(|addr| !addr.is_empty())
+        .ok_or("VAULT_ADDR is not set")?;
+
+    let token = env::var("VAULT_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .or_else(|| {
+            let path = env::var_os("HOME").map(|home| PathBuf::from(home).join(".vault-token"))?;
+            fs::read_to_string(path).ok().map(|token| token.trim().to_string())
+        })
+        .filter(|token| !token.is_empty())
+        .ok_or("VAULT_TOKEN is not set and there is no ~/.vault-token")?;
+
+    Ok((addr.trim_end_matches('/').to_string(), token))
+}
+
+/// The KV v2 data endpoint of `path` in the engine mounted at `mount`
+fn data_url(addr: &str, mount: &str, path: &str) -> String {
+    format!("{}/v1/{}/data/{}", addr, mount, path)
+}
+
+fn request(method: &str, url: &str, token: &str) -> ureq::Request {
+    let request = ureq::request(method, url).timeout(TIMEOUT).set("X-Vault-Token", token);
+    match env::var("VAULT_NAMESPACE") {
+        Ok(namespace) if !namespace.is_empty() => request.set("X-Vault-Namespace", &namespace),
+        _ => request,
+    }
+}
+
+/// Vault's own explanation of a failed request
+fn describe(err: ureq::Error) -> String {
+    match err {
+        ureq::Error::Status(code, response) => {
+            let errors = response
+                .into_string()
+                .ok()
+                .and_then(|body| serde_json::from_str::<Value>(&body).ok())
+                .and_then(|body| body["errors"].as_array().cloned())
+                .unwrap_or_default();
+            let messages: Vec<&str> = errors.iter().filter_map(Value::as_str).collect();
+            if messages.is_empty() {
+                format!("HTTP {}", code)
+            } else {
+                format!("HTTP {}: {}", code, messages.join("; "))
+            }
+        }
+        err => err.to_string(),
+    }
+}
+
+/// The JSON body of a successful response
+fn json_body(response: ureq::Response) -> Result<Value, String> {
+    let body = response.into_string().map_err(|err| err.to_string())?;
+    serde_json::from_str(&body).map_err(|err| err.to_string())
+}
+
+/// The keys and version of a KV v2 read response. A deleted or destroyed
+/// current version has no data but still counts for check-and-set.
+fn current(response: &Value) -> (Map<String, Value>, u64) {
+    let data = response["data"]["data"].as_object().cloned().unwrap_or_default();
+    let version = response["data"]["metadata"]["version"].as_u64().unwrap_or(0);
+    (data, version)
+}
+
+#[derive(Serialize)]
+struct WriteOptions {
+    cas: u64,
+}
+
+/// A value of the written secret: one kept from the current version, or
+/// the new one, borrowed so it is only ever copied into the zeroized body
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Field<'a> {
+    Kept(&'a Value),
+    Secret(&'a str),
+}
+
+#[derive(Serialize)]
+struct WriteBody<'a> {
+    options: WriteOptions,
+    data: BTreeMap<&'a str, Field<'a>>,
+}
+
+/// The body that sets `key` to `value`, keeping the other keys of `data`,
+/// and is only accepted while the secret is still at version `cas`. It is
+/// serialized straight into a buffer that is wiped when dropped.
+fn write_body(data: &Map<String, Value>, key: &str, value: &str, cas: u64) -> Zeroizing<Vec<u8>> {
+    let mut fields: BTreeMap<&str, Field> = data.iter().map(|(name, kept)| (name.as_str(), Field::Kept(kept))).collect();
+    fields.insert(key, Field::Secret(value));
+
+    // Room for every character of the secret escaped as \uXXXX, so the
+    // buffer never grows and leaves a copy behind
+    let kept = serde_json::to_vec(data).map_or(0, |json| json.len());
+    let mut body = Zeroizing::new(Vec::with_capacity(kept + (key.len() + value.len()) * 6 + 64));
+    serde_json::to_writer(&mut *body, &WriteBody { options: WriteOptions { cas }, data: fields })
+        .expect("the write body is always serializable");
+    body
+}
+
+/// Set `key` to `value` in the secret at `path`, keeping its other keys,
+/// and return the new version. The write is check-and-set against the
+/// version read first, or against `cas` when it is given (0 when the secret
+/// must not exist yet), so a concurrent change makes it fail instead of
+/// being overwritten.
+pub fn write(mount: &str, path: &str, key: &str, value: &str, cas: Option<u64>) -> Result<u64, String> {
+    let (addr, token) = server()?;
+    let url = data_url(&addr, mount, path);
+
+    let (data, version) = match request("GET", &url, &token).call() {
+        Ok(response) => current(&json_body(response)?),
+        Err(ureq::Error::Status(404, _)) => (Map::new(), 0),
+        Err(err) => return Err(describe(err)),
+    };
+
+    let body = write_body(&data, key, value, cas.unwrap_or(version));
+    let response = request("POST", &url, &token)
+        .set("Content-Type", "application/json")
+        .send_bytes(&body)
+        .map_err(describe)?;
+    let response = json_body(response)?;
+
+    response["data"]["version"].as_u64().ok_or_else(|| "no version in Vault's response".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_data_url() {
+        assert_eq!(
+            data_url("https://vault.example.com:8200", "secret", "prod/db"),
+            "https://vault.example.com:8200/v1/secret/data/prod/db"
+        );
+    }
+
+    #[test]
+    fn test_read_and_write_bodies() {
+        let response = json!({
+            "data": {
+                "data": { "username": "app", "password": "old" },
+                "metadata": { "version": 3, "deletion_time": "" }
+            }
+        });
+        let (data, version) = current(&response);
+        assert_eq!(version, 3);
+
+        let body = write_body(&data, "password", "new\"", version);
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, json!({
+            "options": { "cas": 3 },
+            "data": { "username": "app", "password": "new\"" }
+        }));
+
+        // A deleted current version keeps its number
+        let deleted = json!({ "data": { "data": null, "metadata": { "version": 4 } } });
+        assert_eq!(current(&deleted), (Map::new(), 4));
+    }
+}
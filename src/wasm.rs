@@ -0,0 +1,37 @@
+//! JavaScript bindings, built with the `wasm` feature.
+//!
+//! ```js
+//! import init, { Options, generate } from "./pkg/password.js";
+//!
+//! await init();
+//! const options = new Options();
+//! options.length = 24;
+//! options.excludeAmbiguous = true;
+//! const password = generate(options);
+//! ```
+//!
+//! Each call seeds a fresh ChaCha20 generator from `crypto.getRandomValues`.
+//! The returned string is copied into the JavaScript heap, where it can't be
+//! wiped; only the Rust-side buffers are zeroized.
+
+use wasm_bindgen::prelude::*;
+
+use crate::options::{self, Options};
+
+#[wasm_bindgen]
+impl Options {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Options {
+        Options::default()
+    }
+}
+
+/// Generate a password, passphrase or pronounceable password. Throws when
+/// the length isn't between 1 and [`MAX_LENGTH_LIMIT`](crate::MAX_LENGTH_LIMIT), the
+/// word count isn't between 1 and 64 or the options conflict, before
+/// anything is allocated.
+#[wasm_bindgen]
+pub fn generate(options: &Options) -> Result<String, JsError> {
+    let secret = options::generate(options)?;
+    Ok(secret.as_str().to_owned())
+}
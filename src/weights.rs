@@ -0,0 +1,274 @@
+//! Weighted character classes (`--weights letters=4,digits=1,symbols=1`).
+//!
+//! Each position first picks a class in proportion to its weight, then a
+//! character uniformly from the pool's members of that class. Passwords lean
+//! towards the heavier classes, which are easier to type on a phone, while
+//! staying uniformly random within each class. The entropy of a position is
+//! the entropy of the class choice plus the expected entropy of the character
+//! within the chosen class, which is below `log2(pool size)` unless the
+//! weights are proportional to the class sizes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+use zeroize::Zeroizing;
+
+use crate::policy::{Policy, MAX_ATTEMPTS};
+use crate::{PasswordError, Secret};
+
+/// Largest weight accepted for a class
+pub const MAX_WEIGHT: u32 = 1000;
+
+/// A class of characters that can be weighted. Anything that isn't a letter
+/// or digit, the space and emoji included, counts as a symbol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeightClass {
+    Letters,
+    Digits,
+    Symbols,
+}
+
+impl WeightClass {
+    /// Names accepted by `--weights`
+    pub const NAMES: [&'static str; 3] = ["letters", "digits", "symbols"];
+    const ALL: [WeightClass; 3] = [WeightClass::Letters, WeightClass::Digits, WeightClass::Symbols];
+
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            WeightClass::Letters => c.is_alphabetic(),
+            WeightClass::Digits => c.is_numeric(),
+            WeightClass::Symbols => !c.is_alphanumeric(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+}
+
+/// Relative weight of each class. A class left out of `--weights` keeps a
+/// weight of 1, and a weight of 0 leaves the class out of the password.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weights {
+    weights: [u32; 3],
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights { weights: [1; 3] }
+    }
+}
+
+impl Weights {
+    pub fn get(&self, class: WeightClass) -> u32 {
+        self.weights[class as usize]
+    }
+}
+
+impl FromStr for Weights {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Weights::default();
+        let mut seen = Vec::new();
+
+        for entry in s.split(',') {
+            let (name, weight) = entry.split_once('=')
+                .ok_or_else(|| format!("Expected CLASS=WEIGHT, got '{}'", entry))?;
+            let class = WeightClass::ALL.into_iter()
+                .find(|class| class.name() == name.trim().to_ascii_lowercase())
+                .ok_or_else(|| format!(
+                    "Unknown class '{}' (expected {})", name, WeightClass::NAMES.join(", ")
+                ))?;
+            if seen.contains(&class) {
+                return Err(format!("The weight of {} is given twice", class.name()));
+            }
+            seen.push(class);
+
+            let weight: u32 = weight.trim().parse()
+                .map_err(|_| format!("The weight of {} must be a whole number. Got: {}", class.name(), weight))?;
+            if weight > MAX_WEIGHT {
+                return Err(format!("The weight of {} must be at most {}. Got: {}", class.name(), MAX_WEIGHT, weight));
+            }
+            weights.weights[class as usize] = weight;
+        }
+
+        if weights.weights.iter().all(|&weight| weight == 0) {
+            return Err("At least one class needs a weight above 0".to_string());
+        }
+
+        Ok(weights)
+    }
+}
+
+impl fmt::Display for Weights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let entries: Vec<String> = WeightClass::ALL.iter()
+            .map(|class| format!("{}={}", class.name(), self.get(*class)))
+            .collect();
+        write!(f, "{}", entries.join(","))
+    }
+}
+
+/// A character pool split into weighted classes
+#[derive(Clone, Debug)]
+pub struct WeightedCharset {
+    /// Every character that can be drawn
+    chars: Zeroizing<Vec<char>>,
+    /// The weight and members of each class that can be drawn from
+    pools: Vec<(u32, Zeroizing<Vec<char>>)>,
+    weights: Weights,
+}
+
+impl WeightedCharset {
+    /// Split `charset` into the weighted classes. Classes with no members in
+    /// the pool, or a weight of 0, are dropped.
+    pub fn new(charset: &[char], weights: Weights) -> Result<Self, PasswordError> {
+        let mut chars = Zeroizing::new(Vec::new());
+        let mut pools = Vec::new();
+
+        for class in WeightClass::ALL {
+            let weight = weights.get(class);
+            let mut members = Zeroizing::new(Vec::new());
+            members.extend(charset.iter().filter(|c| class.contains(**c)));
+            if weight > 0 && !members.is_empty() {
+                chars.extend(members.iter());
+                pools.push((weight, members));
+            }
+        }
+
+        if pools.is_empty() {
+            return Err(PasswordError::EmptyCharacterSet);
+        }
+
+        Ok(WeightedCharset { chars, pools, weights })
+    }
+
+    /// Every character that can be drawn
+    pub fn chars(&self) -> &[char] {
+        &self.chars
+    }
+
+    pub fn weights(&self) -> Weights {
+        self.weights
+    }
+
+    /// Entropy in bits of a single position
+    pub fn entropy_per_char(&self) -> f64 {
+        let total: u32 = self.pools.iter().map(|(weight, _)| weight).sum();
+        self.pools.iter()
+            .map(|(weight, members)| {
+                let p = *weight as f64 / total as f64;
+                p * ((members.len() as f64).log2() - p.log2())
+            })
+            .sum()
+    }
+
+    /// Entropy in bits of a password of `length` characters
+    pub fn entropy(&self, length: u32) -> f64 {
+        length as f64 * self.entropy_per_char()
+    }
+
+    /// Generate `length` characters, picking each one's class by weight
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R, length: u32) -> Secret {
+        let total: u32 = self.pools.iter().map(|(weight, _)| weight).sum();
+        let mut password = Secret::new(String::with_capacity(length as usize));
+
+        for _ in 0..length {
+            let mut pick = rng.gen_range(0..total);
+            let members = self.pools.iter()
+                .find(|(weight, _)| {
+                    let found = pick < *weight;
+                    pick = pick.saturating_sub(*weight);
+                    found
+                })
+                .map(|(_, members)| members)
+                .expect("the pick is below the total weight");
+            password.push(members[rng.gen_range(0..members.len())]);
+        }
+
+        password
+    }
+}
+
+/// Generate `length` weighted characters that satisfy `policy`, rejecting
+/// candidates until one does
+pub fn generate_with_policy<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: u32,
+    charset: &WeightedCharset,
+    policy: &Policy
+) -> Result<Secret, PasswordError> {
+    policy.validate(length, charset.chars())?;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let password = charset.generate(rng, length);
+        if policy.is_satisfied(&password) {
+            return Ok(password);
+        }
+    }
+
+    Err(PasswordError::PolicyUnsatisfiable(MAX_ATTEMPTS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_charset, get_secure_rng, password_entropy};
+
+    #[test]
+    fn test_parse_weights() {
+        let weights: Weights = "letters=4,digits=1,symbols=1".parse().unwrap();
+        assert_eq!(weights.get(WeightClass::Letters), 4);
+        assert_eq!(weights.to_string(), "letters=4,digits=1,symbols=1");
+
+        // Unnamed classes keep a weight of 1
+        let weights: Weights = "symbols=0".parse().unwrap();
+        assert_eq!(weights.to_string(), "letters=1,digits=1,symbols=0");
+
+        assert!("letters".parse::<Weights>().is_err());
+        assert!("vowels=2".parse::<Weights>().is_err());
+        assert!("letters=-1".parse::<Weights>().is_err());
+        assert!("letters=1001".parse::<Weights>().is_err());
+        assert!("letters=2,letters=3".parse::<Weights>().is_err());
+        assert!("letters=0,digits=0,symbols=0".parse::<Weights>().is_err());
+    }
+
+    #[test]
+    fn test_entropy() {
+        let charset = build_charset(true, false, false);
+
+        // Weights proportional to the class sizes are the same as uniform
+        let proportional = format!("letters=52,digits=10,symbols={}", charset.len() - 62);
+        let weighted = WeightedCharset::new(&charset, proportional.parse().unwrap()).unwrap();
+        assert!((weighted.entropy(20) - password_entropy(20, charset.len())).abs() < 1e-9);
+
+        // Any other weighting costs entropy
+        let weighted = WeightedCharset::new(&charset, "letters=4".parse().unwrap()).unwrap();
+        assert!(weighted.entropy_per_char() < (charset.len() as f64).log2());
+
+        // A class on its own is a uniform pool
+        let weighted = WeightedCharset::new(&charset, "letters=0,symbols=0".parse().unwrap()).unwrap();
+        assert_eq!(weighted.chars().len(), 10);
+        assert!((weighted.entropy_per_char() - 10f64.log2()).abs() < 1e-9);
+
+        let digits: Vec<char> = ('0'..='9').collect();
+        assert!(WeightedCharset::new(&digits, "digits=0".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_generate_follows_weights() {
+        let charset = build_charset(true, false, false);
+        let weighted = WeightedCharset::new(&charset, "letters=8,digits=1,symbols=1".parse().unwrap()).unwrap();
+        let password = weighted.generate(&mut get_secure_rng().unwrap(), 10_000);
+        assert_eq!(password.chars().count(), 10_000);
+
+        let letters = password.chars().filter(|c| c.is_alphabetic()).count();
+        assert!((7_500..8_500).contains(&letters), "{} letters", letters);
+
+        let policy = Policy { min_symbols: 2, ..Policy::default() };
+        let password = generate_with_policy(&mut get_secure_rng().unwrap(), 12, &weighted, &policy).unwrap();
+        assert!(password.chars().filter(|c| !c.is_alphanumeric()).count() >= 2);
+    }
+}
@@ -0,0 +1,49 @@
+//! Wi-Fi pre-shared keys and the `WIFI:` payload phones scan to join a
+//! network.
+//!
+//! A WPA2/WPA3-Personal key is either a passphrase of 8 to 63 printable ASCII
+//! characters or a raw 256-bit key written as 64 hex digits.
+
+use crate::Secret;
+
+/// Longest passphrase a WPA2/WPA3-Personal network accepts
+pub const MAX_PASSPHRASE_LENGTH: usize = 63;
+
+/// Length of a raw pre-shared key in hex digits
+pub const HEX_KEY_LENGTH: usize = 64;
+
+/// Backslash-escape the characters the `WIFI:` format reserves
+fn escape(value: &str, escaped: &mut Secret) {
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+}
+
+/// The `WIFI:T:WPA;S:<ssid>;P:<key>;;` payload that camera apps on Android
+/// and iOS offer to join. `T:WPA` covers both WPA2 and WPA3-Personal.
+pub fn qr_payload(ssid: &str, key: &str) -> Secret {
+    let mut payload = Secret::new(String::with_capacity(ssid.len() * 2 + key.len() * 2 + 20));
+    payload.push_str("WIFI:T:WPA;S:");
+    escape(ssid, &mut payload);
+    payload.push_str(";P:");
+    escape(key, &mut payload);
+    payload.push_str(";;");
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_payload() {
+        assert_eq!(qr_payload("Home", "hT7kPq2M").as_str(), "WIFI:T:WPA;S:Home;P:hT7kPq2M;;");
+        assert_eq!(
+            qr_payload("Cafe; guests", r#"a:b,c\d"e"#).as_str(),
+            r#"WIFI:T:WPA;S:Cafe\; guests;P:a\:b\,c\\d\"e;;"#
+        );
+    }
+}